@@ -0,0 +1,180 @@
+//! Command Audit Log
+//!
+//! Append-only record of every URScript command the stream sent (or tried to
+//! send) to the robot, with its outcome and duration. Entries go to a rotating
+//! JSON lines file and a bounded in-memory history served by `@history`.
+//! Audit failures are logged and never affect the command itself.
+
+use crate::config::AuditConfig;
+use crate::json_output::FailureReason;
+use crate::stream::CommandStatus;
+use crate::recording::RotatingFile;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tracing::error;
+
+/// One executed command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditEntry {
+    /// When the command finished (Unix epoch seconds)
+    pub timestamp: f64,
+    /// Sequence number from the command's `accepted` event
+    pub sequence: u32,
+    pub command: String,
+    /// Interpreter ID (0 if the command never got one)
+    pub command_id: u32,
+    /// "completed", "rejected" or "failed"
+    pub result: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<FailureReason>,
+    pub duration_ms: f64,
+    /// Part of `duration_ms` before the interpreter accepted the command
+    /// (pre-checks, waiting for the controller, reconnect retries)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wait_ms: Option<f64>,
+    /// Part of `duration_ms` from the interpreter accepting the command until it finished
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exec_ms: Option<f64>,
+}
+
+impl AuditEntry {
+    /// `sent_after` is how long into `elapsed` the command reached the interpreter (None if it never did)
+    pub fn new(sequence: u32, command: &str, command_id: u32, status: &CommandStatus, elapsed: Duration, sent_after: Option<Duration>) -> Self {
+        let (result, failure_reason) = match status {
+            CommandStatus::Completed => ("completed", None),
+            CommandStatus::Sent => ("sent", None),
+            CommandStatus::Failed(reason @ (FailureReason::Rejected { .. } | FailureReason::BufferFull { .. })) => ("rejected", Some(reason.clone())),
+            CommandStatus::Failed(reason) => ("failed", Some(reason.clone())),
+        };
+
+        Self {
+            timestamp: crate::json_output::current_timestamp(),
+            sequence,
+            command: command.to_string(),
+            command_id,
+            result: result.to_string(),
+            failure_reason,
+            duration_ms: elapsed.as_secs_f64() * 1000.0,
+            wait_ms: sent_after.map(|wait| wait.as_secs_f64() * 1000.0),
+            exec_ms: sent_after.map(|wait| elapsed.saturating_sub(wait).as_secs_f64() * 1000.0),
+        }
+    }
+}
+
+/// Audit file writer plus the most recent entries in memory
+pub struct AuditLog {
+    file: Option<RotatingFile>,
+    history: VecDeque<AuditEntry>,
+    history_size: usize,
+}
+
+impl AuditLog {
+    /// Open the audit file described by `config`
+    ///
+    /// If the file can't be opened the error is logged and only the in-memory
+    /// history is kept.
+    pub fn new(config: &AuditConfig) -> Self {
+        let file = match RotatingFile::open(&config.path, config.max_file_bytes, config.max_files()) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                error!("Audit log file disabled: {:#}", e);
+                None
+            }
+        };
+
+        Self {
+            file,
+            history: VecDeque::with_capacity(config.history_size()),
+            history_size: config.history_size(),
+        }
+    }
+
+    /// Append an entry; a failed write disables the file, not the command
+    pub fn record(&mut self, entry: AuditEntry) {
+        if let Some(file) = &mut self.file {
+            // Flush per entry so the log survives a crash
+            let written = serde_json::to_string(&entry)
+                .map_err(anyhow::Error::from)
+                .and_then(|line| file.write_line(&line))
+                .and_then(|_| file.flush());
+            if let Err(e) = written {
+                error!("Audit log write failed, disabling file: {:#}", e);
+                self.file = None;
+            }
+        }
+
+        if self.history.len() == self.history_size {
+            self.history.pop_front();
+        }
+        if self.history_size > 0 {
+            self.history.push_back(entry);
+        }
+    }
+
+    /// The last `limit` entries, oldest first
+    pub fn recent(&self, limit: usize) -> Vec<&AuditEntry> {
+        self.history.iter().skip(self.history.len().saturating_sub(limit)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(sequence: u32, status: CommandStatus) -> AuditEntry {
+        AuditEntry::new(sequence, "movej([0,0,0,0,0,0])", sequence + 100, &status, Duration::from_millis(250), Some(Duration::from_millis(10)))
+    }
+
+    #[test]
+    fn test_records_file_and_bounded_history() {
+        let path = std::env::temp_dir().join(format!("urd-audit-{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let config = AuditConfig {
+            path: path.display().to_string(),
+            max_file_bytes: None,
+            max_files: None,
+            history_size: Some(2),
+        };
+
+        let mut audit = AuditLog::new(&config);
+        audit.record(entry(1, CommandStatus::Completed));
+        audit.record(entry(2, CommandStatus::Failed(FailureReason::Rejected { raw_reply: "error".to_string() })));
+        audit.record(entry(3, CommandStatus::Failed(FailureReason::Timeout { seconds: 5.0 })));
+
+        let recent = audit.recent(10);
+        assert_eq!(recent.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(recent[0].result, "rejected");
+        assert_eq!(recent[1].result, "failed");
+        assert_eq!(audit.recent(1)[0].sequence, 3);
+
+        // The file keeps every entry, not just the history
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["result"], "completed");
+        assert_eq!(lines[0]["duration_ms"], 250.0);
+        assert_eq!(lines[0]["wait_ms"], 10.0);
+        assert_eq!(lines[0]["exec_ms"], 240.0);
+        assert!(lines[0].get("failure_reason").is_none());
+        assert_eq!(lines[1]["failure_reason"]["kind"], "rejected");
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_unwritable_file_keeps_history() {
+        let config = AuditConfig {
+            path: "/nonexistent-dir/audit.jsonl".to_string(),
+            max_file_bytes: None,
+            max_files: None,
+            history_size: None,
+        };
+
+        let mut audit = AuditLog::new(&config);
+        audit.record(entry(1, CommandStatus::Completed));
+        assert_eq!(audit.recent(10).len(), 1);
+    }
+}
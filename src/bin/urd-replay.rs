@@ -0,0 +1,74 @@
+//! RTDE Replay
+//!
+//! Feeds a recorded RTDE stream (see `recording` in the daemon config) back
+//! through the controller's monitoring output at the recorded rate, producing
+//! the same JSON events the daemon printed - without a robot. Publish rate
+//! limits run on each frame's recorded `stime`, so `--speed` only changes
+//! pacing, not which events come out.
+
+use urd::{RobotController, RtdeReplay};
+use anyhow::{Context, Result};
+use tracing::info;
+use clap::Parser;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(name = "urd-replay")]
+#[command(about = "Replay a recorded RTDE stream through URD's monitoring output")]
+#[command(version)]
+struct Args {
+    /// Recording file written by the daemon
+    file: String,
+
+    /// Path to the daemon configuration file (publishing settings)
+    #[arg(short, long)]
+    config: Option<String>,
+
+    /// Playback speed multiplier (0 replays as fast as possible)
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config_path = args.config
+        .clone()
+        .or_else(|| std::env::var("DEFAULT_CONFIG_PATH").ok())
+        .unwrap_or_else(|| "config/default_config.yaml".to_string());
+
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .with_level(true)
+        .with_writer(std::io::stderr)
+        .init();
+
+    info!("Replaying {} with config {}", args.file, config_path);
+
+    // Only the publishing settings are used - no connection is made
+    let mut controller = RobotController::new_with_config(&config_path)
+        .context("Failed to create robot controller")?;
+    controller.enable_monitor_output();
+
+    let mut previous_stime = None;
+    let mut frames = 0;
+
+    for frame in RtdeReplay::open(&args.file)? {
+        let frame = frame?;
+
+        // Sleep for the recorded gap between packages
+        if let Some(previous) = previous_stime {
+            let gap: f64 = frame.stime - previous;
+            if args.speed > 0.0 && gap > 0.0 {
+                tokio::time::sleep(Duration::from_secs_f64(gap / args.speed)).await;
+            }
+        }
+        previous_stime = Some(frame.stime);
+
+        controller.process_rtde_package(&frame.data, frame.stime);
+        frames += 1;
+    }
+
+    info!("Replayed {} frames", frames);
+    Ok(())
+}
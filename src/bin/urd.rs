@@ -6,7 +6,7 @@
 //! - Sequential execution with completion tracking
 //! - Buffer management and cleanup
 
-use urd::{RobotController, CommandStream};
+use urd::{RobotController, CommandStream, CommandMetrics, ConfigError, DaemonConfig};
 use anyhow::{Context, Result};
 use tracing::{info, error};
 use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
@@ -20,6 +20,35 @@ struct Args {
     /// Path to the daemon configuration file
     #[arg(short, long)]
     config: Option<String>,
+    
+    /// Fail a command if it hasn't finished executing after this many seconds
+    /// (overrides interpreter.execution_timeout_seconds)
+    #[arg(long)]
+    timeout_secs: Option<u64>,
+    
+    /// Check a URScript file without connecting to the robot, print the report and exit
+    #[arg(long, value_name = "FILE")]
+    validate: Option<String>,
+    
+    /// Validate the configuration file without connecting to the robot and exit
+    #[arg(long)]
+    check_config: bool,
+    
+    /// Execute a URScript file instead of reading stdin, then exit
+    #[arg(long, value_name = "FILE")]
+    script: Option<String>,
+    
+    /// With --script, submit the whole file as one command (as @group) instead of line by line
+    #[arg(long, requires = "script")]
+    group: bool,
+    
+    /// Indent JSON events on stdout instead of one event per line
+    #[arg(long, conflicts_with = "quiet")]
+    pretty: bool,
+    
+    /// Don't write JSON events to stdout (logs still go to stderr)
+    #[arg(short, long)]
+    quiet: bool,
 }
 
 impl Args {
@@ -35,8 +64,40 @@ impl Args {
 async fn main() -> Result<()> {
     // Parse command line arguments
     let args = Args::parse();
+    
+    // Dry run: pure analysis, never touches the robot
+    if let Some(path) = &args.validate {
+        let script = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path))?;
+        let report = urd::validate_urscript(&script);
+        println!("{}", serde_json::to_string(&report)?);
+        std::process::exit(if report.is_valid() { 0 } else { 1 });
+    }
+    
     let config_path = args.get_config_path();
     
+    if args.check_config {
+        let errors = DaemonConfig::load_from_path(&config_path)
+            .map_err(|e| vec![ConfigError { field: "file".to_string(), message: e.to_string() }])
+            .and_then(|config| config.validate())
+            .err()
+            .unwrap_or_default();
+        let report = serde_json::json!({
+            "type": "config_check",
+            "path": config_path,
+            "valid": errors.is_empty(),
+            "errors": errors,
+        });
+        println!("{}", report);
+        std::process::exit(if errors.is_empty() { 0 } else { 1 });
+    }
+    
+    if args.quiet {
+        urd::json_output::set_output_format(urd::json_output::OutputFormat::Quiet);
+    } else if args.pretty {
+        urd::json_output::set_output_format(urd::json_output::OutputFormat::Pretty);
+    }
+    
     // Initialize tracing subscriber
     std::env::set_var("RUST_LOG", "info");
     tracing_subscriber::fmt()
@@ -55,8 +116,19 @@ async fn main() -> Result<()> {
     let mut controller = RobotController::new_with_config(&config_path)
         .context("Failed to create robot controller")?;
     
+    // Refuse to start on values that would misbehave at runtime
+    if let Err(errors) = controller.daemon_config().validate() {
+        for e in &errors {
+            error!("Invalid configuration: {}", e);
+        }
+        anyhow::bail!("{} invalid configuration value(s) in {}", errors.len(), config_path);
+    }
+    
     // Get monitoring setting from config
     let enable_monitoring = controller.daemon_config().command.monitor_execution;
+    let heartbeat_interval = controller.daemon_config().publishing.heartbeat_interval();
+    #[cfg(feature = "metrics")]
+    let metrics_address = controller.daemon_config().metrics.as_ref().map(|m| m.bind_address.clone());
     
     // Perform full initialization sequence
     match controller.initialize(enable_monitoring).await {
@@ -73,27 +145,71 @@ async fn main() -> Result<()> {
         }
     }
     
+    // Publish state transitions (reconnects, emergency halts) as they happen
+    let state_handle = {
+        let receiver = controller.subscribe_state();
+        tokio::spawn(async move {
+            run_state_events(receiver).await
+        })
+    };
+    
     // Create shared controller for monitoring and command stream
     let controller = Arc::new(tokio::sync::Mutex::new(controller));
     let shutdown_signal = Arc::new(AtomicBool::new(false));
     
-    // Spawn monitoring task if monitoring is enabled
-    let monitoring_handle = if enable_monitoring {
+    // Monitoring task; idles until monitoring is enabled (at startup or by @monitoring on)
+    let monitoring_handle = {
         let controller_clone = Arc::clone(&controller);
         let shutdown_clone = Arc::clone(&shutdown_signal);
         
-        Some(tokio::spawn(async move {
+        tokio::spawn(async move {
             run_monitoring_loop(controller_clone, shutdown_clone).await
-        }))
-    } else {
-        None
+        })
     };
     
+    // Dedicated emergency abort channel that doesn't wait on the command stream
+    #[cfg(unix)]
+    let abort_handle = {
+        let controller_clone = Arc::clone(&controller);
+        let handle = controller.lock().await.abort_handle();
+        tokio::spawn(async move {
+            run_abort_listener(controller_clone, handle).await
+        })
+    };
+    
+    // Command execution statistics, scraped over HTTP with the metrics feature
+    let metrics = Arc::new(CommandMetrics::new());
+    #[cfg(feature = "metrics")]
+    let metrics_handle = metrics_address.map(|address| {
+        let metrics_clone = Arc::clone(&metrics);
+        let controller_clone = Arc::clone(&controller);
+        tokio::spawn(async move {
+            if let Err(e) = urd::metrics::serve(address, metrics_clone, controller_clone).await {
+                error!("Metrics endpoint failed: {}", e);
+            }
+        })
+    });
+    
+    // Liveness events so clients can detect a dead daemon
+    let heartbeat_handle = heartbeat_interval.map(|interval| {
+        let controller_clone = Arc::clone(&controller);
+        let metrics_clone = Arc::clone(&metrics);
+        tokio::spawn(async move {
+            run_heartbeat(controller_clone, metrics_clone, interval).await
+        })
+    });
+    
     // Create command stream with shared shutdown signal
-    let mut stream = CommandStream::new_with_shared_controller(controller.clone(), shutdown_signal.clone());
+    let mut stream = CommandStream::new_with_shared_controller(controller.clone(), shutdown_signal.clone())
+        .with_command_timeout(args.timeout_secs.map(std::time::Duration::from_secs))
+        .with_metrics(metrics);
     
     // Run command stream (now handles Ctrl+C internally for immediate abort)
-    match stream.run().await {
+    let result = match &args.script {
+        Some(path) => stream.run_script(path, args.group).await,
+        None => stream.run().await,
+    };
+    match result {
         Ok(_) => {
             info!("Command stream completed normally");
         }
@@ -101,17 +217,23 @@ async fn main() -> Result<()> {
             error!("Command stream error: {}", e);
             // Signal monitoring to stop
             shutdown_signal.store(true, Ordering::Relaxed);
-            if let Some(handle) = monitoring_handle {
-                let _ = handle.await;
-            }
+            let _ = monitoring_handle.await;
             return Err(e);
         }
     }
     
     // Signal monitoring to stop
     shutdown_signal.store(true, Ordering::Relaxed);
-    if let Some(handle) = monitoring_handle {
-        let _ = handle.await;
+    let _ = monitoring_handle.await;
+    #[cfg(unix)]
+    abort_handle.abort();
+    state_handle.abort();
+    if let Some(handle) = heartbeat_handle {
+        handle.abort();
+    }
+    #[cfg(feature = "metrics")]
+    if let Some(handle) = metrics_handle {
+        handle.abort();
     }
     
     // Graceful shutdown
@@ -123,82 +245,170 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn run_monitoring_loop(
+/// Send an emergency abort whenever SIGUSR1 is received
+/// 
+/// Unlike Ctrl+C this leaves the daemon running, so the robot can be
+/// recovered with @reconnect afterwards. The halt goes out through the
+/// `AbortHandle` at once; only the state change waits for the controller.
+#[cfg(unix)]
+async fn run_abort_listener(
     controller: Arc<tokio::sync::Mutex<RobotController>>,
-    shutdown_signal: Arc<AtomicBool>
+    abort_handle: urd::AbortHandle
 ) -> Result<()> {
-    use urd::rtde::RTDEClient;
+    use tokio::signal::unix::{signal, SignalKind};
+    use urd::json_output::{self, ErrorEvent};
     
-    info!("Starting RTDE monitoring loop");
+    let mut abort_signal = signal(SignalKind::user_defined1())
+        .context("Failed to install SIGUSR1 handler")?;
     
-    // Get robot host from controller
-    let host = {
-        let controller_guard = controller.lock().await;
-        controller_guard.config().robot.host.clone()
-    };
-    
-    // Create RTDE client
-    let mut rtde_client = RTDEClient::new(&host, 30004)?;
+    while abort_signal.recv().await.is_some() {
+        info!("SIGUSR1 received - sending emergency abort");
+        
+        match abort_handle.abort() {
+            Ok(_) => {
+                json_output::output::command_sent(0, "emergency_abort");
+                let controller_clone = Arc::clone(&controller);
+                tokio::spawn(async move {
+                    controller_clone.lock().await.mark_emergency_halted();
+                });
+            }
+            Err(e) => {
+                error!("Failed to send emergency abort: {}", e);
+                json_output::output::error(ErrorEvent::from_error("Emergency abort failed", &e));
+            }
+        }
+    }
     
-    // RTDE handshake
-    rtde_client.connect()?;
-    info!("Connected to RTDE for monitoring");
+    Ok(())
+}
+
+/// Print a `controller_state` event for every controller state transition
+async fn run_state_events(mut receiver: tokio::sync::watch::Receiver<urd::ControllerRobotState>) {
+    use urd::ControllerRobotState;
+    use urd::json_output::{self, ControllerStateEvent};
     
-    rtde_client.negotiate_protocol_version(2)?;
+    let mut previous = receiver.borrow_and_update().clone();
+    while receiver.changed().await.is_ok() {
+        let state = receiver.borrow_and_update().clone();
+        let message = match &state {
+            ControllerRobotState::Error(message) => Some(message.clone()),
+            _ => None,
+        };
+        json_output::output::controller_state(ControllerStateEvent::new(previous.name(), state.name(), message));
+        previous = state;
+    }
+}
+
+/// Print a `heartbeat` event every `interval`
+/// 
+/// Only reads cached controller state, never the robot sockets, so it can't
+/// interleave with command traffic.
+async fn run_heartbeat(
+    controller: Arc<tokio::sync::Mutex<RobotController>>,
+    metrics: Arc<CommandMetrics>,
+    interval: std::time::Duration
+) {
+    use urd::json_output::{self, HeartbeatEvent};
     
-    // Try enhanced monitoring first, fall back to basic if needed
-    let enhanced_variables = vec![
-        "timestamp".to_string(),
-        "actual_q".to_string(),
-        "actual_TCP_pose".to_string(),
-        "robot_mode".to_string(),
-        "safety_mode".to_string(),
-        "runtime_state".to_string(),
-    ];
+    let started = std::time::Instant::now();
+    let mut ticker = tokio::time::interval(interval);
+    let mut robot_ready = false;
     
-    match rtde_client.setup_output_recipe(enhanced_variables.clone(), 125.0) {
-        Ok(_) => {
-            info!("Enhanced robot state monitoring enabled");
-        }
-        Err(_) => {
-            info!("Enhanced monitoring unavailable, using basic monitoring");
-            let basic_variables = vec![
-                "timestamp".to_string(),
-                "actual_q".to_string(),
-                "actual_TCP_pose".to_string(),
-            ];
-            rtde_client.setup_output_recipe(basic_variables, 125.0)?;
+    loop {
+        ticker.tick().await;
+        // Keep beating while a long dashboard operation holds the controller
+        if let Ok(controller_guard) = controller.try_lock() {
+            robot_ready = controller_guard.is_ready();
         }
+        json_output::output::heartbeat(HeartbeatEvent::new(
+            started.elapsed().as_secs_f64(),
+            robot_ready,
+            metrics.in_flight()
+        ));
+    }
+}
+
+async fn run_monitoring_loop(
+    controller: Arc<tokio::sync::Mutex<RobotController>>,
+    shutdown_signal: Arc<AtomicBool>
+) -> Result<()> {
+    use urd::RtdeRecorder;
+    
+    info!("Starting RTDE monitoring loop");
+    
+    // Get robot host and recording settings from controller
+    let (host, port, recording, rtde_timeout, retry_delay, rtde_frequency, mut enabled) = {
+        let controller_guard = controller.lock().await;
+        let daemon_config = controller_guard.daemon_config();
+        (
+            controller_guard.config().robot.host.clone(),
+            controller_guard.config().robot.ports.rtde,
+            daemon_config.recording.clone(),
+            daemon_config.publishing.rtde_timeout(),
+            daemon_config.robot.connection.retry_delay,
+            daemon_config.rtde_frequency(controller_guard.robot_info().series_model().as_deref()),
+            controller_guard.subscribe_monitoring(),
+        )
     };
     
-    rtde_client.start_data_synchronization()?;
+    // Optional capture of every data package for offline replay (urd-replay)
+    let mut recorder = match recording {
+        Some(config) => match RtdeRecorder::new(&config) {
+            Ok(recorder) => {
+                info!("Recording RTDE data to {}", config.path);
+                Some(recorder)
+            }
+            Err(e) => {
+                error!("RTDE recording disabled: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
     
-    info!("RTDE monitoring active");
+    let mut connection = None;
+    let mut consecutive_errors = 0;
     
     // Monitoring loop
     while !shutdown_signal.load(Ordering::Relaxed) {
-        match rtde_client.read_data_package() {
+        // Switched off by @monitoring off: drop the connection and idle
+        if !*enabled.borrow_and_update() {
+            if connection.take().is_some() {
+                info!("RTDE monitoring paused");
+            }
+            // Wake on @monitoring on; the timeout is only for noticing shutdown
+            tokio::select! {
+                changed = enabled.changed() => if changed.is_err() {
+                    break;
+                },
+                _ = tokio::time::sleep(tokio::time::Duration::from_millis(500)) => {}
+            }
+            continue;
+        }
+        
+        let rtde_client = match &mut connection {
+            Some(rtde_client) => rtde_client,
+            None => {
+                // Reads yield to the runtime instead of blocking a worker between packages
+                match connect_rtde(&host, port, rtde_frequency, rtde_timeout).and_then(|client| Ok(client.into_async()?)) {
+                    Ok(rtde_client) => {
+                        info!("RTDE monitoring active");
+                        consecutive_errors = 0;
+                        connection.insert(rtde_client)
+                    }
+                    Err(e) => {
+                        error!("RTDE monitoring connect failed: {}", e);
+                        controller.lock().await.check_rtde_watchdog();
+                        tokio::time::sleep(std::time::Duration::from_secs_f64(retry_delay)).await;
+                        continue;
+                    }
+                }
+            }
+        };
+        
+        match rtde_client.read_data_package().await {
             Ok(data) => {
-                // Process robot state data
-                let joint_positions = data.get("actual_q").cloned().unwrap_or_default();
-                let tcp_pose = data.get("actual_TCP_pose").cloned().unwrap_or_default();
-                let robot_mode = data.get("robot_mode")
-                    .and_then(|v| v.first())
-                    .copied()
-                    .unwrap_or(0.0) as i32;
-                let safety_mode = data.get("safety_mode")
-                    .and_then(|v| v.first())
-                    .copied()
-                    .unwrap_or(0.0) as i32;
-                let runtime_state = data.get("runtime_state")
-                    .and_then(|v| v.first())
-                    .copied()
-                    .unwrap_or(0.0) as i32;
-                
-                // Extract robot timestamp (rtime = seconds since robot power-on)
-                let robot_timestamp = data.get("timestamp")
-                    .and_then(|v| v.first())
-                    .copied();
+                consecutive_errors = 0;
                 
                 // Capture system timestamp (stime = Unix epoch when data received)
                 let wire_timestamp = std::time::SystemTime::now()
@@ -206,32 +416,47 @@ async fn run_monitoring_loop(
                     .unwrap()
                     .as_secs_f64();
                 
-                // Convert Vec<f64> to arrays
-                let joint_array: [f64; 6] = joint_positions.try_into().unwrap_or([0.0; 6]);
-                let tcp_array: [f64; 6] = tcp_pose.try_into().unwrap_or([0.0; 6]);
+                if let Some(active_recorder) = &mut recorder {
+                    if let Err(e) = active_recorder.record(&data, wire_timestamp) {
+                        error!("RTDE recording failed, disabling: {}", e);
+                        recorder = None;
+                    }
+                }
                 
                 // Check shutdown signal before processing data
                 if shutdown_signal.load(Ordering::Relaxed) {
                     break;
                 }
                 
-                // Process monitoring data through controller
-                {
-                    let mut controller_guard = controller.lock().await;
-                    controller_guard.process_monitoring_data(
-                        joint_array,
-                        tcp_array,
-                        robot_mode,
-                        safety_mode,
-                        runtime_state,
-                        robot_timestamp,
-                        wire_timestamp
-                    );
+                // Process monitoring data through controller, unless @monitoring off landed mid-read
+                let mut controller_guard = controller.lock().await;
+                if controller_guard.is_monitoring_enabled() {
+                    controller_guard.process_rtde_package(&data, wire_timestamp);
                 }
             }
             Err(e) => {
-                if !shutdown_signal.load(Ordering::Relaxed) {
-                    error!("Monitoring error: {}", e);
+                if shutdown_signal.load(Ordering::Relaxed) {
+                    break;
+                }
+                error!("Monitoring error: {}", e);
+                consecutive_errors += 1;
+                
+                // Reads time out after rtde_timeout_ms, so a silent stream lands here
+                let stale = controller.lock().await.check_rtde_watchdog();
+                if stale || consecutive_errors >= RTDE_RECONNECT_AFTER_ERRORS {
+                    // Reconnect only the RTDE client (same recipe); the interpreter is untouched
+                    info!("Reconnecting RTDE monitoring after {} failed reads", consecutive_errors);
+                    match rtde_client.reconnect().await {
+                        Ok(()) => {
+                            info!("RTDE monitoring reconnected");
+                            consecutive_errors = 0;
+                        }
+                        Err(e) => {
+                            error!("RTDE reconnect failed: {}", e);
+                            tokio::time::sleep(std::time::Duration::from_secs_f64(retry_delay)).await;
+                        }
+                    }
+                } else {
                     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await; // Brief pause before retry
                 }
             }
@@ -242,4 +467,62 @@ async fn run_monitoring_loop(
     Ok(())
 }
 
+/// Failed reads in a row before the RTDE connection is rebuilt
+/// 
+/// A read that fails part-way through a package leaves the socket out of step
+/// with the message framing, so retrying on the same connection rarely helps.
+const RTDE_RECONNECT_AFTER_ERRORS: u32 = 3;
 
+/// Connect to RTDE and start streaming the monitoring recipe
+/// 
+/// `read_timeout` bounds each read so a silently stalled stream surfaces as
+/// an error for the staleness watchdog.
+fn connect_rtde(host: &str, port: u16, frequency: f64, read_timeout: Option<std::time::Duration>) -> Result<urd::rtde::RTDEClient> {
+    use urd::rtde::RTDEClient;
+    
+    // Create RTDE client (robot.ports.rtde, for simulators with remapped ports)
+    let mut rtde_client = RTDEClient::new(host, port)?;
+    
+    // RTDE handshake
+    rtde_client.connect()?;
+    info!("Connected to RTDE for monitoring");
+    
+    rtde_client.negotiate_protocol_version(2)?;
+    
+    // Pose and joints are required; everything else is streamed where the firmware has it
+    let required_variables = ["timestamp", "actual_q", "actual_TCP_pose"];
+    let optional_variables = [
+        "actual_qd",
+        "actual_TCP_speed",
+        "robot_mode",
+        "safety_mode",
+        "runtime_state",
+        "actual_digital_input_bits",
+        "actual_digital_output_bits",
+        "actual_TCP_force",
+        "joint_temperatures",
+        "actual_current",
+        "standard_analog_input0",
+        "standard_analog_input1",
+        "tool_analog_input0",
+        "tool_analog_input1",
+        "tool_output_voltage",
+    ];
+    let variables = required_variables.iter().chain(&optional_variables).map(|name| name.to_string()).collect();
+    
+    let (_, missing) = rtde_client.setup_available_output_recipe(variables, frequency)?;
+    if let Some(name) = missing.iter().find(|name| required_variables.contains(&name.as_str())) {
+        return Err(urd::URError::Protocol(format!("Output variable not available: {}", name)).into());
+    }
+    if missing.is_empty() {
+        info!("Robot state monitoring enabled with all optional data");
+    } else {
+        info!("Robot state monitoring enabled without: {}", missing.join(", "));
+    }
+    info!("RTDE output frequency: {}Hz", frequency);
+    
+    rtde_client.start_data_synchronization()?;
+    rtde_client.set_read_timeout(read_timeout)?;
+    
+    Ok(rtde_client)
+}
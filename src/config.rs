@@ -1,7 +1,9 @@
 //! Configuration loading for UR robot
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::time::Duration;
 use crate::{Result, URError};
 
 // Config is now just an alias for DaemonConfig since everything is flattened
@@ -15,6 +17,9 @@ pub struct RobotConfig {
     pub movement: MovementConfig,
     pub connection: ConnectionConfig,
     pub model: Option<String>,
+    /// Power on and release brakes during startup (default true); when false
+    /// the robot is left as found until `@power on`
+    pub auto_power_on: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -47,31 +52,204 @@ pub struct DaemonConfig {
     pub publishing: PublishingConfig,
     pub command: CommandConfig,
     pub interpreter: Option<InterpreterConfig>,
+    pub metrics: Option<MetricsConfig>,
+    pub safety: Option<SafetyConfig>,
+    pub recording: Option<RecordingConfig>,
+    pub audit: Option<AuditConfig>,
+}
+
+/// Capture of raw RTDE data packages for offline replay
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecordingConfig {
+    /// JSON lines file to append frames to
+    pub path: String,
+    /// Roll over to a new file once this size is reached (unlimited if omitted)
+    pub max_file_bytes: Option<u64>,
+    /// Number of files kept including the live one (default 5)
+    pub max_files: Option<u32>,
+}
+
+/// Audit log of URScript commands sent to the robot
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuditConfig {
+    /// JSON lines file to append entries to
+    pub path: String,
+    /// Roll over to a new file once this size is reached (unlimited if omitted)
+    pub max_file_bytes: Option<u64>,
+    /// Number of files kept including the live one (default 5)
+    pub max_files: Option<u32>,
+    /// Entries kept in memory for `@history` (default 100)
+    pub history_size: Option<usize>,
+}
+
+/// Motion limits applied to `a=`/`v=` arguments of movej/movel/speedj/speedl
+/// and to literal pose targets
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SafetyConfig {
+    /// Joint speed ceiling for movej/speedj (rad/s)
+    pub max_joint_vel: Option<f64>,
+    /// Joint acceleration ceiling for movej/speedj (rad/s²)
+    pub max_joint_acc: Option<f64>,
+    /// Tool speed ceiling for movel/speedl (m/s)
+    pub max_tcp_vel: Option<f64>,
+    /// Tool acceleration ceiling for movel/speedl (m/s²)
+    pub max_tcp_acc: Option<f64>,
+    /// "clamp" (default) rewrites values to the ceiling, "strict" rejects the command
+    pub mode: Option<String>,
+    /// Box that literal `p[...]` motion targets must lie in (always rejects)
+    pub workspace: Option<WorkspaceConfig>,
+}
+
+/// TCP position bounds in the base frame (m); an omitted axis is unbounded
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct WorkspaceConfig {
+    pub x: Option<[f64; 2]>,
+    pub y: Option<[f64; 2]>,
+    pub z: Option<[f64; 2]>,
+}
+
+/// Prometheus endpoint settings (used when built with the `metrics` feature)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MetricsConfig {
+    /// Address to serve `/metrics` on, e.g. "0.0.0.0:9100"
+    pub bind_address: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PublishingConfig {
     pub pub_rate_hz: u32,
     pub decimal_places: Option<u32>,
+    /// Per-event overrides of `decimal_places`, keyed by event type
+    /// (`position`, `wrench`, `joints_diag`); other keys are ignored
+    pub topic_decimal_places: Option<HashMap<String, u32>>,
+    /// How RTDE samples between `position` events are combined: "latest"
+    /// (default) publishes the newest sample, "average" the mean over the interval
+    pub position_coalescing: Option<String>,
+    /// Emit the raw decoded RTDE variable map as `rtde_debug` events (protocol debugging)
+    pub debug_rtde: Option<bool>,
+    /// Rate for `wrench` events from `actual_TCP_force` (Hz); omitted or 0 disables them
+    pub wrench_rate_hz: Option<u32>,
+    /// Rate for `joints_diag` events (joint temperatures and currents, Hz);
+    /// omitted or 0 disables them
+    pub joint_diag_rate_hz: Option<u32>,
+    /// Mark monitoring stale when no RTDE package arrives within this many
+    /// milliseconds (default 1000, 0 disables the watchdog)
+    pub rtde_timeout_ms: Option<u64>,
+    /// RTDE output frequency requested from the controller (Hz, default 125);
+    /// independent of `pub_rate_hz`, which only limits printed events
+    pub rtde_rate_hz: Option<f64>,
+    /// Interval between `heartbeat` events (ms, default 1000, 0 disables them)
+    pub heartbeat_interval_ms: Option<u64>,
+}
+
+/// How `command.lint` treats findings in streamed URScript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintMode {
+    #[default]
+    Off,
+    /// Emit `lint_warning` events and send the command anyway
+    Warn,
+    /// Fail the command without sending it
+    Reject,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CommandConfig {
     pub monitor_execution: bool,
     pub stream_robot_state: String,
+    /// Reconnect and resubmit a command when the connection drops mid-execution
+    /// (uses `robot.connection.retry_attempts` / `retry_delay`, default false)
+    pub retry_on_connection_loss: Option<bool>,
+    /// Fail URScript immediately when RTDE shows the robot isn't RUNNING with
+    /// normal/reduced safety, instead of letting it time out (default true)
+    pub require_running: Option<bool>,
+    /// Hold URScript once the safety mode leaves NORMAL/REDUCED, until the
+    /// robot has recovered and `@resume` is sent (default true)
+    pub pause_on_fault: Option<bool>,
+    /// Lint URScript before sending it: "off" (default), "warn" or "reject"
+    pub lint: Option<LintMode>,
+    /// First of the six RTDE output double registers `@ik` and `@var get` overwrite (default 18)
+    pub ik_double_register: Option<u8>,
+    /// RTDE output int register `@ik` and `@var get` overwrite with their request marker (default 23)
+    pub ik_int_register: Option<u8>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct InterpreterConfig {
+    /// URScript commands between automatic interpreter buffer clears (default 500)
     pub clear_buffer_limit: Option<u32>,
+    /// Fail a command that hasn't finished after this long (default: no timeout)
     pub execution_timeout_seconds: Option<u64>,
     pub enable_monitoring: Option<bool>,
     pub max_concurrent_commands: Option<u32>,
     pub initialization_timeout_seconds: Option<u64>,
+    /// Let the execution timeout fire inside `{ ... }` blocks (default false)
+    pub timeout_inside_blocks: Option<bool>,
+    /// How long SIGTERM waits for sent commands to finish before aborting (default 30)
+    pub drain_timeout_seconds: Option<u64>,
+    /// Interpreter port connection attempts at startup (default `initialization_timeout_seconds`)
+    pub connect_max_attempts: Option<u32>,
+    /// Delay before the first connection retry (ms, default 1000)
+    pub connect_base_delay_ms: Option<u64>,
+    /// Multiplier applied to the delay after each failed attempt (default 1.0, i.e. fixed delay)
+    pub connect_backoff: Option<f64>,
+    /// Upper bound for the retry delay (ms, default 10000)
+    pub connect_max_delay_ms: Option<u64>,
+    /// No-op statement sent after each command; its executed ID marks completion (default "time(0)")
+    pub termination_token: Option<String>,
+}
+
+/// A semantically invalid configuration value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigError {
+    /// Dotted path of the offending field, e.g. "publishing.pub_rate_hz"
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
 }
 
 // Config is now just an alias for DaemonConfig, so no separate implementation needed
 
+/// Outcome of reloading the configuration while the daemon runs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigReload {
+    /// Changed fields that are now in effect, e.g. "publishing.pub_rate_hz"
+    pub applied: Vec<String>,
+    /// Changed fields that only take effect after a restart, e.g. "robot.host"
+    pub ignored: Vec<String>,
+}
+
+/// Dotted paths of the fields that differ between two configs
+pub fn changed_fields(old: &DaemonConfig, new: &DaemonConfig) -> Vec<String> {
+    fn diff(path: &str, old: &serde_json::Value, new: &serde_json::Value, changed: &mut Vec<String>) {
+        use serde_json::Value;
+        match (old, new) {
+            (Value::Object(old), Value::Object(new)) => {
+                let keys: std::collections::BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+                for key in keys {
+                    let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    diff(&path, old.get(key).unwrap_or(&Value::Null), new.get(key).unwrap_or(&Value::Null), changed);
+                }
+            }
+            _ if old != new => changed.push(path.to_string()),
+            _ => {}
+        }
+    }
+    
+    let mut changed = Vec::new();
+    match (serde_json::to_value(old), serde_json::to_value(new)) {
+        (Ok(old), Ok(new)) => diff("", &old, &new, &mut changed),
+        _ => changed.push("config".to_string()),
+    }
+    changed
+}
+
 impl DaemonConfig {
     pub fn load_from_path(config_path: &str) -> Result<Self> {
         let contents = fs::read_to_string(config_path)
@@ -86,10 +264,17 @@ impl Default for InterpreterConfig {
     fn default() -> Self {
         Self {
             clear_buffer_limit: Some(500),
-            execution_timeout_seconds: Some(30),
+            execution_timeout_seconds: None,
             enable_monitoring: Some(true),
             max_concurrent_commands: Some(10),
             initialization_timeout_seconds: Some(30),
+            timeout_inside_blocks: Some(false),
+            drain_timeout_seconds: Some(30),
+            connect_max_attempts: None,
+            connect_base_delay_ms: Some(1000),
+            connect_backoff: Some(1.0),
+            connect_max_delay_ms: Some(10000),
+            termination_token: Some("time(0)".to_string()),
         }
     }
 }
@@ -97,12 +282,34 @@ impl Default for InterpreterConfig {
 impl InterpreterConfig {
     /// Get clear buffer limit with default fallback
     pub fn clear_buffer_limit(&self) -> u32 {
-        self.clear_buffer_limit.unwrap_or(500)
+        self.clear_buffer_limit.filter(|&limit| limit > 0).unwrap_or(500)
     }
     
-    /// Get execution timeout with default fallback
-    pub fn execution_timeout(&self) -> u64 {
-        self.execution_timeout_seconds.unwrap_or(30)
+    /// Get per-command execution timeout (None or 0 disables it)
+    pub fn execution_timeout(&self) -> Option<Duration> {
+        self.execution_timeout_seconds
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs)
+    }
+    
+    /// Get whether the execution timeout applies inside brace blocks
+    pub fn timeout_inside_blocks(&self) -> bool {
+        self.timeout_inside_blocks.unwrap_or(false)
+    }
+    
+    /// Get the completion sentinel statement with default fallback
+    pub fn termination_token(&self) -> String {
+        self.termination_token
+            .as_deref()
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .unwrap_or("time(0)")
+            .to_string()
+    }
+    
+    /// Get the SIGTERM drain timeout with default fallback
+    pub fn drain_timeout(&self) -> Duration {
+        Duration::from_secs(self.drain_timeout_seconds.unwrap_or(30))
     }
     
     /// Get monitoring enabled with default fallback
@@ -119,11 +326,337 @@ impl InterpreterConfig {
     pub fn initialization_timeout(&self) -> u64 {
         self.initialization_timeout_seconds.unwrap_or(30)
     }
+    
+    /// Get interpreter connection attempts, falling back to one per second of `initialization_timeout`
+    pub fn connect_max_attempts(&self) -> u32 {
+        self.connect_max_attempts
+            .unwrap_or(self.initialization_timeout() as u32)
+            .max(1)
+    }
+    
+    /// Get the delay after failed connection attempt `attempt` (1-based)
+    /// 
+    /// `connect_base_delay_ms * connect_backoff^(attempt - 1)`, capped at `connect_max_delay_ms`.
+    pub fn connect_retry_delay(&self, attempt: u32) -> Duration {
+        let base_ms = self.connect_base_delay_ms.unwrap_or(1000) as f64;
+        let backoff = self.connect_backoff.filter(|&b| b >= 1.0).unwrap_or(1.0);
+        let max_ms = self.connect_max_delay_ms.unwrap_or(10000) as f64;
+        
+        let delay_ms = base_ms * backoff.powi(attempt.saturating_sub(1) as i32);
+        Duration::from_millis(delay_ms.min(max_ms) as u64)
+    }
+}
+
+impl PublishingConfig {
+    /// Whether `position` events carry the mean of the samples in each interval
+    pub fn position_averaging(&self) -> bool {
+        self.position_coalescing.as_deref() == Some("average")
+    }
+    
+    /// Get the heartbeat interval (None when heartbeats are disabled)
+    pub fn heartbeat_interval(&self) -> Option<Duration> {
+        match self.heartbeat_interval_ms.unwrap_or(1000) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+    
+    /// Get the RTDE staleness timeout (None when the watchdog is disabled)
+    pub fn rtde_timeout(&self) -> Option<Duration> {
+        match self.rtde_timeout_ms {
+            Some(0) => None,
+            Some(ms) => Some(Duration::from_millis(ms)),
+            None => Some(Duration::from_millis(1000)),
+        }
+    }
+}
+
+impl RobotConfig {
+    /// Whether startup may energize the robot (default true)
+    pub fn auto_power_on(&self) -> bool {
+        self.auto_power_on.unwrap_or(true)
+    }
+}
+
+impl CommandConfig {
+    /// Whether URScript is refused while RTDE shows the robot can't run it (default true)
+    pub fn require_running(&self) -> bool {
+        self.require_running.unwrap_or(true)
+    }
+    
+    /// Whether a safety fault holds URScript until `@resume` (default true)
+    pub fn pause_on_fault(&self) -> bool {
+        self.pause_on_fault.unwrap_or(true)
+    }
+    
+    /// URScript lint mode (default off)
+    pub fn lint(&self) -> LintMode {
+        self.lint.unwrap_or_default()
+    }
+    
+    /// Output registers register readbacks (`@ik`, `@var get`) publish through: (first double register, int register)
+    pub fn ik_registers(&self) -> (u8, u8) {
+        (
+            self.ik_double_register.unwrap_or(crate::motion::IK_RESULT_REGISTER),
+            self.ik_int_register.unwrap_or(crate::motion::IK_STATUS_REGISTER),
+        )
+    }
+}
+
+impl SafetyConfig {
+    /// Whether over-limit commands are rejected instead of clamped
+    pub fn strict(&self) -> bool {
+        self.mode.as_deref() == Some("strict")
+    }
+}
+
+impl RecordingConfig {
+    /// Get number of retained recording files with default fallback
+    pub fn max_files(&self) -> u32 {
+        self.max_files.unwrap_or(5).max(1)
+    }
+}
+
+impl AuditConfig {
+    /// Get number of retained audit files with default fallback
+    pub fn max_files(&self) -> u32 {
+        self.max_files.unwrap_or(5).max(1)
+    }
+    
+    /// Get in-memory history length with default fallback
+    pub fn history_size(&self) -> usize {
+        self.history_size.unwrap_or(100)
+    }
 }
 
 impl DaemonConfig {
+    /// Check value ranges and required fields, reporting every problem found
+    /// 
+    /// Loading only catches malformed YAML; this catches values that parse but
+    /// would break the daemon at runtime (e.g. `pub_rate_hz: 0`).
+    pub fn validate(&self) -> std::result::Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, field: &str, message: &str| {
+            if !ok {
+                errors.push(ConfigError { field: field.to_string(), message: message.to_string() });
+            }
+        };
+        let positive = |value: Option<f64>| value.is_none_or(|v| v > 0.0);
+        
+        let robot = &self.robot;
+        check(!robot.host.trim().is_empty() && !robot.host.contains(char::is_whitespace),
+            "robot.host", "must be a hostname or IP address without spaces");
+        check(robot.ports.primary != 0, "robot.ports.primary", "must be a non-zero port");
+        check(robot.ports.rtde != 0, "robot.ports.rtde", "must be a non-zero port");
+        check(robot.ports.dashboard != 0, "robot.ports.dashboard", "must be a non-zero port");
+        check(robot.ports.interpreter != Some(0), "robot.ports.interpreter", "must be a non-zero port");
+        check(robot.movement.speed > 0.0, "robot.movement.speed", "must be greater than 0");
+        check(robot.movement.acceleration > 0.0, "robot.movement.acceleration", "must be greater than 0");
+        check(robot.movement.blend_radius >= 0.0, "robot.movement.blend_radius", "must not be negative");
+        check(robot.connection.timeout > 0.0, "robot.connection.timeout", "must be greater than 0");
+        check(robot.connection.retry_delay >= 0.0, "robot.connection.retry_delay", "must not be negative");
+        
+        let publishing = &self.publishing;
+        check(publishing.pub_rate_hz > 0, "publishing.pub_rate_hz", "must be greater than 0");
+        check(publishing.pub_rate_hz <= 1000, "publishing.pub_rate_hz", "must be at most 1000");
+        check(publishing.decimal_places.is_none_or(|places| places <= 10),
+            "publishing.decimal_places", "must be at most 10");
+        check(publishing.topic_decimal_places.iter().flatten().all(|(_, &places)| places <= 10),
+            "publishing.topic_decimal_places", "must be at most 10");
+        check(matches!(publishing.position_coalescing.as_deref(), None | Some("latest") | Some("average")),
+            "publishing.position_coalescing", "must be \"latest\" or \"average\"");
+        check(publishing.wrench_rate_hz.is_none_or(|hz| hz <= 1000),
+            "publishing.wrench_rate_hz", "must be at most 1000");
+        check(publishing.joint_diag_rate_hz.is_none_or(|hz| hz <= 1000),
+            "publishing.joint_diag_rate_hz", "must be at most 1000");
+        check(positive(publishing.rtde_rate_hz), "publishing.rtde_rate_hz", "must be greater than 0");
+        
+        check(matches!(self.command.stream_robot_state.as_str(), "true" | "false" | "dynamic"),
+            "command.stream_robot_state", "must be true, false or \"dynamic\"");
+        // Output registers 0-47 exist; the solution takes six in a row
+        check(self.command.ik_double_register.is_none_or(|register| register <= 42),
+            "command.ik_double_register", "must be at most 42");
+        check(self.command.ik_int_register.is_none_or(|register| register <= 47),
+            "command.ik_int_register", "must be at most 47");
+        
+        if let Some(interpreter) = &self.interpreter {
+            check(interpreter.connect_base_delay_ms.is_none_or(|ms| ms > 0),
+                "interpreter.connect_base_delay_ms", "must be greater than 0");
+            check(interpreter.connect_backoff.is_none_or(|backoff| backoff >= 1.0),
+                "interpreter.connect_backoff", "must be at least 1.0");
+            // Sent as its own interpreter line, so it must not open or close a block
+            check(interpreter.termination_token.as_deref().is_none_or(|token| {
+                let token = token.trim();
+                !token.is_empty() && !token.starts_with('@') && !token.starts_with('#')
+                    && !token.contains(['\n', '{', '}'])
+            }), "interpreter.termination_token", "must be a single URScript statement without braces");
+        }
+        
+        if let Some(safety) = &self.safety {
+            check(positive(safety.max_joint_vel), "safety.max_joint_vel", "must be greater than 0");
+            check(positive(safety.max_joint_acc), "safety.max_joint_acc", "must be greater than 0");
+            check(positive(safety.max_tcp_vel), "safety.max_tcp_vel", "must be greater than 0");
+            check(positive(safety.max_tcp_acc), "safety.max_tcp_acc", "must be greater than 0");
+            check(matches!(safety.mode.as_deref(), None | Some("clamp") | Some("strict")),
+                "safety.mode", "must be \"clamp\" or \"strict\"");
+            if let Some(workspace) = &safety.workspace {
+                for (axis, bounds) in [("x", workspace.x), ("y", workspace.y), ("z", workspace.z)] {
+                    check(bounds.is_none_or(|[min, max]| min < max),
+                        &format!("safety.workspace.{}", axis), "must be [min, max] with min < max");
+                }
+            }
+        }
+        
+        if let Some(recording) = &self.recording {
+            check(!recording.path.trim().is_empty(), "recording.path", "must not be empty");
+        }
+        
+        if let Some(audit) = &self.audit {
+            check(!audit.path.trim().is_empty(), "audit.path", "must not be empty");
+        }
+        
+        if let Some(metrics) = &self.metrics {
+            check(metrics.bind_address.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()),
+                "metrics.bind_address", "must be host:port");
+        }
+        
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+    
+    /// This config with the settings that can change while connected taken from `new`
+    /// 
+    /// Live settings are the publishing rates and precision, the motion limits
+    /// and the interpreter buffer clear interval. The RTDE sampling rate,
+    /// staleness timeout and heartbeat interval stay, since the tasks using
+    /// them read them once at startup.
+    pub fn with_live_settings(&self, new: &DaemonConfig) -> DaemonConfig {
+        let mut updated = self.clone();
+        updated.publishing = PublishingConfig {
+            rtde_rate_hz: self.publishing.rtde_rate_hz,
+            rtde_timeout_ms: self.publishing.rtde_timeout_ms,
+            heartbeat_interval_ms: self.publishing.heartbeat_interval_ms,
+            ..new.publishing.clone()
+        };
+        updated.safety = new.safety.clone();
+        
+        let clear_buffer_limit = new.interpreter.as_ref().and_then(|interpreter| interpreter.clear_buffer_limit);
+        if clear_buffer_limit != self.interpreter.as_ref().and_then(|interpreter| interpreter.clear_buffer_limit) {
+            updated.interpreter.get_or_insert_with(InterpreterConfig::default).clear_buffer_limit = clear_buffer_limit;
+        }
+        updated
+    }
+    
     /// Get interpreter configuration with defaults
     pub fn interpreter(&self) -> InterpreterConfig {
         self.interpreter.clone().unwrap_or_default()
     }
-}
\ No newline at end of file
+    
+    /// Get the RTDE output frequency, clamped to what the robot model supports
+    /// 
+    /// `robot.model` takes precedence over `detected_model`, the model the
+    /// dashboard reported; with neither the limit is 125 Hz.
+    pub fn rtde_frequency(&self, detected_model: Option<&str>) -> f64 {
+        let requested = self.publishing.rtde_rate_hz
+            .filter(|&hz| hz > 0.0)
+            .unwrap_or(crate::rtde::DEFAULT_RTDE_FREQUENCY);
+        let model = self.robot.model.as_deref().or(detected_model);
+        let max = crate::rtde::max_rtde_frequency(model);
+        
+        if requested > max {
+            tracing::warn!(
+                "rtde_rate_hz {} exceeds the {} Hz supported by {}, clamping",
+                requested,
+                max,
+                model.unwrap_or("an unknown model")
+            );
+            return max;
+        }
+        requested
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_validate_reports_all_errors() {
+        let mut config = DaemonConfig::load_from_path("config/default_config.yaml").unwrap();
+        assert_eq!(config.validate(), Ok(()));
+        
+        config.publishing.pub_rate_hz = 0;
+        config.robot.host = "192.168.1 .10".to_string();
+        config.robot.ports.interpreter = Some(0);
+        config.command.stream_robot_state = "sometimes".to_string();
+        config.safety = Some(SafetyConfig { max_tcp_vel: Some(-1.0), ..SafetyConfig::default() });
+        
+        let errors = config.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["robot.host", "robot.ports.interpreter", "publishing.pub_rate_hz", "command.stream_robot_state", "safety.max_tcp_vel"]);
+    }
+    
+    #[test]
+    fn test_lint_mode_typo_fails_to_parse() {
+        assert_eq!(serde_yaml::from_str::<LintMode>("reject").unwrap(), LintMode::Reject);
+        assert!(serde_yaml::from_str::<LintMode>("rejct").is_err());
+    }
+    
+    #[test]
+    fn test_live_settings_and_changed_fields() {
+        let current = DaemonConfig::load_from_path("config/default_config.yaml").unwrap();
+        let mut new = current.clone();
+        new.publishing.pub_rate_hz = 50;
+        new.publishing.rtde_rate_hz = Some(500.0);
+        new.safety = Some(SafetyConfig { max_joint_vel: Some(0.5), ..SafetyConfig::default() });
+        new.robot.host = "10.0.0.2".to_string();
+        
+        let updated = current.with_live_settings(&new);
+        assert_eq!(updated.publishing.pub_rate_hz, 50);
+        assert_eq!(updated.robot.host, current.robot.host);
+        assert_eq!(changed_fields(&current, &updated), vec![
+            "publishing.pub_rate_hz",
+            "safety",
+        ]);
+        assert_eq!(changed_fields(&updated, &new), vec!["publishing.rtde_rate_hz", "robot.host"]);
+    }
+    
+    #[test]
+    fn test_connect_retry_delay_backoff() {
+        let fixed = InterpreterConfig::default();
+        assert_eq!(fixed.connect_retry_delay(1), Duration::from_millis(1000));
+        assert_eq!(fixed.connect_retry_delay(5), Duration::from_millis(1000));
+        assert_eq!(fixed.connect_max_attempts(), 30);
+        
+        let exponential = InterpreterConfig {
+            connect_max_attempts: Some(8),
+            connect_base_delay_ms: Some(500),
+            connect_backoff: Some(2.0),
+            connect_max_delay_ms: Some(5000),
+            ..InterpreterConfig::default()
+        };
+        assert_eq!(exponential.connect_retry_delay(1), Duration::from_millis(500));
+        assert_eq!(exponential.connect_retry_delay(3), Duration::from_millis(2000));
+        assert_eq!(exponential.connect_retry_delay(6), Duration::from_millis(5000));
+        assert_eq!(exponential.connect_max_attempts(), 8);
+    }
+    
+    #[test]
+    fn test_termination_token() {
+        let mut config = DaemonConfig::load_from_path("config/default_config.yaml").unwrap();
+        assert_eq!(config.interpreter().termination_token(), "time(0)");
+        
+        config.interpreter = Some(InterpreterConfig {
+            termination_token: Some(" sync() ".to_string()),
+            ..InterpreterConfig::default()
+        });
+        assert_eq!(config.interpreter().termination_token(), "sync()");
+        assert!(config.validate().is_ok());
+        
+        config.interpreter = Some(InterpreterConfig {
+            termination_token: Some("def f(): {".to_string()),
+            ..InterpreterConfig::default()
+        });
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors[0].field, "interpreter.termination_token");
+    }
+}
@@ -4,17 +4,21 @@
 //! state management, and integration with interpreter mode.
 
 use crate::{
-    config::{Config, DaemonConfig},
+    config::{changed_fields, Config, ConfigReload, DaemonConfig},
     interpreter::InterpreterClient,
-    monitoring::{MonitorOutput, PositionData, RobotStateData, 
+    monitoring::{JointDiagData, MonitorOutput, PositionData, RobotStateData, RtdeDebugData, RuntimeEventData, WrenchData,
                 get_robot_mode_name, get_safety_mode_name, get_runtime_state_name},
     rtde::RTDEClient,
+    URError,
 };
-use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::net::TcpStream;
-use std::time::Duration;
-use tracing::{info, error};
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{info, error, warn};
 
 /// Robot operational states
 #[derive(Debug, Clone, PartialEq)]
@@ -26,10 +30,26 @@ pub enum RobotState {
     Error(String),
 }
 
+impl RobotState {
+    /// Lowercase state name used in `controller_state` events
+    pub fn name(&self) -> &'static str {
+        match self {
+            RobotState::Disconnected => "disconnected",
+            RobotState::PowerOff => "power_off",
+            RobotState::Idle => "idle",
+            RobotState::Running => "running",
+            RobotState::Error(_) => "error",
+        }
+    }
+}
+
 /// Primary interface ports for UR robots
 pub const UR_PRIMARY_PORT: u16 = 30001;
 pub const UR_DASHBOARD_PORT: u16 = 29999;
 
+/// Number of standard (control box) digital inputs and outputs
+pub const STANDARD_DIGITAL_IO: u8 = 8;
+
 /// Latest robot status from RTDE monitoring
 #[derive(Debug, Clone)]
 pub struct RobotStatus {
@@ -42,6 +62,118 @@ pub struct RobotStatus {
     pub tcp_pose: [f64; 6],
     pub joint_positions: [f64; 6],
     pub last_updated: f64,
+    /// RTDE `actual_digital_input_bits` (None if not in the recipe)
+    pub digital_input_bits: Option<u64>,
+    /// RTDE `actual_digital_output_bits` (None if not in the recipe)
+    pub digital_output_bits: Option<u64>,
+    /// Standard and tool analog inputs (None if not in the recipe)
+    pub analog_inputs: Option<AnalogInputs>,
+    /// RTDE `actual_qd` joint speeds in rad/s (None if not in the recipe)
+    pub joint_speeds: Option<[f64; 6]>,
+    /// RTDE `actual_TCP_speed` [vx, vy, vz, wx, wy, wz] in m/s and rad/s (None if not in the recipe)
+    pub tcp_speed: Option<[f64; 6]>,
+}
+
+/// Analog inputs from RTDE
+/// 
+/// Values are volts or milliamps, depending on how each input's range is
+/// configured on the pendant.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct AnalogInputs {
+    /// `standard_analog_input0` and `standard_analog_input1`
+    pub standard: [f64; 2],
+    /// `tool_analog_input0` and `tool_analog_input1`
+    pub tool: [f64; 2],
+    /// Tool output voltage setting (0, 12 or 24 V); None where the controller doesn't report it
+    pub tool_output_voltage: Option<i32>,
+}
+
+/// Identity of the connected arm, read from the dashboard at startup
+/// 
+/// Fields the controller doesn't answer (older CB3 software) are "unknown".
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RobotInfo {
+    pub model: String,
+    pub serial_number: String,
+    pub software_version: String,
+}
+
+impl Default for RobotInfo {
+    fn default() -> Self {
+        Self {
+            model: "unknown".to_string(),
+            serial_number: "unknown".to_string(),
+            software_version: "unknown".to_string(),
+        }
+    }
+}
+
+impl RobotInfo {
+    /// Turn a dashboard reply into a field value, "unknown" if the query was refused
+    fn field_from_reply(reply: &str) -> String {
+        let reply = reply.trim();
+        let refused = reply.is_empty()
+            || reply.starts_with("could not understand")
+            || reply.to_ascii_lowercase().contains("error");
+        if refused {
+            "unknown".to_string()
+        } else {
+            reply.to_string()
+        }
+    }
+    
+    /// The arm's model, None if the dashboard didn't report it
+    /// 
+    /// The dashboard names e-Series arms like their CB3 predecessors ("UR10"),
+    /// so software 5.x or later adds the "e".
+    pub fn series_model(&self) -> Option<String> {
+        if self.model == "unknown" {
+            return None;
+        }
+        let major = self.software_version.split('.').next().and_then(|v| v.parse::<u32>().ok());
+        let cb3_name = matches!(self.model.to_ascii_uppercase().as_str(), "UR3" | "UR5" | "UR10" | "UR16");
+        if cb3_name && major.is_some_and(|major| major >= 5) {
+            Some(format!("{}e", self.model))
+        } else {
+            Some(self.model.clone())
+        }
+    }
+    
+    /// Extract the version from `PolyscopeVersion` ("URSoftware 5.11.1.108318 (Mar 22 2021)")
+    fn version_from_reply(reply: &str) -> String {
+        let field = Self::field_from_reply(reply);
+        match field.strip_prefix("URSoftware") {
+            Some(rest) => rest.split_whitespace().next().unwrap_or("unknown").to_string(),
+            None => field,
+        }
+    }
+}
+
+/// Dashboard view of the program: which `.urp` is loaded and whether it runs
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ProgramState {
+    /// Path of the loaded program, None if nothing is loaded
+    pub loaded: Option<String>,
+    pub running: bool,
+    /// `programState` keyword: "STOPPED", "PLAYING", "PAUSED" (or "unknown")
+    pub state: String,
+}
+
+impl ProgramState {
+    /// Build from the `get loaded program`, `programState` and `running` replies
+    fn from_replies(loaded: &str, program_state: &str, running: &str) -> Self {
+        let loaded = loaded.trim().strip_prefix("Loaded program:")
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty());
+        let state = RobotInfo::field_from_reply(program_state)
+            .split_whitespace()
+            .next()
+            .unwrap_or("unknown")
+            .to_string();
+        let running = running.trim().ends_with("true");
+        
+        Self { loaded, running, state }
+    }
 }
 
 impl Default for RobotStatus {
@@ -56,7 +188,338 @@ impl Default for RobotStatus {
             tcp_pose: [0.0; 6],
             joint_positions: [0.0; 6],
             last_updated: 0.0,
+            digital_input_bits: None,
+            digital_output_bits: None,
+            analog_inputs: None,
+            joint_speeds: None,
+            tcp_speed: None,
+        }
+    }
+}
+
+impl RobotStatus {
+    /// Why URScript can't execute in this state, if it can't
+    /// 
+    /// Requires robot mode RUNNING and safety mode NORMAL or REDUCED. Unknown
+    /// modes (no RTDE data, or not in the recipe) are not reported.
+    pub fn not_running_reason(&self) -> Option<String> {
+        if self.robot_mode >= 0 && self.robot_mode != 7 {
+            return Some(format!("robot not in RUNNING mode: current mode {}", self.robot_mode_name));
+        }
+        self.fault_reason()
+    }
+    
+    /// Why the safety system has stopped the robot, if it has
+    /// 
+    /// Any known safety mode other than NORMAL or REDUCED (protective stop,
+    /// emergency stop, fault, violation ...).
+    pub fn fault_reason(&self) -> Option<String> {
+        if self.safety_mode >= 0 && !matches!(self.safety_mode, 1 | 2) {
+            return Some(format!("robot not in NORMAL safety mode: current mode {}", self.safety_mode_name));
+        }
+        None
+    }
+}
+
+/// A script sent to the interpreter that reports through the readback registers
+/// 
+/// Once `command_id` has executed, `read` fetches the registers over a
+/// short-lived RTDE connection. Holds nothing of the controller, so the shared
+/// controller needn't stay locked while the script runs or the read waits.
+#[derive(Debug, Clone)]
+pub struct RegisterReadback {
+    /// Interpreter ID of the script
+    pub command_id: u32,
+    /// Marker the script writes to the int register when it has published
+    pub sequence: i32,
+    /// First output double register and the output int register (`command.ik_registers`)
+    pub registers: (u8, u8),
+    host: String,
+    port: u16,
+    timeout: Duration,
+}
+
+impl RegisterReadback {
+    /// Read the int register and the six double registers from one RTDE package
+    /// 
+    /// Runs on the blocking pool; a silent RTDE port fails after the connection
+    /// timeout instead of stalling the runtime.
+    pub fn read(&self) -> impl std::future::Future<Output = Result<HashMap<String, Vec<f64>>>> + Send + 'static {
+        let (result_register, status_register) = self.registers;
+        let mut variables = vec![format!("output_int_register_{}", status_register)];
+        variables.extend((0..6).map(|i| format!("output_double_register_{}", result_register + i)));
+        let (host, port, timeout) = (self.host.clone(), self.port, self.timeout);
+        
+        async move {
+            tokio::task::spawn_blocking(move || -> Result<HashMap<String, Vec<f64>>> {
+                let mut client = RTDEClient::new(&host, port)?;
+                client.connect()?;
+                client.set_read_timeout(Some(timeout))?;
+                client.negotiate_protocol_version(2)?;
+                client.setup_output_recipe(variables, 10.0)?;
+                client.start_data_synchronization()?;
+                Ok(client.read_data_package()?)
+            }).await?
+        }
+    }
+}
+
+/// Sends the emergency halt without going through the controller
+/// 
+/// Holds a clone of the primary socket and the interpreter's abort signal, kept
+/// current by the controller across reconnects. Aborting never waits on the
+/// shared controller mutex, so it can't be held up by a long dashboard
+/// operation (`@recover`, `@power on`) or an `@ik` solve.
+#[derive(Clone, Default)]
+pub struct AbortHandle {
+    targets: Arc<Mutex<AbortTargets>>,
+}
+
+#[derive(Default)]
+struct AbortTargets {
+    primary_socket: Option<TcpStream>,
+    interpreter_signal: Option<Arc<AtomicBool>>,
+}
+
+impl AbortHandle {
+    /// Send `halt` through the primary socket and signal the interpreter to stop waiting
+    pub fn abort(&self) -> Result<()> {
+        let mut targets = self.targets.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let primary_socket = targets.primary_socket.as_mut()
+            .ok_or_else(|| URError::Connection("Primary socket not connected".to_string()))?;
+        
+        info!("Sending emergency abort through primary socket");
+        primary_socket.write_all(b"halt\n")
+            .context("Failed to send emergency abort to primary socket")?;
+        info!("Emergency abort sent through primary socket");
+        
+        // Signal the interpreter to abort any pending operations
+        if let Some(signal) = &targets.interpreter_signal {
+            signal.store(true, Ordering::Relaxed);
+            info!("Signaled interpreter to abort pending operations");
+        }
+        Ok(())
+    }
+    
+    fn set_primary_socket(&self, socket: Option<TcpStream>) {
+        self.targets.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).primary_socket = socket;
+    }
+    
+    fn set_interpreter_signal(&self, signal: Option<Arc<AtomicBool>>) {
+        self.targets.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).interpreter_signal = signal;
+    }
+}
+
+/// Dashboard server connection, shared so long sequences can run unlocked
+/// 
+/// Each command holds the socket only until its reply is read, keeping
+/// replies paired with commands; sequences that poll for seconds (`recover`)
+/// work on a clone instead of borrowing the controller. A connection dropped
+/// after a failed read is reopened by the next command.
+#[derive(Clone, Default)]
+struct Dashboard {
+    connection: Arc<Mutex<DashboardConnection>>,
+}
+
+#[derive(Default)]
+struct DashboardConnection {
+    socket: Option<TcpStream>,
+    /// Host, port and read timeout to reopen with; None once disconnected
+    endpoint: Option<(String, u16, Duration)>,
+}
+
+impl Dashboard {
+    fn lock(&self) -> std::sync::MutexGuard<'_, DashboardConnection> {
+        self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    
+    /// Connect to the dashboard server at `host:port`
+    fn connect(&self, host: &str, port: u16, timeout: Duration) -> Result<()> {
+        let socket = open_dashboard(host, port, timeout)?;
+        *self.lock() = DashboardConnection {
+            socket: Some(socket),
+            endpoint: Some((host.to_string(), port, timeout)),
+        };
+        Ok(())
+    }
+    
+    /// Close the connection without reopening it later
+    fn disconnect(&self) {
+        *self.lock() = DashboardConnection::default();
+    }
+    
+    /// Whether a connection is open (a command in flight counts as connected)
+    fn is_connected(&self) -> bool {
+        match self.connection.try_lock() {
+            Ok(connection) => connection.socket.is_some(),
+            Err(std::sync::TryLockError::WouldBlock) => true,
+            Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner().socket.is_some(),
+        }
+    }
+    
+    /// Send a command and read its reply, reopening a dropped connection first
+    fn send(&self, command: &str) -> Result<String> {
+        let mut connection = self.lock();
+        let connection = &mut *connection;
+        let socket = match connection.socket {
+            Some(ref mut socket) => socket,
+            None => {
+                let (host, port, timeout) = connection.endpoint.as_ref()
+                    .ok_or_else(|| URError::Connection("Dashboard socket not connected".to_string()))?;
+                info!("Reopening dashboard connection");
+                connection.socket.insert(open_dashboard(host, *port, *timeout)?)
+            }
+        };
+        
+        // Send command
+        let cmd_with_newline = format!("{}\n", command);
+        socket.write_all(cmd_with_newline.as_bytes())
+            .context("Failed to send dashboard command")?;
+        
+        match read_dashboard_reply(socket) {
+            Ok(response) => Ok(response),
+            Err(e) => {
+                // A late reply would be read as the answer to the next command
+                connection.socket = None;
+                Err(e).with_context(|| format!("No dashboard reply to '{}'", command))
+            }
+        }
+    }
+    
+    /// Wait for robot to reach a specific state
+    async fn wait_for_robot_state(&self, target_state: &str, timeout_seconds: u64) -> Result<()> {
+        let start_time = std::time::Instant::now();
+        let timeout = Duration::from_secs(timeout_seconds);
+        
+        loop {
+            let current_state = self.send("robotmode")?;
+            
+            if current_state.contains(target_state) {
+                return Ok(());
+            }
+            
+            if start_time.elapsed() > timeout {
+                return Err(URError::Timeout(format!("waiting for robot state '{}' (current: {})", target_state, current_state)).into());
+            }
+            
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+    
+    /// Send a dashboard program command and turn a nack reply into an error
+    /// 
+    /// The dashboard acknowledges with a fixed phrase (`success_prefix`); anything
+    /// else is a refusal. e-Series controllers refuse program control unless the
+    /// pendant is in remote control mode, which is reported explicitly.
+    fn program_command(&self, command: &str, success_prefix: &str) -> Result<String> {
+        let response = self.send(command)?;
+        if response.starts_with(success_prefix) {
+            return Ok(response);
+        }
+        
+        // CB3 doesn't know this query, so only a definite "false" is reported
+        if let Ok(remote) = self.send("is in remote control") {
+            if remote.trim() == "false" {
+                return Err(URError::NotReady(format!(
+                    "'{}' requires remote control mode - switch the teach pendant to Remote", command
+                )).into());
+            }
+        }
+        
+        Err(URError::Rejected(format!("Dashboard refused '{}': {}", command, response)).into())
+    }
+    
+    /// Power on and release brakes as needed, waiting until the robot is RUNNING
+    async fn power_on_and_release_brakes(&self) -> Result<()> {
+        // Check robot mode
+        let robot_mode = self.send("robotmode")?;
+        info!("Current robot mode: {}", robot_mode);
+        
+        // Power on if needed
+        if robot_mode.contains("POWER_OFF") || robot_mode.contains("DISCONNECTED") {
+            info!("Powering on robot");
+            self.send("power on")?;
+            
+            // Wait for power on
+            self.wait_for_robot_state("IDLE", 15).await?;
+            info!("Robot powered on");
+        }
+        
+        // Release brakes if needed
+        let current_mode = self.send("robotmode")?;
+        if current_mode.contains("IDLE") {
+            info!("Releasing brakes");
+            self.send("brake release")?;
+            
+            // Wait for running state
+            self.wait_for_robot_state("RUNNING", 10).await?;
+            info!("Brakes released, robot ready");
+        }
+        
+        Ok(())
+    }
+    
+    fn unlock_protective_stop(&self) -> Result<()> {
+        let response = self.program_command("unlock protective stop", "Protective stop releasing")?;
+        
+        info!("Protective stop unlocked: {}", response);
+        Ok(())
+    }
+    
+    fn close_safety_popup(&self) -> Result<()> {
+        let response = self.program_command("close safety popup", "closing safety popup")?;
+        
+        info!("Safety popup closed: {}", response);
+        Ok(())
+    }
+    
+    /// See `RobotController::recover_task`
+    async fn recover(&self, safety_mode: i32) -> Result<String> {
+        let safety_mode_name = get_safety_mode_name(safety_mode);
+        
+        match safety_mode {
+            1 | 2 => {
+                self.wait_for_robot_state("RUNNING", 10).await?;
+                return Ok(format!("Robot already in {} safety mode", safety_mode_name));
+            }
+            3 => {}
+            5 => return Err(URError::NotReady(
+                "Safeguard stop is active - reset the safeguard input before recovering".to_string()
+            ).into()),
+            6..=8 => return Err(URError::NotReady(format!(
+                "{} is active - release the emergency stop and reset it on the teach pendant", safety_mode_name
+            )).into()),
+            _ => return Err(URError::NotReady(format!(
+                "Safety mode {} cannot be recovered remotely - resolve it on the teach pendant", safety_mode_name
+            )).into()),
+        }
+        
+        info!("Recovering from protective stop");
+        self.close_safety_popup()?;
+        
+        // Retry through the controller's mandatory 5 second wait
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            match self.unlock_protective_stop() {
+                Ok(_) => break,
+                Err(e) if Instant::now() < deadline => {
+                    info!("Protective stop not yet unlockable, retrying: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+                Err(e) => return Err(e),
+            }
         }
+        
+        // Unlocking can leave the brakes engaged
+        let robot_mode = self.send("robotmode")?;
+        if robot_mode.contains("IDLE") {
+            info!("Releasing brakes");
+            self.send("brake release")?;
+        }
+        self.wait_for_robot_state("RUNNING", 10).await?;
+        
+        info!("Robot recovered from protective stop");
+        Ok("Protective stop unlocked, robot running".to_string())
     }
 }
 
@@ -65,30 +528,68 @@ pub struct RobotController {
     config: Config,
     daemon_config: DaemonConfig,
     primary_socket: Option<TcpStream>,
-    dashboard_socket: Option<TcpStream>,
+    dashboard: Dashboard,
     interpreter: Option<InterpreterClient>,
     rtde_monitor: Option<RTDEClient>,
     monitor_output: Option<MonitorOutput>,
     state: RobotState,
+    /// Set by `mark_emergency_halted`, cleared by the next state change
+    emergency_halted: bool,
+    /// Publishes every change made through `set_state`
+    state_sender: watch::Sender<RobotState>,
+    /// Whether the RTDE monitoring task should be streaming
+    monitoring_sender: watch::Sender<bool>,
     robot_status: RobotStatus,
+    robot_info: RobotInfo,
+    /// When the last RTDE data package was processed
+    last_rtde_package: Option<Instant>,
+    /// Set by the watchdog when RTDE data stops arriving
+    rtde_stale: bool,
+    /// Speed slider fraction last written by `set_speed_scaling`
+    speed_scaling: Option<f64>,
+    /// Marker of the last `send_readback` request
+    readback_sequence: i32,
+    /// File the config was loaded from, re-read by `reload_config`
+    config_path: Option<String>,
+    /// Fault that is holding URScript (`command.pause_on_fault`), until `clear_fault_hold`
+    fault_hold: Option<String>,
+    /// Lock-free route to the primary socket for emergency aborts
+    abort_handle: AbortHandle,
 }
 
 impl RobotController {
     /// Create a new robot controller with daemon config path
     pub fn new_with_config(daemon_config_path: &str) -> Result<Self> {
         let config = DaemonConfig::load_from_path(daemon_config_path)?;
-        
-        Ok(Self {
+        let mut controller = Self::new(config);
+        controller.config_path = Some(daemon_config_path.to_string());
+        Ok(controller)
+    }
+    
+    /// Create a new robot controller from an already loaded config
+    pub fn new(config: DaemonConfig) -> Self {
+        Self {
             config: config.clone(),
             daemon_config: config,
             primary_socket: None,
-            dashboard_socket: None,
+            dashboard: Dashboard::default(),
             interpreter: None,
             rtde_monitor: None,
             monitor_output: None,
             state: RobotState::Disconnected,
+            emergency_halted: false,
+            state_sender: watch::Sender::new(RobotState::Disconnected),
+            monitoring_sender: watch::Sender::new(false),
             robot_status: RobotStatus::default(),
-        })
+            robot_info: RobotInfo::default(),
+            last_rtde_package: None,
+            rtde_stale: false,
+            speed_scaling: None,
+            readback_sequence: 0,
+            config_path: None,
+            fault_hold: None,
+            abort_handle: AbortHandle::default(),
+        }
     }
     
     /// Perform complete robot initialization sequence
@@ -104,15 +605,19 @@ impl RobotController {
         info!("Robot: {}", self.config.robot.host);
         
         // Initialize connections and interpreter
-        self.initialize_connections_and_interpreter().await?;
+        let interpreter_started = self.initialize_connections_and_interpreter().await?;
         
         // Optionally spawn monitor
         if enable_monitoring {
             self.spawn_monitor().await?;
         }
         
-        self.state = RobotState::Running;
-        info!("Robot initialization complete!");
+        if interpreter_started {
+            self.set_state(RobotState::Running);
+            info!("Robot initialization complete!");
+        } else {
+            info!("Robot connected without interpreter mode - waiting for @power on");
+        }
         Ok(())
     }
     
@@ -122,171 +627,240 @@ impl RobotController {
         
         let socket = TcpStream::connect((
             self.config.robot.host.as_str(),
-            UR_PRIMARY_PORT
+            self.config.robot.ports.primary
         )).context("Failed to connect to primary interface")?;
         
+        self.abort_handle.set_primary_socket(Some(socket.try_clone()
+            .context("Failed to clone primary socket for emergency aborts")?));
         self.primary_socket = Some(socket);
-        info!("Connected to primary interface at {}:{}", self.config.robot.host, UR_PRIMARY_PORT);
+        info!("Connected to primary interface at {}:{}", self.config.robot.host, self.config.robot.ports.primary);
         Ok(())
     }
     
     /// Assess robot state and prepare it for operation
-    async fn assess_and_prepare_robot(&mut self) -> Result<()> {
+    /// 
+    /// Returns false when `robot.auto_power_on` is off and the robot isn't
+    /// already running, so interpreter mode can't start.
+    async fn assess_and_prepare_robot(&mut self) -> Result<bool> {
         info!("Assessing robot state");
         
         // Connect to dashboard for state queries and control
-        let dashboard_socket = TcpStream::connect((
-            self.config.robot.host.as_str(),
-            UR_DASHBOARD_PORT
-        )).context("Failed to connect to dashboard")?;
+        self.dashboard.connect(
+            &self.config.robot.host,
+            self.config.robot.ports.dashboard,
+            Duration::from_secs_f64(self.config.robot.connection.timeout),
+        )?;
         
-        self.dashboard_socket = Some(dashboard_socket);
-        
-        // Check robot mode
-        let robot_mode = self.send_dashboard_command("robotmode").await?;
-        info!("Current robot mode: {}", robot_mode);
+        self.query_robot_info().await;
         
-        // Power on if needed
-        if robot_mode.contains("POWER_OFF") || robot_mode.contains("DISCONNECTED") {
-            info!("Powering on robot");
-            self.send_dashboard_command("power on").await?;
-            
-            // Wait for power on
-            self.wait_for_robot_state("IDLE", 15).await?;
-            info!("Robot powered on");
+        if self.config.robot.auto_power_on() {
+            self.power_on_and_release_brakes().await?;
+            return Ok(true);
         }
         
-        // Release brakes if needed
-        let current_mode = self.send_dashboard_command("robotmode").await?;
-        if current_mode.contains("IDLE") {
-            info!("Releasing brakes");
-            self.send_dashboard_command("brake release").await?;
-            
-            // Wait for running state
-            self.wait_for_robot_state("RUNNING", 10).await?;
-            info!("Brakes released, robot ready");
+        // Safe mode: never energize the robot unasked
+        let robot_mode = self.send_dashboard_command("robotmode").await?;
+        if robot_mode.contains("RUNNING") {
+            return Ok(true);
         }
+        warn!("{} and robot.auto_power_on is false - leaving the robot as it is", robot_mode);
+        self.set_state(if robot_mode.contains("IDLE") { RobotState::Idle } else { RobotState::PowerOff });
+        Ok(false)
+    }
+    
+    /// Power on and release brakes as needed, waiting until the robot is RUNNING
+    async fn power_on_and_release_brakes(&self) -> Result<()> {
+        self.dashboard.power_on_and_release_brakes().await
+    }
+    
+    /// Read model, serial number and software version from the dashboard
+    /// 
+    /// Never fails: unanswered queries leave the field "unknown".
+    async fn query_robot_info(&mut self) {
+        let model = self.send_dashboard_command("get robot model").await.unwrap_or_default();
+        let serial_number = self.send_dashboard_command("get serial number").await.unwrap_or_default();
+        let software_version = self.send_dashboard_command("PolyscopeVersion").await.unwrap_or_default();
         
-        Ok(())
+        let model = RobotInfo::field_from_reply(&model);
+        let serial_number = RobotInfo::field_from_reply(&serial_number);
+        let software_version = RobotInfo::version_from_reply(&software_version);
+        
+        self.robot_info = RobotInfo { model, serial_number, software_version };
+        info!(
+            "Robot model: {}, serial: {}, software: {}",
+            self.robot_info.model, self.robot_info.serial_number, self.robot_info.software_version
+        );
     }
     
     /// Start interpreter mode on the robot
     async fn start_interpreter_mode(&mut self) -> Result<()> {
-        info!("Starting interpreter mode");
-        
         let primary_socket = self.primary_socket.as_mut()
-            .ok_or_else(|| anyhow!("Primary socket not connected"))?;
-        
-        // Send interpreter mode activation script
-        let interpreter_script = "def ur_init():\n  textmsg(\"Starting interpreter mode\")\n  interpreter_mode()\nend\nur_init()\n";
-        
-        primary_socket.write_all(interpreter_script.as_bytes())
-            .context("Failed to send interpreter mode script")?;
-        
-        // Give it time to process
-        tokio::time::sleep(Duration::from_millis(1000)).await;
-        
-        info!("Interpreter mode script sent");
-        Ok(())
+            .ok_or_else(|| URError::Connection("Primary socket not connected".to_string()))?;
+        send_interpreter_script(primary_socket).await
     }
     
     /// Validate that interpreter mode is running and connect to it
     async fn validate_interpreter(&mut self) -> Result<()> {
-        info!("Validating interpreter mode");
-        
-        // Try to connect to interpreter port
-        let mut interpreter = InterpreterClient::new(&self.config.robot.host, None)?;
-        
-        // Retry connection with timeout from configuration
-        let interpreter_config = self.interpreter_config();
-        let max_attempts = interpreter_config.initialization_timeout() as u32;
-        let mut attempts = 0;
-        
-        while attempts < max_attempts {
-            match interpreter.connect() {
-                Ok(_) => break,
-                Err(_) if attempts < max_attempts - 1 => {
-                    attempts += 1;
-                    info!("Waiting for interpreter mode (attempt {}/{})", attempts, max_attempts);
-                    tokio::time::sleep(Duration::from_millis(1000)).await;
-                }
-                Err(e) => return Err(anyhow!("Failed to connect to interpreter after {} attempts: {}", max_attempts, e)),
-            }
-        }
-        
-        // Test interpreter with a simple command
-        let result = interpreter.execute_command("textmsg(\"Interpreter mode validated\")")?;
-        info!("Interpreter mode validated (command ID: {})", result.id);
-        
-        self.interpreter = Some(interpreter);
+        let interpreter = connect_interpreter(&self.config.robot.host, self.config.robot.ports.interpreter, &self.interpreter_config()).await?;
+        self.install_interpreter(interpreter);
         Ok(())
     }
     
+    fn install_interpreter(&mut self, interpreter: InterpreterClient) {
+        self.abort_handle.set_interpreter_signal(Some(interpreter.get_abort_signal()));
+        self.interpreter = Some(interpreter);
+    }
+    
     /// Spawn RTDE monitoring (optional)
     async fn spawn_monitor(&mut self) -> Result<()> {
         info!("Starting RTDE monitoring");
         
-        let rtde_client = RTDEClient::new(&self.config.robot.host, 30004)?;
+        let rtde_client = RTDEClient::new(&self.config.robot.host, self.config.robot.ports.rtde)?;
         self.rtde_monitor = Some(rtde_client);
         
+        self.enable_monitor_output();
+        self.monitoring_sender.send_replace(true);
+        Ok(())
+    }
+    
+    /// Start or stop RTDE monitoring without restarting the daemon
+    /// 
+    /// The monitoring task follows `subscribe_monitoring`: starting reconnects
+    /// RTDE with a fresh recipe, stopping drops the connection. The robot
+    /// status is cleared on stop since nothing would keep it current. Returns
+    /// false if monitoring was already in the requested state.
+    pub async fn set_monitoring_enabled(&mut self, enabled: bool) -> Result<bool> {
+        if enabled == self.is_monitoring_enabled() {
+            return Ok(false);
+        }
+        
+        if enabled {
+            self.spawn_monitor().await?;
+        } else {
+            info!("Stopping RTDE monitoring");
+            self.monitoring_sender.send_replace(false);
+            self.rtde_monitor = None;
+            self.monitor_output = None;
+            self.last_rtde_package = None;
+            self.rtde_stale = false;
+            self.robot_status = RobotStatus::default();
+        }
+        Ok(true)
+    }
+    
+    /// Whether RTDE monitoring is switched on (it may still be stale)
+    pub fn is_monitoring_enabled(&self) -> bool {
+        *self.monitoring_sender.borrow()
+    }
+    
+    /// Follow monitoring being switched on and off
+    pub fn subscribe_monitoring(&self) -> watch::Receiver<bool> {
+        self.monitoring_sender.subscribe()
+    }
+    
+    /// Build JSON monitor output from the publishing configuration
+    fn build_monitor_output(&self) -> MonitorOutput {
+        let publishing = &self.daemon_config.publishing;
+        let dynamic_mode = self.daemon_config.command.stream_robot_state == "dynamic";
+        
+        MonitorOutput::new(publishing.pub_rate_hz, dynamic_mode, publishing.decimal_places.unwrap_or(4))
+            .with_debug_rtde(publishing.debug_rtde.unwrap_or(false))
+            .with_wrench_rate(publishing.wrench_rate_hz.unwrap_or(0))
+            .with_joint_diag_rate(publishing.joint_diag_rate_hz.unwrap_or(0))
+            .with_topic_decimal_places(publishing.topic_decimal_places.clone().unwrap_or_default())
+            .with_position_averaging(publishing.position_averaging())
+    }
+    
+    /// Set up JSON monitor output from the publishing configuration
+    /// 
+    /// Called when monitoring starts; also used to print replayed RTDE data
+    /// without a robot connection.
+    pub fn enable_monitor_output(&mut self) {
         // Initialize JSON monitor output
         let pub_rate_hz = self.daemon_config.publishing.pub_rate_hz;
         let dynamic_mode = self.daemon_config.command.stream_robot_state == "dynamic";
-        let decimal_places = self.daemon_config.publishing.decimal_places.unwrap_or(4);
+        let debug_rtde = self.daemon_config.publishing.debug_rtde.unwrap_or(false);
+        let wrench_rate_hz = self.daemon_config.publishing.wrench_rate_hz.unwrap_or(0);
+        let joint_diag_rate_hz = self.daemon_config.publishing.joint_diag_rate_hz.unwrap_or(0);
+        
+        self.monitor_output = Some(self.build_monitor_output());
         
-        self.monitor_output = Some(MonitorOutput::new(pub_rate_hz, dynamic_mode, decimal_places));
+        // Give the stream a full timeout period to deliver its first package
+        self.last_rtde_package = Some(Instant::now());
+        self.rtde_stale = false;
         
         info!("RTDE monitoring started with JSON output");
         info!("Publication rate: {}Hz, Dynamic mode: {}", pub_rate_hz, dynamic_mode);
-        Ok(())
+        if debug_rtde {
+            info!("Raw RTDE debug output enabled (rtde_debug events)");
+        }
+        if wrench_rate_hz > 0 {
+            info!("Wrench output enabled at {}Hz (wrench events)", wrench_rate_hz);
+        }
+        if joint_diag_rate_hz > 0 {
+            info!("Joint diagnostics enabled at {}Hz (joints_diag events)", joint_diag_rate_hz);
+        }
     }
     
-    /// Send a command to the dashboard interface
-    async fn send_dashboard_command(&mut self, command: &str) -> Result<String> {
-        let socket = self.dashboard_socket.as_mut()
-            .ok_or_else(|| anyhow!("Dashboard socket not connected"))?;
-        
-        // Send command
-        let cmd_with_newline = format!("{}\n", command);
-        socket.write_all(cmd_with_newline.as_bytes())
-            .context("Failed to send dashboard command")?;
+    /// Re-read the config file this controller was created from and apply it
+    /// 
+    /// See `apply_config` for what changes live.
+    pub fn reload_config(&mut self) -> Result<ConfigReload> {
+        let path = self.config_path.clone().ok_or_else(|| {
+            URError::NotReady("Configuration was not loaded from a file".to_string())
+        })?;
+        let config = DaemonConfig::load_from_path(&path)?;
+        self.apply_config(config)
+    }
+    
+    /// Apply the settings of `config` that are safe to change while connected
+    /// 
+    /// Publishing rates and precision, motion limits and the buffer clear
+    /// interval take effect at once, without touching the robot connections.
+    /// Other changes (host, ports, monitoring, recording) are reported as
+    /// ignored. An invalid config is rejected and nothing changes.
+    pub fn apply_config(&mut self, config: DaemonConfig) -> Result<ConfigReload> {
+        if let Err(errors) = config.validate() {
+            let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            return Err(URError::Config(format!("Invalid configuration, nothing applied: {}", errors.join("; "))).into());
+        }
         
-        // Read response
-        let mut buffer = [0u8; 1024];
-        let bytes_read = socket.read(&mut buffer)
-            .context("Failed to read dashboard response")?;
+        let updated = self.daemon_config.with_live_settings(&config);
+        let reload = ConfigReload {
+            applied: changed_fields(&self.daemon_config, &updated),
+            ignored: changed_fields(&updated, &config),
+        };
+        self.config = updated.clone();
+        self.daemon_config = updated;
         
-        let response = String::from_utf8_lossy(&buffer[..bytes_read])
-            .trim()
-            .to_string();
+        // Rebuild monitor output without restarting sequence numbers or change detection
+        if let Some(previous) = self.monitor_output.take() {
+            self.monitor_output = Some(self.build_monitor_output().continue_from(previous));
+        }
         
-        Ok(response)
+        info!("Configuration reloaded: {} field(s) applied", reload.applied.len());
+        if !reload.ignored.is_empty() {
+            warn!("Restart required for: {}", reload.ignored.join(", "));
+        }
+        Ok(reload)
     }
     
-    /// Wait for robot to reach a specific state
-    async fn wait_for_robot_state(&mut self, target_state: &str, timeout_seconds: u64) -> Result<()> {
-        let start_time = std::time::Instant::now();
-        let timeout = Duration::from_secs(timeout_seconds);
-        
-        loop {
-            let current_state = self.send_dashboard_command("robotmode").await?;
-            
-            if current_state.contains(target_state) {
-                return Ok(());
-            }
-            
-            if start_time.elapsed() > timeout {
-                return Err(anyhow!("Timeout waiting for robot state '{}' (current: {})", target_state, current_state));
-            }
-            
-            tokio::time::sleep(Duration::from_millis(500)).await;
-        }
+    /// Send a command to the dashboard interface
+    async fn send_dashboard_command(&self, command: &str) -> Result<String> {
+        self.dashboard.send(command)
     }
     
     /// Get a mutable reference to the interpreter client
     pub fn interpreter_mut(&mut self) -> Result<&mut InterpreterClient> {
-        self.interpreter.as_mut()
-            .ok_or_else(|| anyhow!("Interpreter not initialized"))
+        self.interpreter.as_mut().ok_or_else(|| {
+            let message = match self.state {
+                RobotState::PowerOff | RobotState::Idle => {
+                    "Interpreter mode not started - the robot is not powered on and robot.auto_power_on is false; use @power on"
+                }
+                _ => "Interpreter not initialized",
+            };
+            URError::NotReady(message.to_string()).into()
+        })
     }
     
     /// Get the current robot state
@@ -294,6 +868,30 @@ impl RobotController {
         &self.state
     }
     
+    /// Subscribe to state transitions
+    /// 
+    /// The receiver starts at the current state; `changed()` resolves on each
+    /// transition. Intermediate states can be skipped by a slow subscriber.
+    pub fn subscribe_state(&self) -> watch::Receiver<RobotState> {
+        self.state_sender.subscribe()
+    }
+    
+    /// The only place the state changes, so subscribers see every transition
+    fn set_state(&mut self, state: RobotState) {
+        if state == self.state {
+            return;
+        }
+        info!("Controller state {} -> {}", self.state.name(), state.name());
+        self.state_sender.send_replace(state.clone());
+        self.state = state;
+        self.emergency_halted = false;
+    }
+    
+    /// Whether the last state change was an emergency halt (`halt` sent through an `AbortHandle`)
+    pub fn is_emergency_halted(&self) -> bool {
+        self.emergency_halted
+    }
+    
     /// Check if the robot is ready for commands
     pub fn is_ready(&self) -> bool {
         matches!(self.state, RobotState::Running) && self.interpreter.is_some()
@@ -319,49 +917,134 @@ impl RobotController {
         (
             self.interpreter.is_some(),
             self.primary_socket.is_some(),
-            self.dashboard_socket.is_some(),
-            self.monitor_output.is_some(),
+            self.dashboard.is_connected(),
+            self.monitor_output.is_some() && !self.rtde_stale,
         )
     }
     
+    /// Whether the watchdog has flagged the RTDE stream as stale
+    pub fn is_monitoring_stale(&self) -> bool {
+        self.rtde_stale
+    }
+    
+    /// Time since the last RTDE data package (None before monitoring starts)
+    pub fn rtde_data_age(&self) -> Option<Duration> {
+        self.last_rtde_package.map(|received| received.elapsed())
+    }
+    
+    /// Check RTDE data freshness against `publishing.rtde_timeout_ms`
+    /// 
+    /// Emits an `error` event when the stream first goes stale. Returns true
+    /// while monitoring is stale, i.e. `robot_status` is no longer current.
+    pub fn check_rtde_watchdog(&mut self) -> bool {
+        let Some(timeout) = self.daemon_config.publishing.rtde_timeout() else {
+            return false;
+        };
+        let Some(age) = self.rtde_data_age() else {
+            return false;
+        };
+        
+        if age > timeout && !self.rtde_stale {
+            self.rtde_stale = true;
+            let message = format!(
+                "No RTDE data for {} ms (timeout {} ms) - robot status is stale",
+                age.as_millis(),
+                timeout.as_millis()
+            );
+            error!("{}", message);
+            crate::json_output::output::error(crate::json_output::ErrorEvent::new(&message, None).with_error_code("TIMEOUT"));
+        }
+        self.rtde_stale
+    }
+    
+    /// Get the arm's model, serial number and software version
+    pub fn robot_info(&self) -> &RobotInfo {
+        &self.robot_info
+    }
+    
     /// Get the latest robot status from RTDE monitoring
     pub fn get_robot_status(&self) -> &RobotStatus {
         &self.robot_status
     }
     
+    /// Why URScript should be refused up front, if the latest RTDE data shows it can't run
+    /// 
+    /// Without fresh RTDE data nothing is known, so commands are let through.
+    /// Disabled by `command.require_running: false`.
+    pub fn command_blocked_reason(&self) -> Option<String> {
+        if let Some(fault) = &self.fault_hold {
+            return Some(format!("Paused on fault ({}) - recover the robot, then @resume", fault));
+        }
+        if !self.daemon_config.command.require_running() || self.rtde_stale {
+            return None;
+        }
+        self.robot_status.not_running_reason()
+    }
+    
+    /// The fault holding URScript since it was first seen, if any
+    pub fn fault_hold(&self) -> Option<&str> {
+        self.fault_hold.as_deref()
+    }
+    
+    /// Release the fault hold, once RTDE shows the robot has recovered
+    /// 
+    /// Returns false if nothing was held.
+    pub fn clear_fault_hold(&mut self) -> Result<bool> {
+        if self.fault_hold.is_none() {
+            return Ok(false);
+        }
+        if let Some(reason) = self.robot_status.fault_reason().filter(|_| !self.rtde_stale) {
+            return Err(URError::RobotState(format!("still faulted, recover the robot first: {}", reason)).into());
+        }
+        info!("Fault hold released");
+        self.fault_hold = None;
+        Ok(true)
+    }
+    
     /// Attempt to reconnect and reinitialize the robot for interpreter mode
     pub async fn reconnect(&mut self) -> Result<()> {
         info!("Attempting robot reconnection and reinitialization");
         
         // Close existing connections
         self.primary_socket = None;
-        self.dashboard_socket = None;
+        self.dashboard.disconnect();
         self.interpreter = None;
-        self.state = RobotState::Disconnected;
+        self.abort_handle.set_primary_socket(None);
+        self.abort_handle.set_interpreter_signal(None);
+        self.set_state(RobotState::Disconnected);
         self.robot_status = RobotStatus::default();
         
         // Attempt full reinitialization sequence
         match self.initialize_connections_and_interpreter().await {
-            Ok(_) => {
+            Ok(true) => {
                 info!("Robot reconnection successful");
-                self.state = RobotState::Running;
+                self.set_state(RobotState::Running);
+                Ok(())
+            }
+            Ok(false) => {
+                info!("Robot reconnected without interpreter mode - waiting for @power on");
                 Ok(())
             }
             Err(e) => {
                 error!("Robot reconnection failed: {}", e);
-                self.state = RobotState::Error(format!("Reconnection failed: {}", e));
+                self.set_state(RobotState::Error(format!("Reconnection failed: {}", e)));
                 Err(e)
             }
         }
     }
     
     /// Internal method for connection and interpreter initialization
-    async fn initialize_connections_and_interpreter(&mut self) -> Result<()> {
+    /// 
+    /// Returns false if the robot was left unpowered (`robot.auto_power_on: false`)
+    /// and interpreter mode was not started.
+    async fn initialize_connections_and_interpreter(&mut self) -> Result<bool> {
         // Step 1: Connect to primary socket
         self.connect_primary().await?;
         
         // Step 2: Assess and prepare robot state
-        self.assess_and_prepare_robot().await?;
+        if !self.assess_and_prepare_robot().await? {
+            return Ok(false);
+        }
         
         // Step 3: Start interpreter mode
         self.start_interpreter_mode().await?;
@@ -369,36 +1052,298 @@ impl RobotController {
         // Step 4: Validate interpreter mode
         self.validate_interpreter().await?;
         
+        Ok(true)
+    }
+    
+    /// Power on, release brakes and start interpreter mode on request
+    /// 
+    /// The explicit step for `robot.auto_power_on: false`; with the robot
+    /// already running it only starts interpreter mode if that is missing.
+    pub async fn power_on(&mut self) -> Result<()> {
+        let interpreter = self.power_on_task().await?;
+        self.complete_power_on(interpreter);
         Ok(())
     }
+    
+    /// The `power_on` sequence, run without borrowing the controller
+    /// 
+    /// Resolves to the interpreter it started, if one was missing, to be handed
+    /// to `complete_power_on`. Powering on takes up to ~30s of dashboard polling,
+    /// so a shared controller should be unlocked before awaiting it.
+    pub fn power_on_task(&self) -> impl std::future::Future<Output = Result<Option<InterpreterClient>>> + Send + 'static {
+        let dashboard = self.dashboard.clone();
+        let interpreter_start = self.interpreter.is_none().then(|| (
+            self.primary_socket.as_ref().map(TcpStream::try_clone),
+            self.config.robot.host.clone(),
+            self.config.robot.ports.interpreter,
+            self.interpreter_config(),
+        ));
+        
+        async move {
+            dashboard.power_on_and_release_brakes().await?;
+            
+            let Some((primary_socket, host, port, interpreter_config)) = interpreter_start else {
+                return Ok(None);
+            };
+            let mut primary_socket = primary_socket
+                .ok_or_else(|| URError::Connection("Primary socket not connected".to_string()))?
+                .context("Failed to clone primary socket")?;
+            send_interpreter_script(&mut primary_socket).await?;
+            connect_interpreter(&host, port, &interpreter_config).await.map(Some)
+        }
+    }
+    
+    /// Finish `power_on_task`: install its interpreter and mark the robot running
+    /// 
+    /// An interpreter connected in the meantime (e.g. by `reconnect`) is kept.
+    pub fn complete_power_on(&mut self, interpreter: Option<InterpreterClient>) {
+        if let Some(interpreter) = interpreter {
+            if self.interpreter.is_none() {
+                self.install_interpreter(interpreter);
+            }
+        }
+        
+        self.set_state(RobotState::Running);
+        info!("Robot powered on and ready for commands");
+    }
 
     /// Send immediate abort through primary socket (bypasses interpreter queue)
     /// This should be faster than sending abort through the interpreter
     pub fn emergency_abort(&mut self) -> Result<()> {
-        if let Some(primary_socket) = &mut self.primary_socket {
-            info!("Sending emergency abort through primary socket");
-            
-            // Send abort command directly to primary socket
-            let abort_script = "halt\n";
-            
-            primary_socket.write_all(abort_script.as_bytes())
-                .context("Failed to send emergency abort to primary socket")?;
-            
-            info!("Emergency abort sent through primary socket");
-            
-            // Signal the interpreter to abort any pending operations
-            if let Some(interpreter) = &self.interpreter {
-                interpreter.signal_emergency_abort();
-                info!("Signaled interpreter to abort pending operations");
-            }
-            
-            // Mark that we've sent halt - interpreter will be unresponsive
-            self.state = RobotState::Error("Emergency halted".to_string());
-            
-            Ok(())
-        } else {
-            Err(anyhow!("Primary socket not connected"))
+        self.abort_handle.abort()?;
+        self.mark_emergency_halted();
+        Ok(())
+    }
+    
+    /// Handle for sending the emergency abort without locking the controller
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.abort_handle.clone()
+    }
+    
+    /// Record that `halt` was sent through an `AbortHandle`
+    /// 
+    /// The interpreter will be unresponsive, so cleanup on shutdown is skipped.
+    pub fn mark_emergency_halted(&mut self) {
+        self.set_state(RobotState::Error("Emergency halted".to_string()));
+        self.emergency_halted = true;
+    }
+    
+    /// Send a dashboard program command and turn a nack reply into an error
+    async fn program_command(&self, command: &str, success_prefix: &str) -> Result<String> {
+        self.dashboard.program_command(command, success_prefix)
+    }
+    
+    /// Load an installation-level program (`<name>.urp`) through the dashboard
+    /// 
+    /// Playing a loaded program replaces the interpreter mode program, so
+    /// `reconnect` is needed before streaming URScript again.
+    pub async fn load_program(&mut self, name: &str) -> Result<()> {
+        let file = if name.ends_with(".urp") { name.to_string() } else { format!("{}.urp", name) };
+        let response = self.program_command(&format!("load {}", file), "Loading program").await?;
+        
+        info!("Program loaded: {}", response);
+        Ok(())
+    }
+    
+    /// Start (or resume) the loaded program through the dashboard
+    pub async fn play_program(&mut self) -> Result<()> {
+        let response = self.program_command("play", "Starting program").await?;
+        
+        info!("Program playing: {}", response);
+        Ok(())
+    }
+    
+    /// Stop the running program through the dashboard
+    pub async fn stop_program(&mut self) -> Result<()> {
+        let response = self.program_command("stop", "Stopped").await?;
+        
+        info!("Program stopped: {}", response);
+        Ok(())
+    }
+    
+    /// Pause the running program through the dashboard, holding motion in place
+    /// 
+    /// Unlike an abort the interpreter buffer is kept, so `resume_program`
+    /// continues from where the robot stopped.
+    pub async fn pause_program(&mut self) -> Result<()> {
+        let response = self.program_command("pause", "Pausing program").await?;
+        
+        info!("Program paused: {}", response);
+        Ok(())
+    }
+    
+    /// Query the loaded program and whether it is running from the dashboard
+    /// 
+    /// Replies are read whole whatever their length (program paths vary), so a
+    /// long path can't leave part of a reply to be mistaken for the next one.
+    pub async fn get_program_state(&mut self) -> Result<ProgramState> {
+        let loaded = self.send_dashboard_command("get loaded program").await?;
+        let program_state = self.send_dashboard_command("programState").await?;
+        let running = self.send_dashboard_command("running").await?;
+        
+        Ok(ProgramState::from_replies(&loaded, &program_state, &running))
+    }
+    
+    /// Resume a program previously paused with `pause_program`
+    pub async fn resume_program(&mut self) -> Result<()> {
+        self.play_program().await
+    }
+    
+    /// Set a standard digital output through the interpreter
+    pub fn set_digital_output(&mut self, pin: u8, value: bool) -> Result<()> {
+        if pin >= STANDARD_DIGITAL_IO {
+            return Err(URError::InvalidRequest(format!(
+                "Digital output {} out of range (0-{})", pin, STANDARD_DIGITAL_IO - 1
+            )).into());
         }
+        
+        let script = format!("set_standard_digital_out({}, {})", pin, if value { "True" } else { "False" });
+        let result = self.interpreter_mut()?.execute_command(&script)?;
+        if result.rejected {
+            return Err(URError::Rejected(format!("'{}': {}", script, result.raw_reply)).into());
+        }
+        
+        info!("Digital output {} set to {}", pin, value);
+        Ok(())
+    }
+    
+    /// Assign a global interpreter variable (`name = value`)
+    /// 
+    /// `value` must already be a URScript literal. The variable lives until the
+    /// interpreter buffer is next cleared.
+    pub fn set_variable(&mut self, name: &str, value: &str) -> Result<()> {
+        let script = format!("{} = {}", name, value);
+        let result = self.interpreter_mut()?.execute_command(&script)?;
+        if result.rejected {
+            return Err(URError::Rejected(format!("'{}': {}", script, result.raw_reply)).into());
+        }
+        
+        info!("Variable {} set to {}", name, value);
+        Ok(())
+    }
+    
+    /// Send a script that reports through the readback registers
+    /// 
+    /// `build` gets the request's marker and the registers (`command.ik_registers`)
+    /// and returns the script. `what` names the request in a rejection.
+    pub fn send_readback(&mut self, what: &str, build: impl FnOnce(i32, (u8, u8)) -> String) -> Result<RegisterReadback> {
+        self.readback_sequence = self.readback_sequence % 1_000_000 + 1;
+        let sequence = self.readback_sequence;
+        let registers = self.daemon_config.command.ik_registers();
+        
+        let result = self.interpreter_mut()?.execute_command(&build(sequence, registers))?;
+        if result.rejected {
+            return Err(URError::Rejected(format!("{}: {}", what, result.raw_reply)).into());
+        }
+        
+        Ok(RegisterReadback {
+            command_id: result.id,
+            sequence,
+            registers,
+            host: self.config.robot.host.clone(),
+            port: self.config.robot.ports.rtde,
+            timeout: Duration::from_secs_f64(self.config.robot.connection.timeout),
+        })
+    }
+    
+    /// Whether the interpreter has executed command `command_id`
+    pub fn command_executed(&mut self, command_id: u32) -> Result<bool> {
+        Ok(self.interpreter_mut()?.get_last_executed_id()? >= command_id)
+    }
+    
+    /// Read the standard digital inputs from the latest RTDE data
+    pub fn get_digital_inputs(&self) -> Result<[bool; STANDARD_DIGITAL_IO as usize]> {
+        let bits = self.robot_status.digital_input_bits.ok_or_else(|| URError::NotReady(
+            "Digital inputs unavailable - RTDE monitoring is not running or the controller lacks actual_digital_input_bits".to_string()
+        ))?;
+        
+        Ok(std::array::from_fn(|pin| bits & (1 << pin) != 0))
+    }
+    
+    /// Read the standard and tool analog inputs from the latest RTDE data
+    pub fn get_analog_inputs(&self) -> Result<AnalogInputs> {
+        self.robot_status.analog_inputs.ok_or_else(|| URError::NotReady(
+            "Analog inputs unavailable - RTDE monitoring is not running or the controller lacks the analog input variables".to_string()
+        ).into())
+    }
+    
+    /// Read the TCP speed from the latest RTDE data
+    pub fn get_tcp_speed(&self) -> Result<[f64; 6]> {
+        self.robot_status.tcp_speed.ok_or_else(|| URError::NotReady(
+            "TCP speed unavailable - RTDE monitoring is not running or the recipe lacks actual_TCP_speed".to_string()
+        ).into())
+    }
+    
+    /// Whether any joint turns faster than `threshold` (rad/s) in the latest RTDE data
+    /// 
+    /// None when joint speeds aren't available, so callers can tell "stopped"
+    /// from "unknown".
+    pub fn is_moving(&self, threshold: f64) -> Option<bool> {
+        self.robot_status.joint_speeds.map(|speeds| speeds.iter().any(|speed| speed.abs() > threshold))
+    }
+    
+    /// Set the robot-wide speed slider through an RTDE input recipe
+    /// 
+    /// Scales every motion the controller executes, including programs run
+    /// outside urd; `a=`/`v=` arguments of individual commands are unchanged.
+    /// The slider keeps its value after the RTDE connection closes.
+    pub fn set_speed_scaling(&mut self, fraction: f64) -> Result<()> {
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(URError::InvalidRequest(format!(
+                "Speed fraction {} out of range (0.0-1.0)", fraction
+            )).into());
+        }
+        
+        let mut client = RTDEClient::new(&self.config.robot.host, self.config.robot.ports.rtde)?;
+        client.connect()?;
+        client.negotiate_protocol_version(2)?;
+        let recipe_id = client.setup_input_recipe(&["speed_slider_mask", "speed_slider_fraction"])?;
+        client.start_data_synchronization()?;
+        client.send_speed_slider(recipe_id, fraction)?;
+        
+        self.speed_scaling = Some(fraction);
+        info!("Speed slider set to {:.0}%", fraction * 100.0);
+        Ok(())
+    }
+    
+    /// Speed slider fraction last set by urd (None if never set this session)
+    pub fn speed_scaling(&self) -> Option<f64> {
+        self.speed_scaling
+    }
+    
+    /// Unlock a protective stop through the dashboard
+    /// 
+    /// The controller refuses the unlock for 5 seconds after the stop occurred.
+    pub async fn unlock_protective_stop(&mut self) -> Result<()> {
+        self.dashboard.unlock_protective_stop()
+    }
+    
+    /// Dismiss the safety popup on the teach pendant
+    pub async fn close_safety_popup(&mut self) -> Result<()> {
+        self.dashboard.close_safety_popup()
+    }
+    
+    /// Recover from a protective stop and bring the robot back to RUNNING
+    /// 
+    /// See `recover_task`, which this runs with the controller borrowed throughout.
+    pub async fn recover(&mut self) -> Result<String> {
+        self.recover_task().await
+    }
+    
+    /// Protective stop recovery that runs without borrowing the controller
+    /// 
+    /// The latest RTDE `safety_mode` decides what is attempted: NORMAL and
+    /// REDUCED need nothing, a protective stop is unlocked, and every other
+    /// mode (e-stops, safeguard stops, faults, violations) needs an operator
+    /// and is reported as an error. The future resolves to a description of
+    /// what was done. Recovery takes up to ~20s of dashboard polling, so a
+    /// shared controller should be unlocked before awaiting it. The interpreter
+    /// program may have been stopped, so `reconnect` can be needed before
+    /// streaming URScript again.
+    pub fn recover_task(&self) -> impl std::future::Future<Output = Result<String>> + Send + 'static {
+        let dashboard = self.dashboard.clone();
+        let safety_mode = self.robot_status.safety_mode;
+        async move { dashboard.recover(safety_mode).await }
     }
     
     /// Process robot state data and output JSON monitoring
@@ -411,6 +1356,7 @@ impl RobotController {
     /// * `runtime_state` - Runtime state from RTDE
     /// * `robot_timestamp` - Robot's internal timestamp (rtime, seconds since power-on) - None if not available  
     /// * `wire_timestamp` - System timestamp when data was received by daemon (stime, Unix epoch)
+    #[allow(clippy::too_many_arguments)]
     pub fn process_monitoring_data(&mut self, 
         joint_positions: [f64; 6], 
         tcp_pose: [f64; 6], 
@@ -431,13 +1377,34 @@ impl RobotController {
             tcp_pose,
             joint_positions,
             last_updated: wire_timestamp,
+            ..std::mem::take(&mut self.robot_status)
         };
         
+        // Latch the fault, so commands stay held even if it clears before the client notices.
+        // Only a safety mode the controller actually reported can latch.
+        if self.fault_hold.is_none() && safety_mode >= 0 && self.daemon_config.command.pause_on_fault() {
+            if let Some(reason) = self.robot_status.fault_reason() {
+                warn!("Holding URScript until recovery and @resume: {}", reason);
+                crate::json_output::output_event(&serde_json::json!({
+                    "timestamp": crate::json_output::current_timestamp(),
+                    "type": "paused_on_fault",
+                    "reason": reason,
+                    "safety_mode_name": self.robot_status.safety_mode_name,
+                }));
+                self.fault_hold = Some(reason);
+            }
+        }
+        
         if let Some(monitor_output) = &mut self.monitor_output {
             // Check and output combined position data (TCP + joints)
-            if monitor_output.should_output_position(tcp_pose, joint_positions, wire_timestamp) {
-                let position_data = PositionData::new_rounded(tcp_pose, joint_positions, robot_timestamp, wire_timestamp, monitor_output.decimal_places);
-                monitor_output.output_position(&position_data);
+            if let Some((tcp_pose, joint_positions)) = monitor_output.coalesce_position(tcp_pose, joint_positions, wire_timestamp) {
+                let position_data = PositionData::new_rounded(tcp_pose, joint_positions, robot_timestamp, wire_timestamp, monitor_output.decimal_places_for("position"));
+                monitor_output.output_position(position_data);
+            }
+            
+            // Program start/stop as its own event, so clients don't have to diff robot_state
+            if let Some(previous) = monitor_output.runtime_transition(runtime_state) {
+                monitor_output.output_runtime_event(RuntimeEventData::new(previous, runtime_state, robot_timestamp, wire_timestamp));
             }
             
             // Check and output robot state (never rate limited)
@@ -452,7 +1419,109 @@ impl RobotController {
                     robot_timestamp,
                     wire_timestamp,
                 );
-                monitor_output.output_robot_state(&robot_state_data);
+                monitor_output.output_robot_state(robot_state_data);
+            }
+        }
+    }
+    
+    /// Process one decoded RTDE data package from the monitoring recipe
+    /// 
+    /// Extracts the robot state, wrench and debug outputs from the raw variable map.
+    pub fn process_rtde_package(&mut self, data: &HashMap<String, Vec<f64>>, wire_timestamp: f64) {
+        self.last_rtde_package = Some(Instant::now());
+        if self.rtde_stale {
+            info!("RTDE data flowing again - monitoring no longer stale");
+            self.rtde_stale = false;
+        }
+        
+        let as_array = |name: &str| -> [f64; 6] {
+            data.get(name)
+                .and_then(|v| <[f64; 6]>::try_from(v.as_slice()).ok())
+                .unwrap_or([0.0; 6])
+        };
+        // Modes missing from the recipe stay unknown (-1) rather than reading as mode 0
+        let as_i32 = |name: &str| -> i32 {
+            data.get(name)
+                .and_then(|v| v.first())
+                .copied()
+                .unwrap_or(-1.0) as i32
+        };
+        
+        // Robot timestamp (rtime = seconds since robot power-on)
+        let robot_timestamp = data.get("timestamp")
+            .and_then(|v| v.first())
+            .copied();
+        
+        self.process_monitoring_data(
+            as_array("actual_q"),
+            as_array("actual_TCP_pose"),
+            as_i32("robot_mode"),
+            as_i32("safety_mode"),
+            as_i32("runtime_state"),
+            robot_timestamp,
+            wire_timestamp
+        );
+        let as_bits = |name: &str| data.get(name).and_then(|v| v.first()).map(|&bits| bits as u64);
+        self.robot_status.digital_input_bits = as_bits("actual_digital_input_bits");
+        self.robot_status.digital_output_bits = as_bits("actual_digital_output_bits");
+        self.robot_status.joint_speeds = data.contains_key("actual_qd").then(|| as_array("actual_qd"));
+        self.robot_status.tcp_speed = data.contains_key("actual_TCP_speed").then(|| as_array("actual_TCP_speed"));
+        self.robot_status.analog_inputs = data.contains_key("standard_analog_input0").then(|| {
+            let value = |name: &str| data.get(name).and_then(|v| v.first()).copied().unwrap_or(0.0);
+            AnalogInputs {
+                standard: [value("standard_analog_input0"), value("standard_analog_input1")],
+                tool: [value("tool_analog_input0"), value("tool_analog_input1")],
+                tool_output_voltage: data.contains_key("tool_output_voltage").then(|| as_i32("tool_output_voltage")),
+            }
+        });
+        if data.contains_key("actual_TCP_force") {
+            self.process_wrench_data(as_array("actual_TCP_force"), robot_timestamp, wire_timestamp);
+        }
+        if data.contains_key("joint_temperatures") && data.contains_key("actual_current") {
+            self.process_joint_diag_data(as_array("joint_temperatures"), as_array("actual_current"), robot_timestamp, wire_timestamp);
+        }
+        self.process_rtde_debug(data, robot_timestamp, wire_timestamp);
+    }
+    
+    /// Output TCP force/torque from RTDE `actual_TCP_force` when wrench output is enabled
+    pub fn process_wrench_data(&mut self, 
+        tcp_force: [f64; 6], 
+        robot_timestamp: Option<f64>,
+        wire_timestamp: f64
+    ) {
+        if let Some(monitor_output) = &mut self.monitor_output {
+            if monitor_output.should_output_wrench(wire_timestamp) {
+                let wrench_data = WrenchData::new_rounded(tcp_force, robot_timestamp, wire_timestamp, monitor_output.decimal_places_for("wrench"));
+                monitor_output.output_wrench(wrench_data);
+            }
+        }
+    }
+    
+    /// Output joint temperatures and currents when joint diagnostics are enabled
+    pub fn process_joint_diag_data(&mut self, 
+        temperatures: [f64; 6], 
+        currents: [f64; 6], 
+        robot_timestamp: Option<f64>,
+        wire_timestamp: f64
+    ) {
+        if let Some(monitor_output) = &mut self.monitor_output {
+            if monitor_output.should_output_joint_diag(wire_timestamp) {
+                let diag_data = JointDiagData::new_rounded(temperatures, currents, robot_timestamp, wire_timestamp, monitor_output.decimal_places_for("joints_diag"));
+                monitor_output.output_joint_diag(diag_data);
+            }
+        }
+    }
+    
+    /// Output the raw decoded RTDE data package when debug output is enabled
+    pub fn process_rtde_debug(&mut self, 
+        data: &HashMap<String, Vec<f64>>, 
+        robot_timestamp: Option<f64>,
+        wire_timestamp: f64
+    ) {
+        if let Some(monitor_output) = &mut self.monitor_output {
+            if monitor_output.should_output_rtde_debug(wire_timestamp) {
+                let debug_data = RtdeDebugData::new(data, robot_timestamp, wire_timestamp);
+                monitor_output.output_rtde_debug(debug_data);
             }
         }
     }
@@ -484,17 +1553,165 @@ impl RobotController {
         
         // Close connections
         self.primary_socket = None;
-        self.dashboard_socket = None;
+        self.dashboard.disconnect();
         self.interpreter = None;
+        self.abort_handle.set_primary_socket(None);
+        self.abort_handle.set_interpreter_signal(None);
         self.rtde_monitor = None;
         self.monitor_output = None;
+        self.monitoring_sender.send_replace(false);
         
-        self.state = RobotState::Disconnected;
+        self.set_state(RobotState::Disconnected);
         info!("Robot controller shutdown complete");
         Ok(())
     }
 }
 
+/// How long the termination token gets to execute when interpreter mode is validated
+const TOKEN_EXECUTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Send the script that starts interpreter mode through the primary socket
+async fn send_interpreter_script(primary_socket: &mut TcpStream) -> Result<()> {
+    info!("Starting interpreter mode");
+    
+    // Send interpreter mode activation script
+    let interpreter_script = "def ur_init():\n  textmsg(\"Starting interpreter mode\")\n  interpreter_mode()\nend\nur_init()\n";
+    
+    primary_socket.write_all(interpreter_script.as_bytes())
+        .context("Failed to send interpreter mode script")?;
+    
+    // Give it time to process
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+    
+    info!("Interpreter mode script sent");
+    Ok(())
+}
+
+/// Connect to interpreter mode and check that it runs statements and the termination token
+async fn connect_interpreter(host: &str, port: Option<u16>, interpreter_config: &crate::config::InterpreterConfig) -> Result<InterpreterClient> {
+    info!("Validating interpreter mode");
+    
+    // Try to connect to interpreter port
+    let mut interpreter = InterpreterClient::new(host, port)?;
+    
+    // Retry connection with the configured backoff policy
+    let max_attempts = interpreter_config.connect_max_attempts();
+    let mut attempts = 0;
+    
+    while attempts < max_attempts {
+        match interpreter.connect() {
+            Ok(_) => break,
+            Err(_) if attempts < max_attempts - 1 => {
+                attempts += 1;
+                let delay = interpreter_config.connect_retry_delay(attempts);
+                info!("Waiting for interpreter mode (attempt {}/{}, retrying in {}ms)", attempts, max_attempts, delay.as_millis());
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(URError::Connection(format!("Failed to connect to interpreter after {} attempts: {}", max_attempts, e)).into()),
+        }
+    }
+    
+    // Test interpreter with a simple command
+    let result = interpreter.execute_command("textmsg(\"Interpreter mode validated\")")?;
+    info!("Interpreter mode validated (command ID: {})", result.id);
+    
+    // Completion tracking waits on the token's executed ID, so it must be accepted
+    let token = interpreter_config.termination_token();
+    let token_result = interpreter.execute_command(&token)?;
+    if token_result.rejected || token_result.id == 0 {
+        return Err(URError::Config(format!(
+            "interpreter.termination_token '{}' does not produce a trackable command ID: {}",
+            token, token_result.raw_reply
+        )).into());
+    }
+    
+    // An ID alone isn't enough: waits would hang if the executed ID never reaches it
+    let deadline = Instant::now() + TOKEN_EXECUTION_TIMEOUT;
+    while interpreter.get_last_executed_id()? < token_result.id {
+        if Instant::now() >= deadline {
+            return Err(URError::Config(format!(
+                "interpreter.termination_token '{}' was accepted (ID {}) but not reported executed within {}s",
+                token, token_result.id, TOKEN_EXECUTION_TIMEOUT.as_secs()
+            )).into());
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    
+    Ok(interpreter)
+}
+
+/// Open a dashboard connection and consume its greeting
+fn open_dashboard(host: &str, port: u16, timeout: Duration) -> Result<TcpStream> {
+    let mut socket = TcpStream::connect((host, port))
+        .context("Failed to connect to dashboard")?;
+    socket.set_read_timeout(Some(timeout))
+        .context("Failed to set dashboard read timeout")?;
+    
+    // The dashboard greets each connection; consume it so replies line up with commands
+    read_dashboard_reply(&mut socket)
+        .context("Failed to read dashboard greeting")?;
+    Ok(socket)
+}
+
+/// Read one newline-terminated dashboard reply, across as many reads as it takes
+/// 
+/// The socket's read timeout bounds each read; hitting it is a `Timeout` error.
+fn read_dashboard_reply(socket: &mut impl Read) -> Result<String> {
+    let mut reply = Vec::new();
+    let mut buffer = [0u8; 1024];
+    
+    while !reply.contains(&b'\n') {
+        match socket.read(&mut buffer) {
+            Ok(0) => return Err(URError::Connection("Dashboard closed the connection".to_string()).into()),
+            Ok(n) => reply.extend_from_slice(&buffer[..n]),
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock) => {
+                return Err(URError::Timeout("Dashboard response timeout".to_string()).into());
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(URError::Connection(format!("Failed to read dashboard response: {}", e)).into()),
+        }
+    }
+    
+    Ok(String::from_utf8_lossy(&reply).trim().to_string())
+}
+
+/// Extract the first `count` double registers a readback request `sequence` published
+/// 
+/// Fails if the int register doesn't hold the request's marker, i.e. the
+/// registers are still from an earlier request.
+pub(crate) fn decode_readback(data: &HashMap<String, Vec<f64>>, sequence: i32, registers: (u8, u8), count: usize) -> Result<Vec<f64>> {
+    let (result_register, status_register) = registers;
+    let marker = readback_marker(data, status_register);
+    if marker != Some(sequence) {
+        return Err(URError::Protocol(format!(
+            "Readback result not published (register {} holds {:?}, expected {})",
+            status_register, marker, sequence
+        )).into());
+    }
+    
+    (0..count).map(|i| {
+        let register = format!("output_double_register_{}", result_register as usize + i);
+        data.get(&register).and_then(|values| values.first()).copied()
+            .ok_or_else(|| URError::Protocol(format!("{} missing from RTDE data", register)).into())
+    }).collect()
+}
+
+/// The marker in the readback int register, if the package has it
+fn readback_marker(data: &HashMap<String, Vec<f64>>, status_register: u8) -> Option<i32> {
+    data.get(&format!("output_int_register_{}", status_register))
+        .and_then(|values| values.first())
+        .map(|&marker| marker as i32)
+}
+
+/// Extract the joint solution for request `sequence` from an RTDE package of the `@ik` registers
+pub(crate) fn decode_inverse_kin(data: &HashMap<String, Vec<f64>>, sequence: i32, registers: (u8, u8)) -> Result<[f64; 6]> {
+    if readback_marker(data, registers.1) == Some(-sequence) {
+        return Err(URError::InvalidRequest("No inverse kinematics solution for the pose".to_string()).into());
+    }
+    let joints = decode_readback(data, sequence, registers, 6)?;
+    Ok(std::array::from_fn(|i| joints[i]))
+}
+
 impl Drop for RobotController {
     fn drop(&mut self) {
         // Best effort cleanup - but skip if robot was emergency halted
@@ -507,4 +1724,310 @@ impl Drop for RobotController {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_not_running_reason() {
+        let mut status = RobotStatus::default();
+        assert_eq!(status.not_running_reason(), None);
+        
+        status.robot_mode = 3;
+        status.robot_mode_name = "POWER_OFF".to_string();
+        assert_eq!(status.not_running_reason().unwrap(), "robot not in RUNNING mode: current mode POWER_OFF");
+        
+        status.robot_mode = 7;
+        status.safety_mode = 3;
+        status.safety_mode_name = "PROTECTIVE_STOP".to_string();
+        assert_eq!(status.not_running_reason().unwrap(), "robot not in NORMAL safety mode: current mode PROTECTIVE_STOP");
+        
+        status.safety_mode = 2;
+        assert_eq!(status.not_running_reason(), None);
+    }
+    
+    #[test]
+    fn test_motion_detection_from_joint_speeds() {
+        let mut controller = RobotController::new_with_config("config/default_config.yaml").unwrap();
+        let mut data = HashMap::new();
+        controller.process_rtde_package(&data, 0.0);
+        assert_eq!(controller.is_moving(0.01), None);
+        assert!(controller.get_tcp_speed().is_err());
+        
+        data.insert("actual_qd".to_string(), vec![0.0, -0.2, 0.0, 0.0, 0.0, 0.0]);
+        data.insert("actual_TCP_speed".to_string(), vec![0.05, 0.0, 0.0, 0.0, 0.0, 0.1]);
+        controller.process_rtde_package(&data, 0.0);
+        assert_eq!(controller.is_moving(0.01), Some(true));
+        assert_eq!(controller.is_moving(0.5), Some(false));
+        assert_eq!(controller.get_tcp_speed().unwrap()[0], 0.05);
+        
+        data.insert("actual_qd".to_string(), vec![0.001; 6]);
+        controller.process_rtde_package(&data, 0.0);
+        assert_eq!(controller.is_moving(0.01), Some(false));
+    }
+    
+    #[test]
+    fn test_basic_recipe_does_not_block_commands() {
+        let mut controller = RobotController::new_with_config("config/default_config.yaml").unwrap();
+        assert!(controller.daemon_config().command.require_running());
+        
+        // The basic fallback recipe has no robot_mode or safety_mode
+        let mut data = HashMap::new();
+        data.insert("timestamp".to_string(), vec![12.5]);
+        data.insert("actual_q".to_string(), vec![0.0; 6]);
+        data.insert("actual_TCP_pose".to_string(), vec![0.0; 6]);
+        controller.process_rtde_package(&data, 1.0);
+        
+        assert_eq!(controller.get_robot_status().robot_mode, -1);
+        assert_eq!(controller.get_robot_status().safety_mode, -1);
+        assert_eq!(controller.command_blocked_reason(), None);
+    }
+    
+    #[test]
+    fn test_apply_config_live_settings_only() {
+        let mut controller = RobotController::new_with_config("config/default_config.yaml").unwrap();
+        controller.enable_monitor_output();
+        let host = controller.config().robot.host.clone();
+        
+        let mut config = controller.daemon_config().clone();
+        config.publishing.pub_rate_hz = 0;
+        assert!(controller.apply_config(config.clone()).is_err());
+        assert_eq!(controller.daemon_config().publishing.pub_rate_hz, 10);
+        
+        config.publishing.pub_rate_hz = 25;
+        config.robot.ports.rtde = 40004;
+        let reload = controller.apply_config(config).unwrap();
+        assert_eq!(reload.applied, vec!["publishing.pub_rate_hz"]);
+        assert_eq!(reload.ignored, vec!["robot.ports.rtde"]);
+        assert_eq!(controller.daemon_config().publishing.pub_rate_hz, 25);
+        assert_eq!(controller.config().robot.host, host);
+        assert_eq!(controller.config().robot.ports.rtde, 30004);
+        
+        // The file hasn't changed, so reloading it puts back the original rate
+        let reload = controller.reload_config().unwrap();
+        assert_eq!(reload.applied, vec!["publishing.pub_rate_hz"]);
+        assert!(reload.ignored.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_monitoring_toggle_is_idempotent() {
+        let mut controller = RobotController::new_with_config("config/default_config.yaml").unwrap();
+        let receiver = controller.subscribe_monitoring();
+        assert!(!*receiver.borrow());
+        
+        assert!(controller.set_monitoring_enabled(true).await.unwrap());
+        assert!(!controller.set_monitoring_enabled(true).await.unwrap());
+        assert!(*receiver.borrow());
+        assert!(controller.get_connection_health().3);
+        
+        controller.robot_status.robot_mode = 3;
+        assert!(controller.set_monitoring_enabled(false).await.unwrap());
+        assert!(!controller.set_monitoring_enabled(false).await.unwrap());
+        assert!(!*receiver.borrow());
+        assert!(!controller.get_connection_health().3);
+        // The last status is dropped rather than left to go stale
+        assert_eq!(controller.command_blocked_reason(), None);
+    }
+    
+    #[test]
+    fn test_fault_holds_commands_until_cleared() {
+        let mut controller = RobotController::new_with_config("config/default_config.yaml").unwrap();
+        controller.process_monitoring_data([0.0; 6], [0.0; 6], 7, 3, 1, None, 1.0);
+        assert_eq!(controller.fault_hold(), Some("robot not in NORMAL safety mode: current mode PROTECTIVE_STOP"));
+        assert!(controller.clear_fault_hold().is_err());
+        
+        // Recovery alone doesn't release the hold
+        controller.process_monitoring_data([0.0; 6], [0.0; 6], 7, 1, 1, None, 2.0);
+        assert!(controller.command_blocked_reason().unwrap().contains("then @resume"));
+        
+        assert!(controller.clear_fault_hold().unwrap());
+        assert!(!controller.clear_fault_hold().unwrap());
+        assert_eq!(controller.command_blocked_reason(), None);
+    }
+    
+    #[test]
+    fn test_unreported_safety_mode_never_latches() {
+        let mut controller = RobotController::new_with_config("config/default_config.yaml").unwrap();
+        assert!(controller.daemon_config().command.pause_on_fault());
+        
+        let mut data = HashMap::new();
+        data.insert("actual_q".to_string(), vec![0.0; 6]);
+        data.insert("actual_TCP_pose".to_string(), vec![0.0; 6]);
+        controller.process_rtde_package(&data, 1.0);
+        controller.process_rtde_package(&data, 2.0);
+        assert_eq!(controller.fault_hold(), None);
+        assert!(!controller.clear_fault_hold().unwrap());
+        
+        // A hold latched by a reported fault can be released once the mode is no longer reported
+        controller.process_monitoring_data([0.0; 6], [0.0; 6], 7, 3, 1, None, 3.0);
+        assert!(controller.fault_hold().is_some());
+        controller.process_rtde_package(&data, 4.0);
+        assert!(controller.clear_fault_hold().unwrap());
+    }
+    
+    #[test]
+    fn test_abort_handle_halts_without_controller() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let socket = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut robot_side, _) = listener.accept().unwrap();
+        
+        let controller = RobotController::new_with_config("config/default_config.yaml").unwrap();
+        let handle = controller.abort_handle();
+        assert!(handle.abort().is_err());
+        
+        let signal = Arc::new(AtomicBool::new(false));
+        handle.set_primary_socket(Some(socket));
+        handle.set_interpreter_signal(Some(signal.clone()));
+        
+        // The controller mutex is held elsewhere; the handle doesn't need it
+        let shared = Arc::new(tokio::sync::Mutex::new(controller));
+        let _guard = shared.try_lock().unwrap();
+        handle.abort().unwrap();
+        
+        let mut sent = [0u8; 5];
+        robot_side.read_exact(&mut sent).unwrap();
+        assert_eq!(&sent, b"halt\n");
+        assert!(signal.load(Ordering::Relaxed));
+    }
+    
+    #[test]
+    fn test_dashboard_reopens_after_timeout() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let mut connections = Vec::new();
+            // The first connection never answers
+            for reply in [None, Some("Robotmode: RUNNING\n")] {
+                let (mut socket, _) = listener.accept().unwrap();
+                socket.write_all(b"Connected: Universal Robots Dashboard Server\n").unwrap();
+                let mut command = [0u8; 10];
+                socket.read_exact(&mut command).unwrap();
+                assert_eq!(&command, b"robotmode\n");
+                if let Some(reply) = reply {
+                    socket.write_all(reply.as_bytes()).unwrap();
+                }
+                connections.push(socket);
+            }
+        });
+        
+        let dashboard = Dashboard::default();
+        dashboard.connect("127.0.0.1", port, Duration::from_millis(200)).unwrap();
+        assert!(dashboard.send("robotmode").is_err());
+        assert!(!dashboard.is_connected());
+        
+        assert_eq!(dashboard.send("robotmode").unwrap(), "Robotmode: RUNNING");
+        assert!(dashboard.is_connected());
+        server.join().unwrap();
+        
+        // Disconnecting stops reconnects
+        dashboard.disconnect();
+        assert!(dashboard.send("robotmode").is_err());
+    }
+    
+    #[test]
+    fn test_state_transitions_notify_subscribers() {
+        let mut controller = RobotController::new_with_config("config/default_config.yaml").unwrap();
+        let mut receiver = controller.subscribe_state();
+        
+        controller.set_state(RobotState::Disconnected);
+        assert!(!receiver.has_changed().unwrap());
+        
+        controller.set_state(RobotState::Running);
+        controller.mark_emergency_halted();
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(*receiver.borrow_and_update(), RobotState::Error("Emergency halted".to_string()));
+        assert!(controller.is_emergency_halted());
+        
+        // Any later transition ends the halt
+        controller.set_state(RobotState::Error("Reconnection failed: refused".to_string()));
+        assert!(!controller.is_emergency_halted());
+    }
+    
+    #[test]
+    fn test_program_state_from_replies() {
+        let state = ProgramState::from_replies("Loaded program: /programs/pick_place.urp", "PLAYING pick_place.urp", "Program running: true");
+        assert_eq!(state.loaded.as_deref(), Some("/programs/pick_place.urp"));
+        assert_eq!(state.state, "PLAYING");
+        assert!(state.running);
+        
+        let state = ProgramState::from_replies("No program loaded", "STOPPED <unnamed>", "Program running: false");
+        assert_eq!(state, ProgramState { loaded: None, running: false, state: "STOPPED".to_string() });
+        
+        let state = ProgramState::from_replies("No program loaded", "could not understand: 'programState'", "Program running: false");
+        assert_eq!(state.state, "unknown");
+    }
+    
+    #[test]
+    fn test_dashboard_reply_spanning_reads() {
+        // Chain hands out each part in a separate read
+        let mut socket = "Loaded program: /programs/a/very/".as_bytes().chain("long/path.urp\n".as_bytes());
+        assert_eq!(read_dashboard_reply(&mut socket).unwrap(), "Loaded program: /programs/a/very/long/path.urp");
+        
+        let mut socket = "Robotmode: RUN".as_bytes();
+        assert!(read_dashboard_reply(&mut socket).unwrap_err().to_string().contains("closed the connection"));
+    }
+    
+    #[test]
+    fn test_decode_inverse_kin() {
+        let registers = (30, 40);
+        let mut data = HashMap::new();
+        for i in 0..6u8 {
+            data.insert(format!("output_double_register_{}", 30 + i), vec![i as f64 * 0.5]);
+        }
+        
+        data.insert("output_int_register_40".to_string(), vec![4.0]);
+        assert_eq!(decode_inverse_kin(&data, 4, registers).unwrap(), [0.0, 0.5, 1.0, 1.5, 2.0, 2.5]);
+        // A marker from an earlier request means the registers are stale
+        assert!(decode_inverse_kin(&data, 5, registers).unwrap_err().to_string().contains("not published"));
+        
+        data.insert("output_int_register_40".to_string(), vec![-5.0]);
+        assert!(decode_inverse_kin(&data, 5, registers).unwrap_err().to_string().contains("No inverse kinematics solution"));
+    }
+    
+    #[test]
+    fn test_robot_info_parsing() {
+        assert_eq!(RobotInfo::field_from_reply("UR10\n"), "UR10");
+        assert_eq!(RobotInfo::field_from_reply("20185500001"), "20185500001");
+        assert_eq!(RobotInfo::field_from_reply("could not understand: 'get serial number'"), "unknown");
+        assert_eq!(RobotInfo::field_from_reply(""), "unknown");
+        
+        assert_eq!(RobotInfo::version_from_reply("URSoftware 5.11.1.108318 (Mar 22 2021)"), "5.11.1.108318");
+        assert_eq!(RobotInfo::version_from_reply("URSoftware 3.15.7.106331 (Mar 15 2021)"), "3.15.7.106331");
+        assert_eq!(RobotInfo::version_from_reply("could not understand: 'PolyscopeVersion'"), "unknown");
+    }
+    
+    #[test]
+    fn test_robot_info_series_model() {
+        let info = |model: &str, software_version: &str| RobotInfo {
+            model: model.to_string(),
+            serial_number: "unknown".to_string(),
+            software_version: software_version.to_string(),
+        };
+        assert_eq!(info("UR10", "5.11.1.108318").series_model().as_deref(), Some("UR10e"));
+        assert_eq!(info("UR10", "3.15.7.106331").series_model().as_deref(), Some("UR10"));
+        assert_eq!(info("UR20", "5.14.0").series_model().as_deref(), Some("UR20"));
+        assert_eq!(info("UR5", "unknown").series_model().as_deref(), Some("UR5"));
+        assert_eq!(RobotInfo::default().series_model(), None);
+    }
+    
+    #[test]
+    fn test_rtde_watchdog_marks_monitoring_stale() {
+        let mut config = DaemonConfig::load_from_path("config/default_config.yaml").unwrap();
+        config.publishing.rtde_timeout_ms = Some(20);
+        let mut controller = RobotController::new(config);
+        controller.enable_monitor_output();
+        assert!(!controller.check_rtde_watchdog());
+        assert!(controller.get_connection_health().3);
+        
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(controller.check_rtde_watchdog());
+        assert!(!controller.get_connection_health().3);
+        
+        // Fresh data clears the stale flag
+        controller.process_rtde_package(&std::collections::HashMap::new(), 0.0);
+        assert!(!controller.is_monitoring_stale());
+        assert!(controller.get_connection_health().3);
+    }
+}
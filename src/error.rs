@@ -18,6 +18,18 @@ pub enum URError {
     #[error("Robot state error: {0}")]
     RobotState(String),
     
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    
+    #[error("Command rejected: {0}")]
+    Rejected(String),
+    
+    #[error("Robot not ready: {0}")]
+    NotReady(String),
+    
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+    
     #[error("Tokio task error: {0}")]
     Task(#[from] tokio::task::JoinError),
     
@@ -26,4 +38,79 @@ pub enum URError {
     
     #[error("YAML parsing error: {0}")]
     Yaml(#[from] serde_yaml::Error),
-}
\ No newline at end of file
+}
+
+impl URError {
+    /// Stable machine-readable code for this error
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            URError::Connection(_) => "CONNECTION_ERROR",
+            URError::Config(_) | URError::Yaml(_) => "CONFIG_ERROR",
+            URError::Protocol(_) => "PROTOCOL_ERROR",
+            URError::RobotState(_) => "ROBOT_STATE_ERROR",
+            URError::Timeout(_) => "TIMEOUT",
+            URError::Rejected(_) => "SCRIPT_REJECTED",
+            URError::NotReady(_) => "ROBOT_NOT_READY",
+            URError::InvalidRequest(_) => "INVALID_REQUEST",
+            URError::Task(_) => "INTERNAL_ERROR",
+            URError::Io(_) => "IO_ERROR",
+        }
+    }
+
+    /// Whether retrying the failed operation may succeed
+    ///
+    /// Connection-level failures are transient; rejections and invalid
+    /// requests are deterministic and will fail again.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, URError::Connection(_) | URError::Timeout(_) | URError::Io(_))
+    }
+}
+
+/// Map an `anyhow` error from the higher layers to a stable error code
+///
+/// Looks through the context chain for a typed `URError`, falling back to
+/// `INTERNAL_ERROR` for untyped errors.
+pub fn error_code_for(error: &anyhow::Error) -> &'static str {
+    error.chain()
+        .find_map(|e| e.downcast_ref::<URError>())
+        .map(URError::error_code)
+        .unwrap_or("INTERNAL_ERROR")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_error_code_mapping() {
+        assert_eq!(URError::Connection("x".into()).error_code(), "CONNECTION_ERROR");
+        assert_eq!(URError::Timeout("x".into()).error_code(), "TIMEOUT");
+        assert_eq!(URError::Rejected("x".into()).error_code(), "SCRIPT_REJECTED");
+        assert_eq!(URError::NotReady("x".into()).error_code(), "ROBOT_NOT_READY");
+        assert_eq!(URError::InvalidRequest("x".into()).error_code(), "INVALID_REQUEST");
+
+        assert!(URError::Timeout("x".into()).is_retryable());
+        assert!(!URError::Rejected("x".into()).is_retryable());
+    }
+
+    #[test]
+    fn test_error_code_through_anyhow_context() {
+        let error = Err::<(), _>(URError::Timeout("interpreter reply".into()))
+            .context("Failed to get last executed ID")
+            .unwrap_err();
+        assert_eq!(error_code_for(&error), "TIMEOUT");
+
+        let io_error: URError = std::io::Error::other("boom").into();
+        assert_eq!(io_error.error_code(), "IO_ERROR");
+
+        assert_eq!(error_code_for(&anyhow::anyhow!("untyped")), "INTERNAL_ERROR");
+    }
+
+    #[test]
+    fn test_unconnected_interpreter_reports_connection_error() {
+        let mut interpreter = crate::interpreter::InterpreterClient::new("localhost", None).unwrap();
+        let error = interpreter.execute_command("textmsg(\"hi\")").unwrap_err();
+        assert_eq!(error_code_for(&error), "CONNECTION_ERROR");
+    }
+}
@@ -3,6 +3,7 @@
 //! Pure Rust implementation of the Universal Robots interpreter interface.
 //! Based on the official interpreter examples from UR.
 
+use crate::URError;
 use anyhow::{anyhow, Context, Result};
 use regex::Regex;
 use std::io::{Read, Write};
@@ -27,12 +28,47 @@ pub struct InterpreterClient {
     emergency_abort_signal: Arc<AtomicBool>,
 }
 
+/// How the interpreter answered a statement
+/// 
+/// Discards carry a free-text reason; the kind is classified from that text so
+/// callers can tell a transient refusal from a permanent one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpreterReply {
+    /// Accepted (or answered, for state queries) with this ID
+    Acknowledged { id: u32 },
+    /// Too many uncleared statements; clearing the buffer makes room
+    BufferFull,
+    /// The statement does not compile and never will
+    SyntaxError { message: String },
+    /// Refused for any other reason
+    Discarded { reason: String },
+}
+
+impl InterpreterReply {
+    /// Classify a `discard: <reason>: <statement>` reply
+    /// 
+    /// The reason itself may contain colons, so everything after `discard:` is kept.
+    fn from_discard(raw_reply: &str) -> Self {
+        let reason = raw_reply.split_once(':').map_or(raw_reply, |(_, rest)| rest).trim();
+        let lower = reason.to_ascii_lowercase();
+        if lower.contains("too many") || lower.contains("buffer full") || lower.contains("buffer is full") {
+            InterpreterReply::BufferFull
+        } else if lower.contains("syntax") || lower.contains("compile") || lower.contains("parse") {
+            InterpreterReply::SyntaxError { message: reason.to_string() }
+        } else {
+            InterpreterReply::Discarded { reason: reason.to_string() }
+        }
+    }
+}
+
 /// Result of executing a command
 #[derive(Debug)]
 pub struct CommandResult {
     pub id: u32,
     pub raw_reply: String,
+    /// True for any reply other than `Acknowledged`
     pub rejected: bool,
+    pub reply: InterpreterReply,
 }
 
 impl InterpreterClient {
@@ -80,7 +116,7 @@ impl InterpreterClient {
     /// Will exit immediately if emergency abort signal is set.
     pub fn get_reply(&mut self) -> Result<String> {
         let socket = self.socket.as_mut()
-            .ok_or_else(|| anyhow!("Not connected to interpreter"))?;
+            .ok_or_else(|| URError::Connection("Not connected to interpreter".to_string()))?;
         
         let mut collected = Vec::new();
         let mut buffer = [0u8; 1];
@@ -100,11 +136,11 @@ impl InterpreterClient {
                     }
                 }
                 Err(e) => {
-                    // Check if this is a timeout error
-                    if e.kind() == std::io::ErrorKind::TimedOut {
-                        return Err(anyhow!("Interpreter response timeout - robot may be halted or unresponsive"));
+                    // Check if this is a timeout error (Unix reports read timeouts as WouldBlock)
+                    if matches!(e.kind(), std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock) {
+                        return Err(URError::Timeout("Interpreter response timeout - robot may be halted or unresponsive".to_string()).into());
                     } else {
-                        return Err(e).context("Failed to read from interpreter socket");
+                        return Err(URError::Connection(format!("Failed to read from interpreter socket: {}", e)).into());
                     }
                 }
             }
@@ -120,7 +156,7 @@ impl InterpreterClient {
     /// Returns the command ID on success, or an error if the command was discarded.
     pub fn execute_command(&mut self, command: &str) -> Result<CommandResult> {
         let socket = self.socket.as_mut()
-            .ok_or_else(|| anyhow!("Not connected to interpreter"))?;
+            .ok_or_else(|| URError::Connection("Not connected to interpreter".to_string()))?;
         
         // Ensure command ends with newline
         let command = if command.ends_with('\n') {
@@ -131,22 +167,24 @@ impl InterpreterClient {
         
         // Send command
         socket.write_all(command.as_bytes())
-            .context("Failed to send command to interpreter")?;
+            .map_err(|e| URError::Connection(format!("Failed to send command to interpreter: {}", e)))?;
         
         // Get and parse reply
         let raw_reply = self.get_reply()?;
         let reply = self.state_reply_pattern.captures(&raw_reply)
-            .ok_or_else(|| anyhow!("Invalid interpreter reply format: {}", raw_reply))?;
+            .ok_or_else(|| URError::Protocol(format!("Invalid interpreter reply format: {}", raw_reply)))?;
         
         let status = reply.get(1)
-            .ok_or_else(|| anyhow!("Missing status in reply: {}", raw_reply))?
+            .ok_or_else(|| URError::Protocol(format!("Missing status in reply: {}", raw_reply)))?
             .as_str();
         
         if status == "discard" {
+            let reply = InterpreterReply::from_discard(&raw_reply);
             return Ok(CommandResult {
                 id: 0,
                 raw_reply,
                 rejected: true,
+                reply,
             });
         }
         
@@ -158,6 +196,7 @@ impl InterpreterClient {
             id,
             raw_reply,
             rejected: false,
+            reply: InterpreterReply::Acknowledged { id },
         })
     }
     
@@ -229,4 +268,22 @@ impl Drop for InterpreterClient {
         // Best effort to exit interpreter mode
         let _ = self.end_interpreter();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_classify_discard_replies() {
+        assert_eq!(InterpreterReply::from_discard("discard: Too many interpreted messages: movej(q)"), InterpreterReply::BufferFull);
+        assert_eq!(
+            InterpreterReply::from_discard("discard: Compile error: syntax_error_on_line:1:movej((: movej(("),
+            InterpreterReply::SyntaxError { message: "Compile error: syntax_error_on_line:1:movej((: movej((".to_string() }
+        );
+        assert_eq!(
+            InterpreterReply::from_discard("discard: Interpreter not running: x = 1"),
+            InterpreterReply::Discarded { reason: "Interpreter not running: x = 1".to_string() }
+        );
+    }
+}
@@ -3,9 +3,41 @@
 //! Provides structured JSON output for command status, events, and errors
 //! that can be consumed by external tools and monitoring systems.
 
+use crate::pose_math::{direction_to_azimuth_elevation, quaternion_to_euler_zyx, rotvec_to_direction_vector, rotvec_to_quaternion};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// How JSON events are written to stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One event per line (the default, for line-oriented consumers)
+    Compact,
+    /// Indented, for a human watching the terminal
+    Pretty,
+    /// No stdout events at all; tracing logs on stderr are unaffected
+    Quiet,
+}
+
+static OUTPUT_FORMAT: AtomicU8 = AtomicU8::new(OutputFormat::Compact as u8);
+
+/// Select the format for every event written from now on (process-wide)
+pub fn set_output_format(format: OutputFormat) {
+    OUTPUT_FORMAT.store(format as u8, Ordering::Relaxed);
+}
+
+/// The format events are currently written in
+pub fn output_format() -> OutputFormat {
+    match OUTPUT_FORMAT.load(Ordering::Relaxed) {
+        1 => OutputFormat::Pretty,
+        2 => OutputFormat::Quiet,
+        _ => OutputFormat::Compact,
+    }
+}
+
 /// Get current timestamp as f64 seconds since UNIX epoch with consistent precision
 pub fn current_timestamp() -> f64 {
     let timestamp = SystemTime::now()
@@ -21,11 +53,87 @@ pub fn current_timestamp() -> f64 {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum CommandStatus {
+    Accepted,
     Sent,
     Completed,
     Failed,
 }
 
+/// Machine-readable reason a command failed
+/// 
+/// Serialized with a `kind` tag so clients can decide whether a retry makes
+/// sense without parsing the human-readable message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FailureReason {
+    /// The interpreter refused the command (syntax or state error)
+    Rejected { raw_reply: String },
+    /// The interpreter buffer was still full after clearing it
+    BufferFull { raw_reply: String },
+    /// The command did not finish within the execution timeout
+    Timeout { seconds: f64 },
+    /// Ctrl+C / SIGTERM arrived while the command was running
+    ShutdownInterrupted,
+    /// An emergency abort halted the command
+    EmergencyAbort,
+    /// The connection to the robot was lost
+    ConnectionLost { message: String },
+    /// The request itself was malformed or not allowed right now
+    InvalidRequest { message: String },
+    /// The interpreter, RTDE data or dashboard isn't available yet
+    NotReady { message: String },
+    /// A robot operation other than the command itself timed out (e.g. an `@ik` readback)
+    OperationTimeout { message: String },
+    /// Any other failure
+    Error { message: String },
+}
+
+impl FailureReason {
+    /// Whether resubmitting the same command may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            FailureReason::Timeout { .. }
+                | FailureReason::ConnectionLost { .. }
+                | FailureReason::BufferFull { .. }
+                | FailureReason::NotReady { .. }
+                | FailureReason::OperationTimeout { .. }
+        )
+    }
+    
+    /// Classify an error from the command pipeline by the `URError` in its chain
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        use crate::URError;
+        
+        let message = error.to_string();
+        match error.chain().find_map(|e| e.downcast_ref::<URError>()) {
+            Some(URError::Connection(_) | URError::Io(_)) => FailureReason::ConnectionLost { message },
+            Some(URError::InvalidRequest(_)) => FailureReason::InvalidRequest { message },
+            Some(URError::Rejected(raw_reply)) => FailureReason::Rejected { raw_reply: raw_reply.clone() },
+            Some(URError::NotReady(_)) => FailureReason::NotReady { message },
+            Some(URError::Timeout(_)) => FailureReason::OperationTimeout { message },
+            _ => FailureReason::Error { message },
+        }
+    }
+}
+
+impl std::fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailureReason::Rejected { raw_reply } => write!(f, "Command rejected: {}", raw_reply),
+            FailureReason::BufferFull { raw_reply } => write!(f, "Interpreter buffer full: {}", raw_reply),
+            FailureReason::Timeout { seconds } => write!(f, "Command timed out after {:.1}s", seconds),
+            FailureReason::ShutdownInterrupted => write!(f, "Interrupted by shutdown signal"),
+            FailureReason::EmergencyAbort => write!(f, "Interrupted by emergency abort"),
+            FailureReason::ConnectionLost { message } => write!(f, "Connection lost: {}", message),
+            FailureReason::InvalidRequest { message }
+            | FailureReason::NotReady { message }
+            | FailureReason::OperationTimeout { message }
+            | FailureReason::Error { message } => write!(f, "{}", message),
+        }
+    }
+}
+
 /// Command status event output
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandStatusEvent {
@@ -43,6 +151,12 @@ pub struct CommandStatusEvent {
     /// Original command text (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub command: Option<String>,
+    /// Position of the command in the input stream (only for accepted events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u32>,
+    /// Structured failure reason (only for failed events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<FailureReason>,
 }
 
 /// Error or safety violation event
@@ -58,6 +172,12 @@ pub struct ErrorEvent {
     pub command_id: Option<u32>,
     /// Error message
     pub error: String,
+    /// Stable machine-readable code, e.g. "ROBOT_NOT_READY" (see `URError::error_code`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// Commands that would have been understood, for unknown sentinels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available: Option<Vec<String>>,
 }
 
 /// Buffer management event types
@@ -85,6 +205,190 @@ pub struct BufferEvent {
     pub clear_id: Option<u32>,
 }
 
+/// Periodic liveness event so clients can tell an idle robot from a dead daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatEvent {
+    /// Timestamp when event occurred
+    pub timestamp: f64,
+    /// Event type for JSON parsing
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Seconds since the daemon started
+    pub daemon_uptime_s: f64,
+    /// Whether the robot is running with interpreter mode available
+    pub robot_ready: bool,
+    /// URScript commands accepted but not yet finished
+    pub queue_depth: u64,
+}
+
+impl HeartbeatEvent {
+    /// Create a new heartbeat event
+    pub fn new(daemon_uptime_s: f64, robot_ready: bool, queue_depth: u64) -> Self {
+        Self {
+            timestamp: current_timestamp(),
+            event_type: "heartbeat".to_string(),
+            daemon_uptime_s,
+            robot_ready,
+            queue_depth,
+        }
+    }
+}
+
+/// Cartesian components, of a position (m) or a unit direction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vector3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// UR axis-angle rotation vector (rad)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RotationVector {
+    pub rx: f64,
+    pub ry: f64,
+    pub rz: f64,
+}
+
+/// Unit quaternion, scalar first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// Intrinsic Z-Y-X Euler angles (degrees)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EulerZyx {
+    pub yaw: f64,
+    pub pitch: f64,
+    pub roll: f64,
+}
+
+/// TCP pose with derived orientations, answering `@pose`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoseEvent {
+    /// Timestamp when event occurred
+    pub timestamp: f64,
+    /// Event type for JSON parsing
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub position: Vector3,
+    pub rotation_vector: RotationVector,
+    pub quaternion: Quaternion,
+    pub euler_zyx_deg: EulerZyx,
+    /// Direction the tool's Z axis points in, in base coordinates
+    pub pointing_direction: Vector3,
+    pub azimuth_deg: f64,
+    pub elevation_deg: f64,
+    pub joint_positions: [f64; 6],
+    /// Timestamp of the RTDE data the pose comes from
+    pub last_updated: f64,
+}
+
+impl PoseEvent {
+    /// Create a pose event from the TCP pose and joints, rounded for display
+    /// 
+    /// Millimetres for the position, 0.01° for the Euler angles, 0.1° for
+    /// azimuth and elevation, four places for joints and six for the rest.
+    pub fn new(tcp_pose: [f64; 6], joint_positions: [f64; 6], last_updated: f64) -> Self {
+        let round = |value: f64, places: i32| {
+            let multiplier = 10f64.powi(places);
+            (value * multiplier).round() / multiplier
+        };
+        
+        let [x, y, z, rx, ry, rz] = tcp_pose;
+        let direction = rotvec_to_direction_vector(rx, ry, rz);
+        let (azimuth, elevation) = direction_to_azimuth_elevation(direction);
+        let quaternion = rotvec_to_quaternion(rx, ry, rz);
+        let (yaw, pitch, roll) = quaternion_to_euler_zyx(quaternion);
+        
+        Self {
+            timestamp: current_timestamp(),
+            event_type: "pose".to_string(),
+            position: Vector3 { x: round(x, 3), y: round(y, 3), z: round(z, 3) },
+            rotation_vector: RotationVector { rx: round(rx, 6), ry: round(ry, 6), rz: round(rz, 6) },
+            quaternion: Quaternion {
+                w: round(quaternion[0], 6),
+                x: round(quaternion[1], 6),
+                y: round(quaternion[2], 6),
+                z: round(quaternion[3], 6),
+            },
+            euler_zyx_deg: EulerZyx { yaw: round(yaw, 2), pitch: round(pitch, 2), roll: round(roll, 2) },
+            pointing_direction: Vector3 {
+                x: round(direction[0], 6),
+                y: round(direction[1], 6),
+                z: round(direction[2], 6),
+            },
+            azimuth_deg: round(azimuth, 1),
+            elevation_deg: round(elevation, 1),
+            joint_positions: joint_positions.map(|q| round(q, 4)),
+            last_updated: round(last_updated, 6),
+        }
+    }
+}
+
+/// Robot controller state transition (e.g. running -> error after an emergency halt)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControllerStateEvent {
+    /// Timestamp when event occurred
+    pub timestamp: f64,
+    /// Event type for JSON parsing
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// New state: disconnected, power_off, idle, running or error
+    pub state: String,
+    /// State before the transition
+    pub previous: String,
+    /// Error description (only for the error state)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ControllerStateEvent {
+    /// Create a new controller state event
+    pub fn new(previous: &str, state: &str, message: Option<String>) -> Self {
+        Self {
+            timestamp: current_timestamp(),
+            event_type: "controller_state".to_string(),
+            state: state.to_string(),
+            previous: previous.to_string(),
+            message,
+        }
+    }
+}
+
+/// Periodic update while a long-running command executes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressEvent {
+    /// Timestamp when event occurred
+    pub timestamp: f64,
+    /// Event type for JSON parsing
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Interpreter ID of the command being waited on
+    pub command_id: u32,
+    /// Sequence number from the command's `accepted` event
+    pub sequence: u32,
+    /// Milliseconds since the wait for this command started
+    pub elapsed_ms: f64,
+}
+
+impl ProgressEvent {
+    /// Create a new progress event
+    pub fn new(command_id: u32, sequence: u32, elapsed: std::time::Duration) -> Self {
+        Self {
+            timestamp: current_timestamp(),
+            event_type: "progress".to_string(),
+            command_id,
+            sequence,
+            elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
 impl CommandStatusEvent {
     /// Create a new command status event
     pub fn new(command_id: u32, status: CommandStatus, message: &str, command: Option<String>) -> Self {
@@ -95,6 +399,21 @@ impl CommandStatusEvent {
             status,
             message: message.to_string(),
             command,
+            sequence: None,
+            failure_reason: None,
+        }
+    }
+    
+    /// Create a command accepted event (sent before the interpreter assigns an ID)
+    pub fn accepted(sequence: u32, command: &str) -> Self {
+        Self {
+            sequence: Some(sequence),
+            ..Self::new(
+                0,
+                CommandStatus::Accepted,
+                "Command accepted for execution",
+                Some(command.to_string()),
+            )
         }
     }
     
@@ -119,13 +438,16 @@ impl CommandStatusEvent {
     }
     
     /// Create a command failed event
-    pub fn failed(command_id: u32, error_msg: &str) -> Self {
-        Self::new(
-            command_id,
-            CommandStatus::Failed,
-            error_msg,
-            None,
-        )
+    pub fn failed(command_id: u32, reason: FailureReason) -> Self {
+        Self {
+            failure_reason: Some(reason.clone()),
+            ..Self::new(
+                command_id,
+                CommandStatus::Failed,
+                &reason.to_string(),
+                None,
+            )
+        }
     }
 }
 
@@ -137,9 +459,29 @@ impl ErrorEvent {
             event_type: "error".to_string(),
             command_id,
             error: error.to_string(),
+            error_code: None,
+            available: None,
         }
     }
     
+    /// Create an error event for a failed operation, coded from the error chain
+    pub fn from_error(context: &str, error: &anyhow::Error) -> Self {
+        Self::new(&format!("{}: {}", context, error), None)
+            .with_error_code(crate::error::error_code_for(error))
+    }
+    
+    /// Attach a stable error code
+    pub fn with_error_code(mut self, error_code: &str) -> Self {
+        self.error_code = Some(error_code.to_string());
+        self
+    }
+    
+    /// List the commands that are available instead
+    pub fn with_available(mut self, commands: &[&str]) -> Self {
+        self.available = Some(commands.iter().map(|command| command.to_string()).collect());
+        self
+    }
+    
     /// Create a safety violation event
     pub fn safety_violation(error: &str) -> Self {
         Self {
@@ -147,6 +489,8 @@ impl ErrorEvent {
             event_type: "safety_violation".to_string(),
             command_id: None,
             error: error.to_string(),
+            error_code: None,
+            available: None,
         }
     }
     
@@ -179,10 +523,116 @@ impl BufferEvent {
     }
 }
 
-/// Output a JSON event to stdout
-pub fn output_event<T: Serialize>(event: &T) {
-    if let Ok(json) = serde_json::to_string(event) {
-        println!("{}", json);
+/// Stdout writes of one event type since startup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PublishCounts {
+    pub attempted: u64,
+    pub succeeded: u64,
+    /// Events lost because stdout couldn't be written (e.g. the reader went away)
+    pub failed: u64,
+}
+
+static PUBLISH_COUNTS: Mutex<BTreeMap<String, PublishCounts>> = Mutex::new(BTreeMap::new());
+
+/// Write outcomes per event `type`, for `@status` and the metrics endpoint
+/// 
+/// Events silenced by `--quiet` are not counted.
+pub fn publish_counts() -> BTreeMap<String, PublishCounts> {
+    PUBLISH_COUNTS.lock().map(|counts| counts.clone()).unwrap_or_default()
+}
+
+/// Most error events kept for `@errors`
+pub const RECENT_ERRORS_CAPACITY: usize = 100;
+
+/// Error events from every task (stream, monitoring, abort listener), newest last
+static RECENT_ERRORS: Mutex<VecDeque<ErrorEvent>> = Mutex::new(VecDeque::new());
+
+/// The last `limit` error events, oldest first
+/// 
+/// Kept even with `--quiet`, so `@errors` can show what went wrong.
+pub fn recent_errors(limit: usize) -> Vec<ErrorEvent> {
+    let Ok(errors) = RECENT_ERRORS.lock() else { return Vec::new() };
+    errors.iter().skip(errors.len().saturating_sub(limit)).cloned().collect()
+}
+
+/// An event written to stdout, which knows its own `type`
+pub trait Event: Serialize {
+    /// The `type` field, used to count writes per event type
+    fn event_type(&self) -> &str;
+}
+
+impl Event for serde_json::Value {
+    fn event_type(&self) -> &str {
+        self.get("type").and_then(serde_json::Value::as_str).unwrap_or("unknown")
+    }
+}
+
+/// Implement `Event` for structs carrying their type in an `event_type` field
+macro_rules! impl_event {
+    ($($event:ty),* $(,)?) => {
+        $(impl $crate::json_output::Event for $event {
+            fn event_type(&self) -> &str {
+                &self.event_type
+            }
+        })*
+    };
+}
+pub(crate) use impl_event;
+
+impl_event!(CommandStatusEvent, ErrorEvent, BufferEvent, HeartbeatEvent, PoseEvent, ControllerStateEvent, ProgressEvent);
+
+/// Write one rendered event to stdout and count the outcome
+/// 
+/// A failed write is logged once per event type and then only counted, so a
+/// closed stdout doesn't flood the logs (or panic like `println!`).
+fn write_line(json: &str, event_type: &str) {
+    let written = writeln!(std::io::stdout().lock(), "{}", json);
+    
+    let Ok(mut all_counts) = PUBLISH_COUNTS.lock() else { return };
+    let counts = all_counts.entry(event_type.to_string()).or_default();
+    counts.attempted += 1;
+    match written {
+        Ok(()) => counts.succeeded += 1,
+        Err(e) => {
+            counts.failed += 1;
+            if counts.failed == 1 {
+                tracing::warn!("Failed to write event to stdout: {} - further failures are only counted (see @status)", e);
+            }
+        }
+    }
+}
+
+/// Render an event in `format`; None if it shouldn't be written
+fn format_event<T: Serialize>(event: &T, format: OutputFormat) -> Option<String> {
+    match format {
+        OutputFormat::Compact => serde_json::to_string(event).ok(),
+        OutputFormat::Pretty => serde_json::to_string_pretty(event).ok(),
+        OutputFormat::Quiet => None,
+    }
+}
+
+/// Output a JSON event to stdout in the selected output format
+/// 
+/// Every stdout event goes through here (or `output_json_line`), so none are
+/// hand-assembled and `--quiet` silences all of them.
+pub fn output_event<T: Event>(event: &T) {
+    if let Some(json) = format_event(event, output_format()) {
+        write_line(&json, event.event_type());
+    }
+}
+
+/// Output a JSON line that was formatted by hand (e.g. for fixed decimal places)
+/// 
+/// Written as is in compact mode; re-indented in pretty mode. `event_type` is
+/// the line's `type`.
+pub fn output_json_line(json: &str, event_type: &str) {
+    match output_format() {
+        OutputFormat::Compact => write_line(json, event_type),
+        OutputFormat::Pretty => match serde_json::from_str::<serde_json::Value>(json) {
+            Ok(value) => output_event(&value),
+            Err(_) => write_line(json, event_type),
+        },
+        OutputFormat::Quiet => {}
     }
 }
 
@@ -195,9 +645,15 @@ pub mod output {
         output_event(&event);
     }
     
-    /// Output an error event
+    /// Output an error event, keeping it for `@errors`
     pub fn error(event: ErrorEvent) {
         output_event(&event);
+        if let Ok(mut errors) = RECENT_ERRORS.lock() {
+            if errors.len() == RECENT_ERRORS_CAPACITY {
+                errors.pop_front();
+            }
+            errors.push_back(event);
+        }
     }
     
     /// Output a buffer event
@@ -205,6 +661,31 @@ pub mod output {
         output_event(&event);
     }
     
+    /// Output a heartbeat event
+    pub fn heartbeat(event: HeartbeatEvent) {
+        output_event(&event);
+    }
+    
+    /// Output a command progress event
+    pub fn progress(event: ProgressEvent) {
+        output_event(&event);
+    }
+    
+    /// Output a controller state transition
+    pub fn controller_state(event: ControllerStateEvent) {
+        output_event(&event);
+    }
+    
+    /// Output a TCP pose report
+    pub fn pose(event: PoseEvent) {
+        output_event(&event);
+    }
+    
+    /// Output command accepted notification
+    pub fn command_accepted(sequence: u32, command: &str) {
+        command_status(CommandStatusEvent::accepted(sequence, command));
+    }
+    
     /// Output command sent notification
     pub fn command_sent(command_id: u32, command: &str) {
         command_status(CommandStatusEvent::sent(command_id, command));
@@ -216,18 +697,21 @@ pub mod output {
     }
     
     /// Output command failed notification
-    pub fn command_failed(command_id: u32, error: &str) {
-        command_status(CommandStatusEvent::failed(command_id, error));
+    pub fn command_failed(command_id: u32, reason: FailureReason) {
+        command_status(CommandStatusEvent::failed(command_id, reason));
     }
     
     /// Output command rejected notification (command ID 0)
     pub fn command_rejected(command: &str, reason: &str) {
-        command_status(CommandStatusEvent::new(
-            0,
-            CommandStatus::Failed,
-            &format!("Command rejected: {}", reason),
-            Some(command.to_string()),
-        ));
+        command_refused(command, FailureReason::Rejected { raw_reply: reason.to_string() });
+    }
+    
+    /// Output a failed notification for a command the interpreter never accepted (command ID 0)
+    pub fn command_refused(command: &str, reason: FailureReason) {
+        command_status(CommandStatusEvent {
+            command: Some(command.to_string()),
+            ..CommandStatusEvent::failed(0, reason)
+        });
     }
     
     /// Output safety violation
@@ -249,4 +733,125 @@ pub mod output {
     pub fn buffer_clear_completed(commands_processed: u32, clear_id: u32) {
         buffer(BufferEvent::clear_completed(commands_processed, clear_id));
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_accepted_event_precedes_completion() {
+        let accepted = serde_json::to_value(CommandStatusEvent::accepted(3, "movej([0,0,0,0,0,0])")).unwrap();
+        let completed = serde_json::to_value(CommandStatusEvent::completed(42)).unwrap();
+        
+        assert_eq!(accepted["status"], "accepted");
+        assert_eq!(accepted["sequence"], 3);
+        assert_eq!(accepted["command_id"], 0);
+        assert_eq!(accepted["command"], "movej([0,0,0,0,0,0])");
+        
+        assert_eq!(completed["status"], "completed");
+        assert_eq!(completed["command_id"], 42);
+        assert!(completed.get("sequence").is_none());
+        assert!(accepted["timestamp"].as_f64().unwrap() <= completed["timestamp"].as_f64().unwrap());
+    }
+    
+    #[test]
+    fn test_failure_reason_serialization() {
+        let timeout = serde_json::to_value(CommandStatusEvent::failed(7, FailureReason::Timeout { seconds: 5.0 })).unwrap();
+        assert_eq!(timeout["status"], "failed");
+        assert_eq!(timeout["failure_reason"]["kind"], "timeout");
+        assert_eq!(timeout["failure_reason"]["seconds"], 5.0);
+        assert_eq!(timeout["message"], "Command timed out after 5.0s");
+        
+        let rejected = FailureReason::Rejected { raw_reply: "discard: syntax".to_string() };
+        let json = serde_json::to_value(&rejected).unwrap();
+        assert_eq!(json["kind"], "rejected");
+        assert_eq!(json["raw_reply"], "discard: syntax");
+        assert!(!rejected.is_retryable());
+        
+        assert_eq!(serde_json::to_value(FailureReason::ShutdownInterrupted).unwrap()["kind"], "shutdown_interrupted");
+        assert!(FailureReason::from_error(&crate::URError::Connection("gone".into()).into()).is_retryable());
+        
+        // Typed errors keep their kind through context layers
+        let rejected: anyhow::Error = anyhow::Error::from(crate::URError::Rejected("'x = ': discard".into())).context("@var failed");
+        assert_eq!(FailureReason::from_error(&rejected), FailureReason::Rejected { raw_reply: "'x = ': discard".to_string() });
+        let timeout = FailureReason::from_error(&crate::URError::Timeout("get_inverse_kin did not execute within 5s".into()).into());
+        assert_eq!(serde_json::to_value(&timeout).unwrap()["kind"], "operation_timeout");
+        assert!(timeout.is_retryable());
+        let not_ready = FailureReason::from_error(&crate::URError::NotReady("Interpreter not initialized".into()).into());
+        assert_eq!(serde_json::to_value(&not_ready).unwrap()["kind"], "not_ready");
+        
+        // Non-failure events carry no reason
+        let completed = serde_json::to_value(CommandStatusEvent::completed(7)).unwrap();
+        assert!(completed.get("failure_reason").is_none());
+    }
+    
+    #[test]
+    fn test_output_formats() {
+        let event = ErrorEvent::new("boom", Some(3));
+        let compact = format_event(&event, OutputFormat::Compact).unwrap();
+        let pretty = format_event(&event, OutputFormat::Pretty).unwrap();
+        
+        assert!(!compact.contains('\n'));
+        assert!(pretty.contains("\n  \"error\": \"boom\""));
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&compact).unwrap(), serde_json::from_str::<serde_json::Value>(&pretty).unwrap());
+        assert_eq!(format_event(&event, OutputFormat::Quiet), None);
+    }
+    
+    #[test]
+    fn test_publish_counts_per_event_type() {
+        output_json_line(r#"{"type":"publish_counts_test","nested":{"type":"other"}}"#, "publish_counts_test");
+        output_event(&serde_json::json!({"type": "publish_counts_test"}));
+        
+        let counts = publish_counts()["publish_counts_test"];
+        assert_eq!(counts, PublishCounts { attempted: 2, succeeded: 2, failed: 0 });
+        assert!(!publish_counts().contains_key("other"));
+    }
+    
+    #[test]
+    fn test_recent_errors_are_bounded() {
+        for i in 0..RECENT_ERRORS_CAPACITY + 5 {
+            output::error(ErrorEvent::new(&format!("recent errors test {}", i), None));
+        }
+        
+        let errors = recent_errors(RECENT_ERRORS_CAPACITY + 50);
+        assert_eq!(errors.len(), RECENT_ERRORS_CAPACITY);
+        // Other tests may log errors concurrently, so only check the ones from here
+        let ours: Vec<&str> = errors.iter().map(|e| e.error.as_str()).filter(|e| e.starts_with("recent errors test")).collect();
+        assert_eq!(ours.last(), Some(&format!("recent errors test {}", RECENT_ERRORS_CAPACITY + 4).as_str()));
+        assert!(!ours.contains(&"recent errors test 0"));
+        assert_eq!(recent_errors(3).len(), 3);
+    }
+    
+    #[test]
+    fn test_pose_event_rounding() {
+        let event = PoseEvent::new([0.12345, -0.4, 0.3, 3.0, 0.0, 0.0], [0.0, -1.234567, 1.2, 0.0, 0.0, 0.0], 12.5);
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "pose");
+        assert_eq!(json["position"], serde_json::json!({ "x": 0.123, "y": -0.4, "z": 0.3 }));
+        assert_eq!(json["joint_positions"][1], -1.2346);
+        assert_eq!(json["euler_zyx_deg"]["roll"], 171.89);
+        
+        // A corrupt sample still serializes to valid JSON
+        let event = PoseEvent::new([f64::NAN, 0.0, 0.0, 0.0, 0.0, 0.0], [0.0; 6], 0.0);
+        let line = serde_json::to_string(&event).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&line).unwrap()["position"]["x"].is_null());
+    }
+    
+    #[test]
+    fn test_error_event_codes() {
+        let plain = serde_json::to_value(ErrorEvent::new("boom", None)).unwrap();
+        assert!(plain.get("error_code").is_none());
+        
+        let error: anyhow::Error = crate::URError::NotReady("Interpreter not initialized".into()).into();
+        let coded = serde_json::to_value(ErrorEvent::from_error("@io failed", &error)).unwrap();
+        assert_eq!(coded["error"], "@io failed: Robot not ready: Interpreter not initialized");
+        assert_eq!(coded["error_code"], "ROBOT_NOT_READY");
+        assert!(coded.get("available").is_none());
+        
+        let unknown = serde_json::to_value(ErrorEvent::new("Unknown sentinel command: foo", None)
+            .with_error_code("UNKNOWN_COMMAND")
+            .with_available(&["@status", "@help"])).unwrap();
+        assert_eq!(unknown["available"], serde_json::json!(["@status", "@help"]));
+    }
+}
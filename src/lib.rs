@@ -3,23 +3,40 @@
 //! Pure Rust implementation of RTDE (Real-Time Data Exchange) protocol for Universal Robots.
 //! Based on UR's official RTDE specification.
 
+pub mod audit;
 pub mod config;
 pub mod controller;
 pub mod error;
 pub mod interpreter;
 pub mod json_output;
+pub mod metrics;
+#[cfg(feature = "test-support")]
+pub mod mock;
 pub mod monitoring;
+pub mod motion;
+pub mod pose_math;
+pub mod recording;
 pub mod rtde;
+pub mod safety;
 pub mod stream;
+pub mod urscript_lint;
+pub mod validate;
 
-pub use config::{Config, DaemonConfig, InterpreterConfig};
-pub use controller::{RobotController, RobotState as ControllerRobotState};
-pub use error::{Result, URError};
-pub use interpreter::{InterpreterClient, CommandResult};
-pub use json_output::{CommandStatusEvent, ErrorEvent, BufferEvent, CommandStatus};
-pub use monitoring::{MonitorOutput, PositionData, RobotStateData};
-pub use rtde::{RTDEClient, RTDEMessage, RobotState, RTDESubscriber};
-pub use stream::{CommandStream, CommandStats};
+pub use audit::{AuditEntry, AuditLog};
+pub use config::{AuditConfig, Config, ConfigError, ConfigReload, DaemonConfig, InterpreterConfig, LintMode, WorkspaceConfig};
+pub use controller::{AbortHandle, AnalogInputs, ProgramState, RegisterReadback, RobotController, RobotInfo, RobotState as ControllerRobotState};
+pub use error::{error_code_for, Result, URError};
+pub use interpreter::{InterpreterClient, CommandResult, InterpreterReply};
+pub use metrics::CommandMetrics;
+pub use json_output::{CommandStatusEvent, ErrorEvent, BufferEvent, CommandStatus, FailureReason, PoseEvent};
+pub use motion::{JointConfiguration, MoveParams};
+pub use pose_math::{direction_to_azimuth_elevation, quaternion_to_euler_zyx, quaternion_to_rotvec, rotvec_to_direction_vector, rotvec_to_quaternion};
+pub use recording::{RecordedFrame, RtdeRecorder, RtdeReplay};
+pub use monitoring::{JointDiagData, MonitorOutput, PositionData, RobotStateData, RtdeDebugData, RuntimeEventData, WrenchData};
+pub use rtde::{AsyncRTDEClient, OutputRecipe, RTDEClient, RTDEMessage, RobotState, RTDESubscriber};
+pub use stream::{CommandStream, CommandStats, BufferStats};
+pub use urscript_lint::{LintIssue, Linter};
+pub use validate::{validate_urscript, ValidationReport};
 
 /// High-level robot control interface
 pub struct ControlInterface {
@@ -0,0 +1,236 @@
+//! Prometheus Metrics
+//!
+//! Command execution counters and timings in Prometheus text format. The
+//! `/metrics` HTTP endpoint is only built with the `metrics` feature; the
+//! counters themselves are plain atomics and cost nothing when unused.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the command execution time histogram buckets
+const DURATION_BUCKETS: [f64; 8] = [0.1, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// How long a metrics client gets to send its request line
+#[cfg(feature = "metrics")]
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Command execution statistics shared between the command stream and the metrics endpoint
+#[derive(Debug, Default)]
+pub struct CommandMetrics {
+    accepted: AtomicU64,
+    completed: AtomicU64,
+    rejected: AtomicU64,
+    failed: AtomicU64,
+    /// Per-bucket (non-cumulative) completion counts, plus one overflow bucket
+    duration_buckets: [AtomicU64; DURATION_BUCKETS.len() + 1],
+    duration_sum_micros: AtomicU64,
+}
+
+impl CommandMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a URScript command read from the input stream
+    pub fn record_accepted(&self) {
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a command that finished executing, with its execution time
+    pub fn record_completed(&self, duration: Duration) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+
+        let seconds = duration.as_secs_f64();
+        let bucket = DURATION_BUCKETS.iter()
+            .position(|&bound| seconds <= bound)
+            .unwrap_or(DURATION_BUCKETS.len());
+        self.duration_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.duration_sum_micros.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Record a command the interpreter refused
+    pub fn record_rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a command that was sent but did not complete (timeout, abort, connection loss)
+    pub fn record_failed(&self) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// URScript commands accepted but not yet completed, rejected or failed
+    pub fn in_flight(&self) -> u64 {
+        let finished = self.completed.load(Ordering::Relaxed)
+            + self.rejected.load(Ordering::Relaxed)
+            + self.failed.load(Ordering::Relaxed);
+        self.accepted.load(Ordering::Relaxed).saturating_sub(finished)
+    }
+    
+    /// Render all metrics in Prometheus text exposition format
+    ///
+    /// `connection_health` is the tuple returned by `RobotController::get_connection_health()`.
+    pub fn render(&self, connection_health: (bool, bool, bool, bool)) -> String {
+        let mut out = String::new();
+
+        let counters = [
+            ("urd_commands_accepted_total", "URScript commands read from the input stream", &self.accepted),
+            ("urd_commands_completed_total", "URScript commands that finished executing", &self.completed),
+            ("urd_commands_rejected_total", "URScript commands rejected by the interpreter", &self.rejected),
+            ("urd_commands_failed_total", "URScript commands that timed out or were interrupted", &self.failed),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, value.load(Ordering::Relaxed));
+        }
+
+        let (interpreter, primary, dashboard, monitoring) = connection_health;
+        let _ = writeln!(out, "# HELP urd_connection_up Connection health (1 = connected)");
+        let _ = writeln!(out, "# TYPE urd_connection_up gauge");
+        for (connection, up) in [("interpreter", interpreter), ("primary", primary), ("dashboard", dashboard), ("monitoring", monitoring)] {
+            let _ = writeln!(out, "urd_connection_up{{connection=\"{}\"}} {}", connection, up as u8);
+        }
+
+        let publish_counts = crate::json_output::publish_counts();
+        let publish_counters = [
+            ("urd_events_published_total", "JSON events written to stdout", false),
+            ("urd_events_dropped_total", "JSON events lost because stdout could not be written", true),
+        ];
+        for (name, help, dropped) in publish_counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (event_type, counts) in &publish_counts {
+                let count = if dropped { counts.failed } else { counts.succeeded };
+                let _ = writeln!(out, "{}{{type=\"{}\"}} {}", name, event_type, count);
+            }
+        }
+
+        let name = "urd_command_duration_seconds";
+        let _ = writeln!(out, "# HELP {} Time from sending a command until it finished executing", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        let mut cumulative = 0;
+        for (bound, bucket) in DURATION_BUCKETS.iter().zip(&self.duration_buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+        }
+        cumulative += self.duration_buckets[DURATION_BUCKETS.len()].load(Ordering::Relaxed);
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, cumulative);
+        let _ = writeln!(out, "{}_sum {:.6}", name, self.duration_sum_micros.load(Ordering::Relaxed) as f64 / 1e6);
+        let _ = writeln!(out, "{}_count {}", name, cumulative);
+
+        out
+    }
+}
+
+/// Serve `GET /metrics` on `bind_address` until the task is dropped
+/// 
+/// Each connection is handled on its own task, so a client that connects and
+/// never sends a request can't block other scrapes.
+#[cfg(feature = "metrics")]
+pub async fn serve(
+    bind_address: String,
+    metrics: std::sync::Arc<CommandMetrics>,
+    controller: std::sync::Arc<tokio::sync::Mutex<crate::RobotController>>,
+) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use tokio::net::TcpListener;
+    use tracing::{info, error};
+
+    let listener = TcpListener::bind(&bind_address).await
+        .with_context(|| format!("Failed to bind metrics endpoint to {}", bind_address))?;
+    info!("Metrics endpoint listening on http://{}/metrics", bind_address);
+
+    loop {
+        let socket = match listener.accept().await {
+            Ok((socket, _)) => socket,
+            Err(e) => {
+                // Usually transient (e.g. out of file descriptors); back off briefly
+                error!("Failed to accept metrics connection: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        tokio::spawn(handle_connection(socket, metrics.clone(), controller.clone()));
+    }
+}
+
+/// Answer one metrics request
+#[cfg(feature = "metrics")]
+async fn handle_connection(
+    mut socket: tokio::net::TcpStream,
+    metrics: std::sync::Arc<CommandMetrics>,
+    controller: std::sync::Arc<tokio::sync::Mutex<crate::RobotController>>,
+) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tracing::error;
+
+    // Only the request line matters; scrapes are tiny
+    let mut request = [0u8; 1024];
+    let bytes_read = match tokio::time::timeout(REQUEST_READ_TIMEOUT, socket.read(&mut request)).await {
+        Ok(Ok(n)) => n,
+        Ok(Err(e)) => {
+            error!("Failed to read metrics request: {}", e);
+            return;
+        }
+        Err(_) => {
+            error!("Timed out reading metrics request");
+            return;
+        }
+    };
+    let request = String::from_utf8_lossy(&request[..bytes_read]);
+
+    let response = if request.starts_with("GET /metrics") {
+        let health = controller.lock().await.get_connection_health();
+        let body = metrics.render(health);
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+
+    if let Err(e) = socket.write_all(response.as_bytes()).await {
+        error!("Failed to write metrics response: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counters_gauges_and_histogram() {
+        let metrics = CommandMetrics::new();
+        metrics.record_accepted();
+        metrics.record_accepted();
+        metrics.record_accepted();
+        metrics.record_rejected();
+        metrics.record_completed(Duration::from_millis(300));
+        metrics.record_completed(Duration::from_secs(120));
+
+        let text = metrics.render((true, true, false, true));
+
+        assert!(text.contains("urd_commands_accepted_total 3\n"));
+        assert!(text.contains("urd_commands_rejected_total 1\n"));
+        assert!(text.contains("urd_commands_completed_total 2\n"));
+        assert!(text.contains("urd_connection_up{connection=\"dashboard\"} 0\n"));
+        assert!(text.contains("urd_connection_up{connection=\"interpreter\"} 1\n"));
+        assert!(text.contains("# TYPE urd_events_dropped_total counter\n"));
+
+        // Buckets are cumulative; the 120s command only lands in +Inf
+        assert!(text.contains("urd_command_duration_seconds_bucket{le=\"0.1\"} 0\n"));
+        assert!(text.contains("urd_command_duration_seconds_bucket{le=\"0.5\"} 1\n"));
+        assert!(text.contains("urd_command_duration_seconds_bucket{le=\"60\"} 1\n"));
+        assert!(text.contains("urd_command_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("urd_command_duration_seconds_sum 120.300000\n"));
+        assert!(text.contains("urd_command_duration_seconds_count 2\n"));
+        
+        // 3 accepted, 2 completed, 1 rejected
+        assert_eq!(metrics.in_flight(), 0);
+        metrics.record_accepted();
+        assert_eq!(metrics.in_flight(), 1);
+    }
+}
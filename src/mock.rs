@@ -0,0 +1,398 @@
+//! Mock Robot for Integration Tests
+//!
+//! Listens on local ports standing in for the primary, dashboard, interpreter
+//! and RTDE interfaces, and speaks just enough of each protocol for a
+//! `RobotController` to initialize and stream commands without a robot:
+//! the dashboard powers on and releases brakes, the interpreter acknowledges
+//! every statement with an incrementing ID and "executes" it immediately, and
+//! RTDE serves canned data for any recipe of known variables. Literal
+//! assignments and output register writes are tracked, so register readbacks
+//! (`@var get`) see the values.
+//!
+//! Only built with the `test-support` feature.
+
+use crate::config::DaemonConfig;
+use crate::monitoring::get_robot_mode_name;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Canned joint positions served as `actual_q`
+pub const MOCK_JOINT_POSITIONS: [f64; 6] = [0.0, -1.57, 1.57, -1.57, -1.57, 0.0];
+/// Canned TCP pose served as `actual_TCP_pose`
+pub const MOCK_TCP_POSE: [f64; 6] = [0.1, -0.4, 0.3, 0.0, 3.1, 0.0];
+
+/// Everything the mock has received and its simulated robot state
+#[derive(Debug)]
+struct MockState {
+    robot_mode: i32,
+    primary_scripts: String,
+    dashboard_commands: Vec<String>,
+    interpreter_commands: Vec<String>,
+    last_id: u32,
+    last_cleared: u32,
+    /// Variables assigned a literal number, list or pose
+    variables: HashMap<String, Vec<f64>>,
+    int_registers: HashMap<u8, i32>,
+    double_registers: HashMap<u8, f64>,
+}
+
+/// Serves one accepted connection of an interface
+type Handler = fn(TcpStream, Arc<Mutex<MockState>>);
+
+/// Fake UR controller on 127.0.0.1, one listener per interface
+pub struct MockRobot {
+    ports: [u16; 4],
+    state: Arc<Mutex<MockState>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl MockRobot {
+    /// Bind the four interfaces to free ports and start serving
+    ///
+    /// The robot starts powered off, like a freshly booted controller.
+    pub fn start() -> std::io::Result<Self> {
+        let state = Arc::new(Mutex::new(MockState {
+            robot_mode: 3,
+            primary_scripts: String::new(),
+            dashboard_commands: Vec::new(),
+            interpreter_commands: Vec::new(),
+            last_id: 0,
+            last_cleared: 0,
+            variables: HashMap::new(),
+            int_registers: HashMap::new(),
+            double_registers: HashMap::new(),
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let handlers: [Handler; 4] = [serve_primary, serve_dashboard, serve_interpreter, serve_rtde];
+        let mut ports = [0; 4];
+        for (port, handler) in ports.iter_mut().zip(handlers) {
+            let listener = TcpListener::bind("127.0.0.1:0")?;
+            listener.set_nonblocking(true)?;
+            *port = listener.local_addr()?.port();
+            spawn_listener(listener, handler, Arc::clone(&state), Arc::clone(&stop));
+        }
+
+        Ok(Self { ports, state, stop })
+    }
+
+    /// Daemon configuration pointing every interface at the mock
+    pub fn config(&self) -> DaemonConfig {
+        let [primary, dashboard, interpreter, rtde] = self.ports;
+        let yaml = format!(
+            r#"
+robot:
+  host: "127.0.0.1"
+  ports: {{primary: {primary}, rtde: {rtde}, dashboard: {dashboard}, interpreter: {interpreter}}}
+  tcp_offset: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+  movement: {{speed: 0.1, acceleration: 0.1, blend_radius: 0.01}}
+  connection: {{timeout: 2.0, retry_attempts: 1, retry_delay: 0.1}}
+publishing: {{pub_rate_hz: 10}}
+command: {{monitor_execution: false, stream_robot_state: false}}
+interpreter: {{connect_max_attempts: 5}}
+"#
+        );
+        serde_yaml::from_str(&yaml).expect("mock config is valid")
+    }
+
+    /// URScript sent to the primary interface
+    pub fn primary_scripts(&self) -> String {
+        self.state.lock().unwrap().primary_scripts.clone()
+    }
+
+    /// Dashboard commands in the order received
+    pub fn dashboard_commands(&self) -> Vec<String> {
+        self.state.lock().unwrap().dashboard_commands.clone()
+    }
+
+    /// Interpreter lines in the order received, including state queries
+    pub fn interpreter_commands(&self) -> Vec<String> {
+        self.state.lock().unwrap().interpreter_commands.clone()
+    }
+
+    /// Change the simulated robot mode (RTDE `robot_mode` and dashboard `robotmode`)
+    pub fn set_robot_mode(&self, mode: i32) {
+        self.state.lock().unwrap().robot_mode = mode;
+    }
+}
+
+impl Drop for MockRobot {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Accept connections until the mock is dropped, one thread per connection
+fn spawn_listener(
+    listener: TcpListener,
+    handler: Handler,
+    state: Arc<Mutex<MockState>>,
+    stop: Arc<AtomicBool>,
+) {
+    thread::spawn(move || {
+        while !stop.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if stream.set_nonblocking(false).is_ok() {
+                        let state = Arc::clone(&state);
+                        thread::spawn(move || handler(stream, state));
+                    }
+                }
+                Err(_) => thread::sleep(Duration::from_millis(10)),
+            }
+        }
+    });
+}
+
+fn serve_primary(mut stream: TcpStream, state: Arc<Mutex<MockState>>) {
+    let mut buffer = [0u8; 1024];
+    while let Ok(n) = stream.read(&mut buffer) {
+        if n == 0 {
+            break;
+        }
+        state.lock().unwrap().primary_scripts.push_str(&String::from_utf8_lossy(&buffer[..n]));
+    }
+}
+
+fn serve_dashboard(stream: TcpStream, state: Arc<Mutex<MockState>>) {
+    let Ok(mut writer) = stream.try_clone() else { return };
+    if writer.write_all(b"Connected: Universal Robots Dashboard Server\n").is_err() {
+        return;
+    }
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(command) = line else { break };
+        let mut state = state.lock().unwrap();
+        state.dashboard_commands.push(command.clone());
+
+        let reply = match command.as_str() {
+            "robotmode" => format!("Robotmode: {}", get_robot_mode_name(state.robot_mode)),
+            "power on" => {
+                state.robot_mode = 5;
+                "Powering on".to_string()
+            }
+            "brake release" => {
+                state.robot_mode = 7;
+                "Brake releasing".to_string()
+            }
+            "get robot model" => "UR5".to_string(),
+            "get serial number" => "20235500001".to_string(),
+            "PolyscopeVersion" => "URSoftware 5.11.1.108318 (Mar 22 2021)".to_string(),
+            "get loaded program" => "No program loaded".to_string(),
+            "programState" => "STOPPED <unnamed>".to_string(),
+            "running" => "Program running: false".to_string(),
+            _ => format!("could not understand: '{}'", command),
+        };
+        drop(state);
+
+        if writer.write_all(format!("{}\n", reply).as_bytes()).is_err() {
+            break;
+        }
+    }
+}
+
+fn serve_interpreter(stream: TcpStream, state: Arc<Mutex<MockState>>) {
+    let Ok(mut writer) = stream.try_clone() else { return };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(command) = line else { break };
+        let mut state = state.lock().unwrap();
+        state.interpreter_commands.push(command.clone());
+
+        // Statements execute as soon as they are acknowledged
+        let reply = match command.as_str() {
+            "statelastinterpreted" | "statelastexecuted" => format!("{}: {}", command, state.last_id),
+            "statelastcleared" => format!("{}: {}", command, state.last_cleared),
+            _ => {
+                state.last_id += 1;
+                if command == "clear_interpreter()" {
+                    state.last_cleared = state.last_id;
+                }
+                execute_statement(&mut state, &command);
+                format!("ack: {}: {}", state.last_id, command)
+            }
+        };
+        drop(state);
+
+        if writer.write_all(format!("{}\n", reply).as_bytes()).is_err() {
+            break;
+        }
+    }
+}
+
+/// Apply the effects the mock models: `name = <literal>` and output register writes
+/// 
+/// Register values may be number literals, variables or `name[i]`;
+/// conditionals and anything else are ignored.
+fn execute_statement(state: &mut MockState, command: &str) {
+    if command.starts_with("if ") {
+        return;
+    }
+    if let Some((name, literal)) = command.split_once(" = ") {
+        let literal = literal.trim();
+        let values = literal.parse::<f64>().ok().map(|value| vec![value]).or_else(|| {
+            literal.trim_start_matches('p').strip_prefix('[')?.strip_suffix(']')?
+                .split(',')
+                .map(|value| value.trim().parse().ok())
+                .collect()
+        });
+        if let Some(values) = values {
+            state.variables.insert(name.trim().to_string(), values);
+        }
+        return;
+    }
+
+    // A statement naming an unknown variable fails as a whole, writing nothing
+    let mut writes = Vec::new();
+    for (call, integer) in [("write_output_float_register(", false), ("write_output_integer_register(", true)] {
+        for (start, _) in command.match_indices(call) {
+            let args = &command[start + call.len()..];
+            let Some((register, value)) = args.split_once(')').and_then(|(args, _)| args.split_once(',')) else { return };
+            let Ok(register) = register.trim().parse::<u8>() else { return };
+            let value = value.trim();
+            let value = value.parse::<f64>().ok().or_else(|| {
+                let (name, index) = match value.split_once('[') {
+                    Some((name, index)) => (name, index.strip_suffix(']')?.parse().ok()?),
+                    None => (value, 0),
+                };
+                state.variables.get(name)?.get(index).copied()
+            });
+            let Some(value) = value else { return };
+            writes.push((register, value, integer));
+        }
+    }
+
+    for (register, value, integer) in writes {
+        if integer {
+            state.int_registers.insert(register, value as i32);
+        } else {
+            state.double_registers.insert(register, value);
+        }
+    }
+}
+
+/// RTDE type and current value of the output variables the mock knows
+fn rtde_output(name: &str, state: &MockState) -> Option<(&'static str, Vec<u8>)> {
+    let robot_mode = state.robot_mode;
+    let doubles = |values: &[f64]| values.iter().flat_map(|v| v.to_be_bytes()).collect::<Vec<u8>>();
+
+    let output = match name {
+        "timestamp" => ("DOUBLE", doubles(&[0.0])),
+        "actual_q" => ("VECTOR6D", doubles(&MOCK_JOINT_POSITIONS)),
+        "actual_TCP_pose" => ("VECTOR6D", doubles(&MOCK_TCP_POSE)),
+        "actual_TCP_force" | "actual_qd" | "actual_TCP_speed" => ("VECTOR6D", doubles(&[0.0; 6])),
+        "robot_mode" => ("INT32", robot_mode.to_be_bytes().to_vec()),
+        "safety_mode" => ("INT32", 1i32.to_be_bytes().to_vec()),
+        "runtime_state" => ("UINT32", 1u32.to_be_bytes().to_vec()),
+        "actual_digital_input_bits" | "actual_digital_output_bits" => ("UINT64", 0u64.to_be_bytes().to_vec()),
+        "standard_analog_input0" | "standard_analog_input1" | "tool_analog_input0" | "tool_analog_input1" => ("DOUBLE", doubles(&[0.0])),
+        "tool_output_voltage" => ("INT32", 24i32.to_be_bytes().to_vec()),
+        _ if name.starts_with("output_int_register_") => {
+            let register = name["output_int_register_".len()..].parse().ok()?;
+            ("INT32", state.int_registers.get(&register).copied().unwrap_or(0).to_be_bytes().to_vec())
+        }
+        _ if name.starts_with("output_double_register_") => {
+            let register = name["output_double_register_".len()..].parse().ok()?;
+            ("DOUBLE", doubles(&[state.double_registers.get(&register).copied().unwrap_or(0.0)]))
+        }
+        _ => return None,
+    };
+    Some(output)
+}
+
+fn write_rtde(stream: &mut TcpStream, msg_type: u8, payload: &[u8]) -> std::io::Result<()> {
+    let size = (payload.len() + 3) as u16;
+    stream.write_all(&[(size >> 8) as u8, size as u8, msg_type])?;
+    stream.write_all(payload)
+}
+
+fn serve_rtde(mut stream: TcpStream, state: Arc<Mutex<MockState>>) {
+    let mut recipes: Vec<(u8, Vec<String>, f64)> = Vec::new();
+    let mut header = [0u8; 3];
+
+    // Handshake until the client starts synchronization
+    loop {
+        if stream.read_exact(&mut header).is_err() {
+            return;
+        }
+        let mut payload = vec![0u8; (u16::from_be_bytes([header[0], header[1]]) as usize).saturating_sub(3)];
+        if stream.read_exact(&mut payload).is_err() {
+            return;
+        }
+
+        let reply = match header[2] {
+            79 if payload.len() >= 8 => {
+                let frequency = f64::from_be_bytes(payload[..8].try_into().unwrap());
+                let names: Vec<String> = String::from_utf8_lossy(&payload[8..]).split(',').map(str::to_string).collect();
+                let state = state.lock().unwrap();
+                let types: Vec<&str> = names.iter()
+                    .map(|name| rtde_output(name, &state).map_or("NOT_FOUND", |(var_type, _)| var_type))
+                    .collect();
+                drop(state);
+
+                // A recipe with unknown variables is refused but still gets an ID
+                let id = recipes.len() as u8 + 1;
+                if !types.contains(&"NOT_FOUND") {
+                    recipes.push((id, names, frequency));
+                }
+                [&[id][..], types.join(",").as_bytes()].concat()
+            }
+            78 => {
+                let types: Vec<&str> = String::from_utf8_lossy(&payload).split(',')
+                    .map(|name| match name {
+                        "speed_slider_mask" => "UINT32",
+                        "speed_slider_fraction" => "DOUBLE",
+                        _ => "NOT_FOUND",
+                    })
+                    .collect();
+                [&[1u8][..], types.join(",").as_bytes()].concat()
+            }
+            _ => vec![1],
+        };
+        if write_rtde(&mut stream, header[2], &reply).is_err() {
+            return;
+        }
+        if header[2] == 83 {
+            break;
+        }
+    }
+
+    // Drain input packages so the client never blocks on a full socket
+    if let Ok(mut reader) = stream.try_clone() {
+        thread::spawn(move || {
+            let mut buffer = [0u8; 1024];
+            while matches!(reader.read(&mut buffer), Ok(n) if n > 0) {}
+        });
+    }
+
+    let frequency = recipes.iter().map(|(_, _, frequency)| *frequency).fold(1.0, f64::max).min(125.0);
+    let mut timestamp = 0.0;
+    loop {
+        let packages: Vec<Vec<u8>> = {
+            let state = state.lock().unwrap();
+            recipes.iter().map(|(id, names, _)| {
+                let mut package = vec![*id];
+                for name in names {
+                    match rtde_output(name, &state) {
+                        _ if name == "timestamp" => package.extend_from_slice(&f64::to_be_bytes(timestamp)),
+                        Some((_, value)) => package.extend_from_slice(&value),
+                        None => {}
+                    }
+                }
+                package
+            }).collect()
+        };
+        for package in packages {
+            if write_rtde(&mut stream, 85, &package).is_err() {
+                return;
+            }
+        }
+        timestamp += 1.0 / frequency;
+        thread::sleep(Duration::from_secs_f64(1.0 / frequency));
+    }
+}
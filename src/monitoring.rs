@@ -4,7 +4,9 @@
 //! output based on change detection and publication rate limiting.
 
 use serde::{Deserialize, Serialize};
-use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, HashMap};
+
+crate::json_output::impl_event!(PositionData, RobotStateData, RuntimeEventData, RtdeDebugData, WrenchData, JointDiagData);
 
 /// Combined position monitoring data (TCP pose + joint angles)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -18,6 +20,9 @@ pub struct PositionData {
     /// Event type for JSON output
     #[serde(rename = "type")]
     pub event_type: String,
+    /// Per-event-type sequence number (from 1), so subscribers can detect gaps
+    #[serde(default)]
+    pub seq: u64,
     /// TCP pose [x, y, z, rx, ry, rz] in meters and radians
     pub tcp_pose: [f64; 6],
     /// Joint angles in radians [q0, q1, q2, q3, q4, q5]
@@ -36,6 +41,9 @@ pub struct RobotStateData {
     /// Event type for JSON output
     #[serde(rename = "type")]
     pub event_type: String,
+    /// Per-event-type sequence number (from 1), so subscribers can detect gaps
+    #[serde(default)]
+    pub seq: u64,
     /// Robot mode (numeric)
     pub robot_mode: i32,
     /// Robot mode name
@@ -50,6 +58,92 @@ pub struct RobotStateData {
     pub runtime_state_name: String,
 }
 
+/// Runtime state transition (e.g. PLAYING -> STOPPED when a program ends)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeEventData {
+    /// Robot's internal timestamp (seconds since robot power-on)
+    /// None if robot timestamp is not available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtime: Option<f64>,
+    /// System timestamp (Unix epoch time when data was received by daemon)
+    pub stime: f64,
+    /// Event type for JSON output
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Per-event-type sequence number (from 1), so subscribers can detect gaps
+    #[serde(default)]
+    pub seq: u64,
+    /// Runtime state before the transition (numeric)
+    pub previous_state: i32,
+    /// Runtime state name before the transition
+    pub previous_state_name: String,
+    /// Runtime state after the transition (numeric)
+    pub runtime_state: i32,
+    /// Runtime state name after the transition
+    pub runtime_state_name: String,
+}
+
+/// Raw RTDE data package for protocol debugging
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RtdeDebugData {
+    /// Robot's internal timestamp (seconds since robot power-on)
+    /// None if robot timestamp is not available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtime: Option<f64>,
+    /// System timestamp (Unix epoch time when data was received by daemon)
+    pub stime: f64,
+    /// Event type for JSON output
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Per-event-type sequence number (from 1), so subscribers can detect gaps
+    #[serde(default)]
+    pub seq: u64,
+    /// Every decoded recipe variable, keyed by RTDE variable name
+    pub variables: BTreeMap<String, Vec<f64>>,
+}
+
+/// TCP wrench monitoring data (force/torque)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WrenchData {
+    /// Robot's internal timestamp (seconds since robot power-on)
+    /// None if robot timestamp is not available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtime: Option<f64>,
+    /// System timestamp (Unix epoch time when data was received by daemon)
+    pub stime: f64,
+    /// Event type for JSON output
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Per-event-type sequence number (from 1), so subscribers can detect gaps
+    #[serde(default)]
+    pub seq: u64,
+    /// Force at the TCP [fx, fy, fz] in newtons
+    pub force: [f64; 3],
+    /// Torque at the TCP [tx, ty, tz] in newton-meters
+    pub torque: [f64; 3],
+}
+
+/// Joint diagnostics for predictive maintenance (temperatures and motor currents)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JointDiagData {
+    /// Robot's internal timestamp (seconds since robot power-on)
+    /// None if robot timestamp is not available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtime: Option<f64>,
+    /// System timestamp (Unix epoch time when data was received by daemon)
+    pub stime: f64,
+    /// Event type for JSON output
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Per-event-type sequence number (from 1), so subscribers can detect gaps
+    #[serde(default)]
+    pub seq: u64,
+    /// Joint temperatures in degrees Celsius, base to wrist 3
+    pub temperatures: [f64; 6],
+    /// Actual joint currents in amperes, base to wrist 3
+    pub currents: [f64; 6],
+}
+
 impl PositionData {
     pub fn new_rounded(tcp_pose: [f64; 6], joint_positions: [f64; 6], rtime: Option<f64>, stime: f64, decimal_places: u32) -> Self {
         // Helper function to round values
@@ -80,6 +174,7 @@ impl PositionData {
             rtime,
             stime,
             event_type: "position".to_string(),
+            seq: 0,
             tcp_pose: rounded_tcp_pose,
             joint_positions: rounded_joint_positions,
         }
@@ -88,6 +183,7 @@ impl PositionData {
 
 
 impl RobotStateData {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         robot_mode: i32,
         robot_mode_name: String,
@@ -102,6 +198,7 @@ impl RobotStateData {
             rtime,
             stime,
             event_type: "robot_state".to_string(),
+            seq: 0,
             robot_mode,
             robot_mode_name,
             safety_mode,
@@ -112,14 +209,120 @@ impl RobotStateData {
     }
 }
 
+impl RuntimeEventData {
+    pub fn new(previous_state: i32, runtime_state: i32, rtime: Option<f64>, stime: f64) -> Self {
+        Self {
+            rtime,
+            stime,
+            event_type: "runtime_event".to_string(),
+            seq: 0,
+            previous_state,
+            previous_state_name: get_runtime_state_name(previous_state),
+            runtime_state,
+            runtime_state_name: get_runtime_state_name(runtime_state),
+        }
+    }
+}
+
+impl RtdeDebugData {
+    pub fn new(data: &HashMap<String, Vec<f64>>, rtime: Option<f64>, stime: f64) -> Self {
+        Self {
+            rtime,
+            stime,
+            event_type: "rtde_debug".to_string(),
+            seq: 0,
+            variables: data.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+        }
+    }
+}
+
+impl WrenchData {
+    /// Split an RTDE `actual_TCP_force` vector into force and torque
+    pub fn new_rounded(tcp_force: [f64; 6], rtime: Option<f64>, stime: f64, decimal_places: u32) -> Self {
+        let multiplier = 10.0_f64.powi(decimal_places as i32);
+        let round_value = |value: f64| (value * multiplier).round() / multiplier;
+        
+        Self {
+            rtime,
+            stime,
+            event_type: "wrench".to_string(),
+            seq: 0,
+            force: [round_value(tcp_force[0]), round_value(tcp_force[1]), round_value(tcp_force[2])],
+            torque: [round_value(tcp_force[3]), round_value(tcp_force[4]), round_value(tcp_force[5])],
+        }
+    }
+}
+
+impl JointDiagData {
+    /// Combine RTDE `joint_temperatures` and `actual_current`
+    pub fn new_rounded(temperatures: [f64; 6], currents: [f64; 6], rtime: Option<f64>, stime: f64, decimal_places: u32) -> Self {
+        let multiplier = 10.0_f64.powi(decimal_places as i32);
+        let round_value = |value: f64| (value * multiplier).round() / multiplier;
+        
+        Self {
+            rtime,
+            stime,
+            event_type: "joints_diag".to_string(),
+            seq: 0,
+            temperatures: temperatures.map(round_value),
+            currents: currents.map(round_value),
+        }
+    }
+}
+
+/// RTDE position samples received since the last `position` event
+#[derive(Debug, Default)]
+struct PositionWindow {
+    samples: u32,
+    tcp_sum: [f64; 6],
+    joint_sum: [f64; 6],
+    latest: Option<([f64; 6], [f64; 6])>,
+}
+
+impl PositionWindow {
+    fn add(&mut self, tcp_pose: [f64; 6], joint_positions: [f64; 6]) {
+        self.samples += 1;
+        for i in 0..6 {
+            self.tcp_sum[i] += tcp_pose[i];
+            self.joint_sum[i] += joint_positions[i];
+        }
+        self.latest = Some((tcp_pose, joint_positions));
+    }
+    
+    /// Empty the window, returning its latest sample or the mean of all samples
+    fn take(&mut self, averaging: bool) -> Option<([f64; 6], [f64; 6])> {
+        let window = std::mem::take(self);
+        if !averaging {
+            return window.latest;
+        }
+        window.latest?;
+        let count = window.samples as f64;
+        Some((window.tcp_sum.map(|sum| sum / count), window.joint_sum.map(|sum| sum / count)))
+    }
+}
+
+/// Whether `stime` is less than one `rate_hz` period after `last`
+/// 
+/// A clock that stepped backwards counts as a new interval rather than
+/// suppressing output until it catches up.
+fn within_interval(last: f64, stime: f64, rate_hz: u32) -> bool {
+    (0.0..1.0 / rate_hz.max(1) as f64).contains(&(stime - last))
+}
+
 /// Monitor output manager that handles dynamic output and rate limiting
 pub struct MonitorOutput {
     /// Last position data for change detection (TCP pose + joint positions)
     last_position: Option<([f64; 6], [f64; 6])>, // (tcp_pose, joint_positions)
     /// Last robot state for change detection
     last_robot_state: Option<(i32, i32, i32)>, // (robot_mode, safety_mode, runtime_state)
-    /// Last time combined position was output
-    last_position_output: Option<Instant>,
+    /// Last runtime state, for transition events independent of dynamic mode
+    last_runtime_state: Option<i32>,
+    /// Publish tick (`stime`) of the last combined position output
+    last_position_output: Option<f64>,
+    /// Samples coalesced into the next position output
+    position_window: PositionWindow,
+    /// Publish the mean of each interval instead of its latest sample
+    position_averaging: bool,
     /// Publication rate for position data
     pub_rate_hz: u32,
     /// Position change threshold for dynamic mode
@@ -128,6 +331,22 @@ pub struct MonitorOutput {
     dynamic_mode: bool,
     /// Number of decimal places for rounding
     pub decimal_places: u32,
+    /// Per-event-type overrides of `decimal_places`
+    topic_decimal_places: HashMap<String, u32>,
+    /// Raw RTDE debug output enabled
+    debug_rtde: bool,
+    /// `stime` of the last raw RTDE debug output
+    last_debug_output: Option<f64>,
+    /// Publication rate for wrench data (0 = disabled)
+    wrench_rate_hz: u32,
+    /// `stime` of the last wrench output
+    last_wrench_output: Option<f64>,
+    /// Publication rate for joint diagnostics (0 = disabled)
+    joint_diag_rate_hz: u32,
+    /// `stime` of the last joint diagnostics output
+    last_joint_diag_output: Option<f64>,
+    /// Last sequence number issued per event type
+    sequence_numbers: HashMap<String, u64>,
 }
 
 impl MonitorOutput {
@@ -136,26 +355,149 @@ impl MonitorOutput {
         Self {
             last_position: None,
             last_robot_state: None,
+            last_runtime_state: None,
             last_position_output: None,
+            position_window: PositionWindow::default(),
+            position_averaging: false,
             pub_rate_hz,
             position_threshold: 0.001, // 1mm or 0.001 radians
             dynamic_mode,
             decimal_places,
+            topic_decimal_places: HashMap::new(),
+            debug_rtde: false,
+            last_debug_output: None,
+            wrench_rate_hz: 0,
+            last_wrench_output: None,
+            joint_diag_rate_hz: 0,
+            last_joint_diag_output: None,
+            sequence_numbers: HashMap::new(),
         }
     }
     
-    /// Check if combined position (TCP + joints) should be output
-    pub fn should_output_position(&mut self, tcp_pose: [f64; 6], joint_positions: [f64; 6], _timestamp: f64) -> bool {
-        let now = Instant::now();
+    /// Carry change detection, publish timing and sequence numbers over from
+    /// `previous`, so replacing the output (e.g. on config reload) is seamless
+    pub fn continue_from(mut self, previous: MonitorOutput) -> Self {
+        self.last_position = previous.last_position;
+        self.last_robot_state = previous.last_robot_state;
+        self.last_runtime_state = previous.last_runtime_state;
+        self.last_position_output = previous.last_position_output;
+        self.sequence_numbers = previous.sequence_numbers;
+        self
+    }
+    
+    /// Enable or disable raw RTDE debug output
+    pub fn with_debug_rtde(mut self, enabled: bool) -> Self {
+        self.debug_rtde = enabled;
+        self
+    }
+    
+    /// Publish the mean joint/TCP values of each interval instead of the latest sample
+    /// 
+    /// TCP rotation vectors are averaged component-wise, which is only
+    /// meaningful for the small rotations within one publish interval.
+    pub fn with_position_averaging(mut self, enabled: bool) -> Self {
+        self.position_averaging = enabled;
+        self
+    }
+    
+    /// Set the wrench publication rate (0 disables wrench output)
+    pub fn with_wrench_rate(mut self, wrench_rate_hz: u32) -> Self {
+        self.wrench_rate_hz = wrench_rate_hz;
+        self
+    }
+    
+    /// Check if wrench data should be output (rate limited to `wrench_rate_hz`
+    /// on the package's `stime`)
+    pub fn should_output_wrench(&mut self, stime: f64) -> bool {
+        if self.wrench_rate_hz == 0 {
+            return false;
+        }
         
-        // Check rate limiting (will be re-enabled after testing)
-        if let Some(last_output) = self.last_position_output {
-            let min_interval = Duration::from_millis(1000 / self.pub_rate_hz as u64);
-            if now.duration_since(last_output) < min_interval {
+        if let Some(last_output) = self.last_wrench_output {
+            if within_interval(last_output, stime, self.wrench_rate_hz) {
                 return false;
             }
         }
         
+        self.last_wrench_output = Some(stime);
+        true
+    }
+    
+    /// Override the decimal places for individual event types (e.g. `position`)
+    pub fn with_topic_decimal_places(mut self, topic_decimal_places: HashMap<String, u32>) -> Self {
+        self.topic_decimal_places = topic_decimal_places;
+        self
+    }
+    
+    /// Decimal places for events of type `topic`, falling back to the global setting
+    pub fn decimal_places_for(&self, topic: &str) -> u32 {
+        self.topic_decimal_places.get(topic).copied().unwrap_or(self.decimal_places)
+    }
+    
+    /// Set the joint diagnostics publication rate (0 disables joints_diag output)
+    pub fn with_joint_diag_rate(mut self, joint_diag_rate_hz: u32) -> Self {
+        self.joint_diag_rate_hz = joint_diag_rate_hz;
+        self
+    }
+    
+    /// Check if joint diagnostics should be output (rate limited to
+    /// `joint_diag_rate_hz` on the package's `stime`)
+    pub fn should_output_joint_diag(&mut self, stime: f64) -> bool {
+        if self.joint_diag_rate_hz == 0 {
+            return false;
+        }
+        
+        if let Some(last_output) = self.last_joint_diag_output {
+            if within_interval(last_output, stime, self.joint_diag_rate_hz) {
+                return false;
+            }
+        }
+        
+        self.last_joint_diag_output = Some(stime);
+        true
+    }
+    
+    /// Check if raw RTDE debug data should be output (rate limited to
+    /// `pub_rate_hz` on the package's `stime`)
+    pub fn should_output_rtde_debug(&mut self, stime: f64) -> bool {
+        if !self.debug_rtde {
+            return false;
+        }
+        
+        if let Some(last_output) = self.last_debug_output {
+            if within_interval(last_output, stime, self.pub_rate_hz) {
+                return false;
+            }
+        }
+        
+        self.last_debug_output = Some(stime);
+        true
+    }
+    
+    /// Add an RTDE sample and return the combined position (TCP + joints) to
+    /// output once per publish interval
+    /// 
+    /// Every sample is kept until the next tick, which publishes the latest one
+    /// (or the mean, with position averaging). Ticks follow a fixed
+    /// `pub_rate_hz` schedule rather than restarting at each output. The first
+    /// sample is published immediately.
+    /// 
+    /// Timing runs on the package's `stime`, so a replayed recording publishes
+    /// the same samples as the daemon did, at any playback speed.
+    pub fn coalesce_position(&mut self, tcp_pose: [f64; 6], joint_positions: [f64; 6], stime: f64) -> Option<([f64; 6], [f64; 6])> {
+        self.position_window.add(tcp_pose, joint_positions);
+        
+        let interval = 1.0 / self.pub_rate_hz.max(1) as f64;
+        let tick = match self.last_position_output {
+            Some(last_tick) if within_interval(last_tick, stime, self.pub_rate_hz) => return None,
+            // Keep the cadence unless samples stopped arriving for a whole interval
+            Some(last_tick) if stime - last_tick < interval * 2.0 => last_tick + interval,
+            _ => stime,
+        };
+        self.last_position_output = Some(tick);
+        
+        let (tcp_pose, joint_positions) = self.position_window.take(self.position_averaging)?;
+        
         // Check change detection in dynamic mode
         if self.dynamic_mode {
             if let Some((last_tcp, last_joints)) = self.last_position {
@@ -164,7 +506,7 @@ impl MonitorOutput {
                 let joints_changed = self.positions_changed(&last_joints, &joint_positions);
                 
                 if !tcp_changed && !joints_changed {
-                    return false;
+                    return None;
                 }
             }
             // If no previous position exists, output (first time)
@@ -172,8 +514,7 @@ impl MonitorOutput {
         
         // Update state
         self.last_position = Some((tcp_pose, joint_positions));
-        self.last_position_output = Some(now);
-        true
+        Some((tcp_pose, joint_positions))
     }
     
     /// Check if robot state should be output (never rate limited, only change detection)
@@ -194,6 +535,14 @@ impl MonitorOutput {
         true
     }
     
+    /// Runtime state before this sample if it changed since the last one
+    /// 
+    /// The first sample only records the state; there is no transition to report.
+    pub fn runtime_transition(&mut self, runtime_state: i32) -> Option<i32> {
+        let previous = self.last_runtime_state.replace(runtime_state)?;
+        (previous != runtime_state).then_some(previous)
+    }
+    
     /// Check if positions have changed significantly
     fn positions_changed(&self, old: &[f64; 6], new: &[f64; 6]) -> bool {
         for (old_val, new_val) in old.iter().zip(new.iter()) {
@@ -204,44 +553,78 @@ impl MonitorOutput {
         false
     }
     
+    /// Next sequence number for events of type `event_type`
+    fn next_seq(&mut self, event_type: &str) -> u64 {
+        let seq = self.sequence_numbers.entry(event_type.to_string()).or_insert(0);
+        *seq += 1;
+        *seq
+    }
+    
     /// Output combined position data as JSON with consistent decimal formatting
-    pub fn output_position(&self, data: &PositionData) {
+    pub fn output_position(&mut self, mut data: PositionData) {
+        data.seq = self.next_seq(&data.event_type);
         // Custom JSON formatting to ensure consistent decimal places
+        let decimal_places = self.decimal_places_for(&data.event_type);
         let tcp_formatted: Vec<String> = data.tcp_pose.iter()
-            .map(|&v| format!("{:.prec$}", v, prec = self.decimal_places as usize))
+            .map(|&v| format!("{:.prec$}", v, prec = decimal_places as usize))
             .collect();
         let joint_formatted: Vec<String> = data.joint_positions.iter()
-            .map(|&v| format!("{:.prec$}", v, prec = self.decimal_places as usize))
+            .map(|&v| format!("{:.prec$}", v, prec = decimal_places as usize))
             .collect();
         
         // Build JSON with both timestamp fields
         let json = if let Some(rtime) = data.rtime {
             format!(
-                r#"{{"rtime":{:.6},"stime":{:.6},"type":"{}","tcp_pose":[{}],"joint_positions":[{}]}}"#,
+                r#"{{"rtime":{:.6},"stime":{:.6},"type":"{}","seq":{},"tcp_pose":[{}],"joint_positions":[{}]}}"#,
                 rtime,
                 data.stime,
                 data.event_type,
+                data.seq,
                 tcp_formatted.join(","),
                 joint_formatted.join(",")
             )
         } else {
             format!(
-                r#"{{"stime":{:.6},"type":"{}","tcp_pose":[{}],"joint_positions":[{}]}}"#,
+                r#"{{"stime":{:.6},"type":"{}","seq":{},"tcp_pose":[{}],"joint_positions":[{}]}}"#,
                 data.stime,
                 data.event_type,
+                data.seq,
                 tcp_formatted.join(","),
                 joint_formatted.join(",")
             )
         };
         
-        println!("{}", json);
+        crate::json_output::output_json_line(&json, &data.event_type);
     }
     
     /// Output robot state as JSON
-    pub fn output_robot_state(&self, data: &RobotStateData) {
-        if let Ok(json) = serde_json::to_string(data) {
-            println!("{}", json);
-        }
+    pub fn output_robot_state(&mut self, mut data: RobotStateData) {
+        data.seq = self.next_seq(&data.event_type);
+        crate::json_output::output_event(&data);
+    }
+    
+    /// Output a runtime state transition as JSON
+    pub fn output_runtime_event(&mut self, mut data: RuntimeEventData) {
+        data.seq = self.next_seq(&data.event_type);
+        crate::json_output::output_event(&data);
+    }
+    
+    /// Output wrench data as JSON
+    pub fn output_wrench(&mut self, mut data: WrenchData) {
+        data.seq = self.next_seq(&data.event_type);
+        crate::json_output::output_event(&data);
+    }
+    
+    /// Output joint diagnostics as JSON
+    pub fn output_joint_diag(&mut self, mut data: JointDiagData) {
+        data.seq = self.next_seq(&data.event_type);
+        crate::json_output::output_event(&data);
+    }
+    
+    /// Output raw RTDE debug data as JSON
+    pub fn output_rtde_debug(&mut self, mut data: RtdeDebugData) {
+        data.seq = self.next_seq(&data.event_type);
+        crate::json_output::output_event(&data);
     }
 }
 
@@ -309,4 +692,130 @@ pub fn get_runtime_state_name(state: i32) -> String {
         .find(|(num, _)| *num == state)
         .map(|(_, name)| name.to_string())
         .unwrap_or_else(|| format!("UNKNOWN({})", state))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_rtde_debug_disabled_by_default() {
+        let mut output = MonitorOutput::new(10, false, 4);
+        assert!(!output.should_output_rtde_debug(0.0));
+    }
+    
+    #[test]
+    fn test_rtde_debug_publishes_raw_map_when_enabled() {
+        let mut output = MonitorOutput::new(10, false, 4).with_debug_rtde(true);
+        
+        let mut raw = HashMap::new();
+        raw.insert("actual_q".to_string(), vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        raw.insert("robot_mode".to_string(), vec![7.0]);
+        
+        // First sample is published, an immediate second one is rate limited
+        assert!(output.should_output_rtde_debug(100.0));
+        assert!(!output.should_output_rtde_debug(100.05));
+        assert!(output.should_output_rtde_debug(100.2));
+        
+        let data = RtdeDebugData::new(&raw, Some(12.5), 1700000000.0);
+        let json: serde_json::Value = serde_json::to_value(&data).unwrap();
+        assert_eq!(json["type"], "rtde_debug");
+        assert_eq!(json["rtime"], 12.5);
+        assert_eq!(json["variables"]["robot_mode"][0], 7.0);
+        assert_eq!(json["variables"]["actual_q"].as_array().unwrap().len(), 6);
+    }
+    
+    #[test]
+    fn test_runtime_transitions() {
+        // Transitions are reported even when continuous (non-dynamic) output is on
+        let mut output = MonitorOutput::new(10, false, 4);
+        assert_eq!(output.runtime_transition(2), None);
+        assert_eq!(output.runtime_transition(2), None);
+        assert_eq!(output.runtime_transition(1), Some(2));
+        assert_eq!(output.runtime_transition(1), None);
+        
+        let data = RuntimeEventData::new(2, 1, None, 1700000000.0);
+        let json: serde_json::Value = serde_json::to_value(&data).unwrap();
+        assert_eq!(json["type"], "runtime_event");
+        assert_eq!(json["previous_state_name"], "PLAYING");
+        assert_eq!(json["runtime_state_name"], "STOPPED");
+    }
+    
+    #[test]
+    fn test_topic_decimal_places_override_global() {
+        let overrides = HashMap::from([("position".to_string(), 6), ("wrench".to_string(), 1)]);
+        let output = MonitorOutput::new(10, false, 4).with_topic_decimal_places(overrides);
+        assert_eq!(output.decimal_places_for("position"), 6);
+        assert_eq!(output.decimal_places_for("wrench"), 1);
+        assert_eq!(output.decimal_places_for("joints_diag"), 4);
+        
+        let pose = [0.1234567, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let data = PositionData::new_rounded(pose, [0.0; 6], None, 0.0, output.decimal_places_for("position"));
+        assert_eq!(data.tcp_pose[0], 0.123457);
+    }
+    
+    #[test]
+    fn test_position_coalescing_latest_and_average() {
+        for (averaging, expected) in [(false, 6.0), (true, 4.0)] {
+            let mut output = MonitorOutput::new(20, false, 4).with_position_averaging(averaging);
+            
+            // The first sample goes out at once, later ones wait for the next tick
+            assert_eq!(output.coalesce_position([1.0; 6], [1.0; 6], 10.0), Some(([1.0; 6], [1.0; 6])));
+            assert_eq!(output.coalesce_position([2.0; 6], [2.0; 6], 10.01), None);
+            assert_eq!(output.coalesce_position([4.0; 6], [4.0; 6], 10.02), None);
+            
+            let (tcp_pose, joint_positions) = output.coalesce_position([6.0; 6], [6.0; 6], 10.06).unwrap();
+            assert_eq!(tcp_pose, [expected; 6]);
+            assert_eq!(joint_positions, [expected; 6]);
+        }
+    }
+    
+    #[test]
+    fn test_sequence_numbers_per_event_type() {
+        let mut output = MonitorOutput::new(10, false, 4);
+        assert_eq!(output.next_seq("position"), 1);
+        assert_eq!(output.next_seq("position"), 2);
+        assert_eq!(output.next_seq("robot_state"), 1);
+        assert_eq!(output.next_seq("position"), 3);
+        
+        // Events that never went through MonitorOutput deserialize with seq 0
+        let data: RobotStateData = serde_json::from_str(r#"{"stime":1.0,"type":"robot_state","robot_mode":7,"robot_mode_name":"RUNNING","safety_mode":1,"safety_mode_name":"NORMAL","runtime_state":1,"runtime_state_name":"STOPPED"}"#).unwrap();
+        assert_eq!(data.seq, 0);
+    }
+    
+    #[test]
+    fn test_wrench_output_rate_and_split() {
+        let mut disabled = MonitorOutput::new(10, false, 4);
+        assert!(!disabled.should_output_wrench(0.0));
+        
+        let mut output = MonitorOutput::new(10, false, 4).with_wrench_rate(50);
+        assert!(output.should_output_wrench(5.0));
+        assert!(!output.should_output_wrench(5.01));
+        // A clock stepped backwards doesn't stall output
+        assert!(output.should_output_wrench(4.0));
+        
+        let data = WrenchData::new_rounded([1.0, -2.0, 30.123456, 0.1, 0.2, -0.3], None, 1700000000.0, 2);
+        assert_eq!(data.force, [1.0, -2.0, 30.12]);
+        assert_eq!(data.torque, [0.1, 0.2, -0.3]);
+        
+        let json: serde_json::Value = serde_json::to_value(&data).unwrap();
+        assert_eq!(json["type"], "wrench");
+        assert!(json.get("rtime").is_none());
+    }
+    
+    #[test]
+    fn test_joint_diag_output() {
+        let mut output = MonitorOutput::new(10, false, 4).with_joint_diag_rate(1);
+        assert!(output.should_output_joint_diag(1.0));
+        assert!(!output.should_output_joint_diag(1.5));
+        assert!(output.should_output_joint_diag(2.0));
+        
+        let data = JointDiagData::new_rounded([31.26, 33.0, 32.5, 35.1, 36.0, 34.94], [0.123, -1.5, 0.8, 0.05, 0.02, 0.0], Some(3.0), 1700000000.0, 1);
+        assert_eq!(data.temperatures, [31.3, 33.0, 32.5, 35.1, 36.0, 34.9]);
+        assert_eq!(data.currents[0], 0.1);
+        
+        let json: serde_json::Value = serde_json::to_value(&data).unwrap();
+        assert_eq!(json["type"], "joints_diag");
+        assert_eq!(json["currents"].as_array().unwrap().len(), 6);
+    }
+}
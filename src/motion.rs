@@ -0,0 +1,271 @@
+//! URScript Motion Generation
+//!
+//! Builds movej/movel URScript commands from typed targets so callers don't
+//! have to template command strings by hand, plus the IK query behind `@ik`
+//! and the `force_mode` call behind `@force`.
+
+use crate::config::MovementConfig;
+
+/// Default first of the six RTDE output double registers `inverse_kin` writes the solution to
+/// (`command.ik_double_register`)
+pub const IK_RESULT_REGISTER: u8 = 18;
+/// Default RTDE output int register `inverse_kin` writes its request marker to
+/// (`command.ik_int_register`)
+pub const IK_STATUS_REGISTER: u8 = 23;
+/// URScript's default `movej` joint acceleration (rad/s²)
+pub const DEFAULT_JOINT_ACCELERATION: f64 = 1.4;
+/// URScript's default `movej` joint speed (rad/s)
+pub const DEFAULT_JOINT_VELOCITY: f64 = 1.05;
+
+/// Joint configuration selection for Cartesian targets
+///
+/// A Cartesian pose can usually be reached with several joint configurations
+/// (elbow up/down, wrist flipped). Without a hint the controller picks one,
+/// which may flip the elbow mid-sequence.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum JointConfiguration {
+    /// Let the controller choose the inverse kinematics solution
+    #[default]
+    Any,
+    /// Bias toward the robot's joint configuration at execution time
+    Current,
+    /// Bias toward the given joint configuration (radians)
+    Near([f64; 6]),
+}
+
+/// Motion parameters for a single move
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveParams {
+    /// Tool acceleration (m/s²), or joint acceleration (rad/s²) for `movej`
+    pub acceleration: f64,
+    /// Tool speed (m/s), or joint speed (rad/s) for `movej`
+    pub velocity: f64,
+    /// Blend radius (m), omitted from the command when zero
+    pub blend_radius: f64,
+}
+
+impl From<&MovementConfig> for MoveParams {
+    fn from(movement: &MovementConfig) -> Self {
+        Self {
+            acceleration: movement.acceleration,
+            velocity: movement.speed,
+            blend_radius: movement.blend_radius,
+        }
+    }
+}
+
+impl MoveParams {
+    /// URScript's own `movej` defaults, without blending
+    /// 
+    /// `MovementConfig` holds tool-space values, which don't suit joint moves.
+    pub fn joint_defaults() -> Self {
+        Self {
+            acceleration: DEFAULT_JOINT_ACCELERATION,
+            velocity: DEFAULT_JOINT_VELOCITY,
+            blend_radius: 0.0,
+        }
+    }
+}
+
+/// Parameters of a `force_mode` call, in URScript argument order
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForceMode {
+    /// Force frame pose relative to the base
+    pub task_frame: [f64; 6],
+    /// Axes (x, y, z, rx, ry, rz) that are compliant rather than position controlled
+    pub selection_vector: [bool; 6],
+    /// Force (N) / torque (Nm) to apply along compliant axes
+    pub wrench: [f64; 6],
+    /// How the force frame is interpreted (1, 2 or 3, see the URScript manual)
+    pub force_type: u8,
+    /// Maximum TCP speed along compliant axes, maximum deviation along the others
+    pub limits: [f64; 6],
+}
+
+impl ForceMode {
+    /// Why the parameters would be refused by the controller, if they would
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=3).contains(&self.force_type) {
+            return Err(format!("force type must be 1, 2 or 3, got {}", self.force_type));
+        }
+        if self.task_frame.iter().chain(&self.wrench).any(|v| !v.is_finite()) {
+            return Err("task frame and wrench must be finite".to_string());
+        }
+        if self.limits.iter().any(|&limit| !limit.is_finite() || limit <= 0.0) {
+            return Err("limits must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Format a 6-element array as a URScript list body
+fn format_values(values: &[f64; 6]) -> String {
+    values.iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Format the trailing `a=, v=, r=` arguments
+fn format_params(params: &MoveParams) -> String {
+    if params.blend_radius > 0.0 {
+        format!("a={}, v={}, r={}", params.acceleration, params.velocity, params.blend_radius)
+    } else {
+        format!("a={}, v={}", params.acceleration, params.velocity)
+    }
+}
+
+/// Generate a linear move to a Cartesian TCP pose
+///
+/// With `JointConfiguration::Any` this is a plain `movel(p[...])`. Otherwise the
+/// target is resolved on the robot with `get_inverse_kin(pose, qnear=...)` so the
+/// solution closest to the hint is used; `movel` with a joint target still moves
+/// linearly in tool space.
+pub fn movel(pose: [f64; 6], params: &MoveParams, config: &JointConfiguration) -> String {
+    let target = format!("p[{}]", format_values(&pose));
+
+    let target = match config {
+        JointConfiguration::Any => target,
+        JointConfiguration::Current => {
+            format!("get_inverse_kin({}, qnear=get_actual_joint_positions())", target)
+        }
+        JointConfiguration::Near(q) => {
+            format!("get_inverse_kin({}, qnear=[{}])", target, format_values(q))
+        }
+    };
+
+    format!("movel({}, {})", target, format_params(params))
+}
+
+/// Generate a joint-space move to joint positions (radians)
+pub fn movej(q: [f64; 6], params: &MoveParams) -> String {
+    format!("movej([{}], {})", format_values(&q), format_params(params))
+}
+
+/// Generate the `force_mode(...)` call that starts force control
+/// 
+/// Force mode stays active until `END_FORCE_MODE` runs or the interpreter
+/// program stops; motions sent meanwhile are compliant along selected axes.
+pub fn force_mode(mode: &ForceMode) -> String {
+    let selection = mode.selection_vector.iter()
+        .map(|&compliant| if compliant { "1" } else { "0" })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "force_mode(p[{}], [{}], [{}], {}, [{}])",
+        format_values(&mode.task_frame),
+        selection,
+        format_values(&mode.wrench),
+        mode.force_type,
+        format_values(&mode.limits)
+    )
+}
+
+/// URScript that ends force mode
+pub const END_FORCE_MODE: &str = "end_force_mode()";
+
+/// Generate a one-line IK query that publishes its result through RTDE output registers
+///
+/// On success the joint solution goes to double registers `registers.0`
+/// onwards and `sequence` to int register `registers.1`; without a solution
+/// only `-sequence` is written. Whatever the registers held is overwritten. `get_inverse_kin` alone would raise a
+/// runtime error and stop the interpreter. Needs `get_inverse_kin_has_solution`
+/// (CB3 3.6 / e-Series).
+pub fn inverse_kin(pose: [f64; 6], config: &JointConfiguration, sequence: i32, registers: (u8, u8)) -> String {
+    let (result_register, status_register) = registers;
+    let args = match config {
+        JointConfiguration::Any => format!("p[{}]", format_values(&pose)),
+        JointConfiguration::Current => format!("p[{}], qnear=get_actual_joint_positions()", format_values(&pose)),
+        JointConfiguration::Near(q) => format!("p[{}], qnear=[{}]", format_values(&pose), format_values(q)),
+    };
+
+    let writes = (0..6)
+        .map(|i| format!("write_output_float_register({}, urd_ik[{}])", result_register + i, i))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "if get_inverse_kin_has_solution({args}): urd_ik = get_inverse_kin({args}) {writes} write_output_integer_register({reg}, {seq}) else: write_output_integer_register({reg}, {neg}) end",
+        args = args,
+        writes = writes,
+        reg = status_register,
+        seq = sequence,
+        neg = -sequence,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> MoveParams {
+        MoveParams { acceleration: 0.5, velocity: 0.1, blend_radius: 0.0 }
+    }
+
+    #[test]
+    fn test_movel_without_configuration_hint() {
+        let script = movel([0.1, -0.4, 0.3, 3.1, 0.0, 0.0], &params(), &JointConfiguration::Any);
+        assert_eq!(script, "movel(p[0.1, -0.4, 0.3, 3.1, 0, 0], a=0.5, v=0.1)");
+    }
+
+    #[test]
+    fn test_movel_biased_toward_current_configuration() {
+        let script = movel([0.1, -0.4, 0.3, 3.1, 0.0, 0.0], &params(), &JointConfiguration::Current);
+        assert_eq!(
+            script,
+            "movel(get_inverse_kin(p[0.1, -0.4, 0.3, 3.1, 0, 0], qnear=get_actual_joint_positions()), a=0.5, v=0.1)"
+        );
+    }
+
+    #[test]
+    fn test_movel_biased_toward_explicit_joints_with_blend() {
+        let params = MoveParams { blend_radius: 0.01, ..params() };
+        let config = JointConfiguration::Near([0.0, -1.57, 1.57, -1.57, -1.57, 0.0]);
+        let script = movel([0.1, -0.4, 0.3, 3.1, 0.0, 0.0], &params, &config);
+        assert_eq!(
+            script,
+            "movel(get_inverse_kin(p[0.1, -0.4, 0.3, 3.1, 0, 0], qnear=[0, -1.57, 1.57, -1.57, -1.57, 0]), a=0.5, v=0.1, r=0.01)"
+        );
+    }
+
+    #[test]
+    fn test_movej_with_joint_defaults() {
+        let script = movej([0.0, -1.57, 1.57, -1.57, -1.57, 0.0], &MoveParams::joint_defaults());
+        assert_eq!(script, "movej([0, -1.57, 1.57, -1.57, -1.57, 0], a=1.4, v=1.05)");
+    }
+
+    #[test]
+    fn test_force_mode_script_and_validation() {
+        let mut mode = ForceMode {
+            task_frame: [0.0; 6],
+            selection_vector: [false, false, true, false, false, false],
+            wrench: [0.0, 0.0, -10.0, 0.0, 0.0, 0.0],
+            force_type: 2,
+            limits: [0.1, 0.1, 0.15, 0.3, 0.3, 0.3],
+        };
+        assert_eq!(mode.validate(), Ok(()));
+        assert_eq!(
+            force_mode(&mode),
+            "force_mode(p[0, 0, 0, 0, 0, 0], [0, 0, 1, 0, 0, 0], [0, 0, -10, 0, 0, 0], 2, [0.1, 0.1, 0.15, 0.3, 0.3, 0.3])"
+        );
+
+        mode.limits[2] = 0.0;
+        assert!(mode.validate().is_err());
+        mode.limits[2] = 0.15;
+        mode.force_type = 4;
+        assert!(mode.validate().is_err());
+    }
+
+    #[test]
+    fn test_inverse_kin_publishes_marker_and_solution() {
+        let script = inverse_kin([0.1, -0.4, 0.3, 3.1, 0.0, 0.0], &JointConfiguration::Any, 7, (IK_RESULT_REGISTER, IK_STATUS_REGISTER));
+        assert!(script.starts_with("if get_inverse_kin_has_solution(p[0.1, -0.4, 0.3, 3.1, 0, 0]): urd_ik = get_inverse_kin(p[0.1, -0.4, 0.3, 3.1, 0, 0]) "));
+        assert!(script.contains("write_output_float_register(18, urd_ik[0])"));
+        assert!(script.contains("write_output_float_register(23, urd_ik[5]) write_output_integer_register(23, 7) else: write_output_integer_register(23, -7) end"));
+        assert!(!script.contains('\n'));
+
+        let script = inverse_kin([0.1, -0.4, 0.3, 3.1, 0.0, 0.0], &JointConfiguration::Current, 1, (30, 40));
+        assert!(script.contains("get_inverse_kin(p[0.1, -0.4, 0.3, 3.1, 0, 0], qnear=get_actual_joint_positions())"));
+        assert!(script.contains("write_output_float_register(35, urd_ik[5]) write_output_integer_register(40, 1)"));
+    }
+}
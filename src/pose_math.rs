@@ -0,0 +1,244 @@
+//! Rotation Vector Math
+//!
+//! Conversions between UR rotation vectors (axis-angle, as in `p[x,y,z,rx,ry,rz]`),
+//! quaternions, Euler angles and pointing directions. Used by `@pose` and
+//! available to integrators that post-process TCP poses.
+
+/// Convert rotation vector (axis-angle) to forward direction vector
+pub fn rotvec_to_direction_vector(rx: f64, ry: f64, rz: f64) -> [f64; 3] {
+    // Rotation vector magnitude is the rotation angle
+    let angle = (rx * rx + ry * ry + rz * rz).sqrt();
+    
+    if angle < 1e-8 {
+        // No rotation, return default forward direction (+Z)
+        return [0.0, 0.0, 1.0];
+    }
+    
+    // Normalize rotation axis
+    let kx = rx / angle;
+    let ky = ry / angle;
+    let kz = rz / angle;
+    
+    // Forward direction in TCP frame is +Z
+    let v = [0.0, 0.0, 1.0];
+    
+    // Rodrigues' rotation formula: v_rot = v*cos(θ) + (k×v)*sin(θ) + k*(k·v)*(1-cos(θ))
+    let cos_angle = angle.cos();
+    let sin_angle = angle.sin();
+    let one_minus_cos = 1.0 - cos_angle;
+    
+    // k·v (dot product)
+    let k_dot_v = kx * v[0] + ky * v[1] + kz * v[2]; // = kz since v = [0,0,1]
+    
+    // k×v (cross product)  
+    let cross_x = ky * v[2] - kz * v[1]; // ky*1 - kz*0 = ky
+    let cross_y = kz * v[0] - kx * v[2]; // kz*0 - kx*1 = -kx  
+    let cross_z = kx * v[1] - ky * v[0]; // kx*0 - ky*0 = 0
+    
+    // Apply Rodrigues' formula
+    let result_x = v[0] * cos_angle + cross_x * sin_angle + kx * k_dot_v * one_minus_cos;
+    let result_y = v[1] * cos_angle + cross_y * sin_angle + ky * k_dot_v * one_minus_cos;
+    let result_z = v[2] * cos_angle + cross_z * sin_angle + kz * k_dot_v * one_minus_cos;
+    
+    [result_x, result_y, result_z]
+}
+
+/// Convert rotation vector (axis-angle) to a unit quaternion [w, x, y, z]
+pub fn rotvec_to_quaternion(rx: f64, ry: f64, rz: f64) -> [f64; 4] {
+    let angle = (rx * rx + ry * ry + rz * rz).sqrt();
+    
+    if angle < 1e-8 {
+        // No rotation, identity quaternion
+        return [1.0, 0.0, 0.0, 0.0];
+    }
+    
+    // q = [cos(θ/2), k*sin(θ/2)] with k the normalized rotation axis
+    let half_angle = angle / 2.0;
+    let scale = half_angle.sin() / angle;
+    
+    [half_angle.cos(), rx * scale, ry * scale, rz * scale]
+}
+
+/// Convert a unit quaternion [w, x, y, z] back to a rotation vector (axis-angle)
+/// 
+/// The result has an angle in [0, π]; `q` and `-q` give the same rotation vector.
+pub fn quaternion_to_rotvec(quaternion: [f64; 4]) -> [f64; 3] {
+    // q and -q are the same rotation; pick w >= 0 so the angle stays in [0, π]
+    let [w, x, y, z] = if quaternion[0] < 0.0 { quaternion.map(|c| -c) } else { quaternion };
+    
+    let sin_half = (x * x + y * y + z * z).sqrt();
+    if sin_half < 1e-12 {
+        // No rotation
+        return [0.0, 0.0, 0.0];
+    }
+    
+    let angle = 2.0 * sin_half.atan2(w);
+    let scale = angle / sin_half;
+    
+    [x * scale, y * scale, z * scale]
+}
+
+/// Convert a unit quaternion [w, x, y, z] to ZYX Euler angles in degrees
+/// 
+/// Returns (yaw, pitch, roll) for the intrinsic Z-Y'-X'' sequence, i.e.
+/// R = Rz(yaw) * Ry(pitch) * Rx(roll) as used by ROS `tf`.
+pub fn quaternion_to_euler_zyx(quaternion: [f64; 4]) -> (f64, f64, f64) {
+    let [w, x, y, z] = quaternion;
+    
+    let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+    // Clamp to avoid NaN from rounding at the ±90° pitch singularity
+    let pitch = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+    let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+    
+    (yaw.to_degrees(), pitch.to_degrees(), roll.to_degrees())
+}
+
+/// Convert direction vector to azimuth/elevation angles in degrees
+pub fn direction_to_azimuth_elevation(direction: [f64; 3]) -> (f64, f64) {
+    let [dx, dy, dz] = direction;
+    
+    // Azimuth: angle in XY plane from +X axis (0° = +X, 90° = +Y)
+    // This is the compass bearing of where the robot is pointing horizontally
+    let azimuth_rad = dy.atan2(dx);
+    let azimuth_deg = azimuth_rad.to_degrees();
+    
+    // Elevation: angle from horizontal plane (0° = horizontal, 90° = +Z)
+    // This is how much the robot is pointing up (+) or down (-)
+    let horizontal_distance = (dx * dx + dy * dy).sqrt();
+    let elevation_rad = dz.atan2(horizontal_distance);
+    let elevation_deg = elevation_rad.to_degrees();
+    
+    (azimuth_deg, elevation_deg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_pose_azimuth_elevation_calculation() {
+        // Test data from actual robot output
+        // TCP pose: [-0.19005552,-0.91001301,0.91996543,1.41407608,0.51115312,-0.56129826]
+        let rx = 1.41407608;
+        let ry = 0.51115312;
+        let rz = -0.56129826;
+        
+        // Expected pointing direction from Python reference
+        let expected_direction_x = -0.0003620138906880177;
+        let expected_direction_y = -0.995729840111155;
+        let expected_direction_z = 0.09231443255611105;
+        
+        // Expected azimuth/elevation from Python reference  
+        let expected_azimuth = -90.02083081807142;
+        let expected_elevation = 5.296768755647904;
+        
+        // Calculate direction vector from rotation vector
+        let calculated_direction = rotvec_to_direction_vector(rx, ry, rz);
+        
+        // Calculate azimuth/elevation from direction
+        let (calculated_azimuth, calculated_elevation) = direction_to_azimuth_elevation(calculated_direction);
+        
+        // Test direction vector calculation (tolerance for floating point precision)
+        let direction_tolerance = 1e-6;
+        assert!((calculated_direction[0] - expected_direction_x).abs() < direction_tolerance,
+            "Direction X mismatch: calculated={}, expected={}", calculated_direction[0], expected_direction_x);
+        assert!((calculated_direction[1] - expected_direction_y).abs() < direction_tolerance,
+            "Direction Y mismatch: calculated={}, expected={}", calculated_direction[1], expected_direction_y);
+        assert!((calculated_direction[2] - expected_direction_z).abs() < direction_tolerance,
+            "Direction Z mismatch: calculated={}, expected={}", calculated_direction[2], expected_direction_z);
+        
+        // Test azimuth/elevation calculation (tolerance for floating point precision)
+        let angle_tolerance = 0.01; // 0.01 degree tolerance
+        assert!((calculated_azimuth - expected_azimuth).abs() < angle_tolerance,
+            "Azimuth mismatch: calculated={:.6}, expected={:.6}", calculated_azimuth, expected_azimuth);
+        assert!((calculated_elevation - expected_elevation).abs() < angle_tolerance,
+            "Elevation mismatch: calculated={:.6}, expected={:.6}", calculated_elevation, expected_elevation);
+        
+        println!("✓ Direction vector: [{:.12}, {:.12}, {:.12}]", 
+            calculated_direction[0], calculated_direction[1], calculated_direction[2]);
+        println!("✓ Azimuth: {:.6}° (expected: {:.6}°)", calculated_azimuth, expected_azimuth);
+        println!("✓ Elevation: {:.6}° (expected: {:.6}°)", calculated_elevation, expected_elevation);
+    }
+    
+    #[test]
+    fn test_pose_quaternion_and_euler_calculation() {
+        // Same robot rotation vector as test_pose_azimuth_elevation_calculation
+        let (rx, ry, rz) = (1.41407608, 0.51115312, -0.56129826);
+        let direction = rotvec_to_direction_vector(rx, ry, rz);
+        
+        let [w, x, y, z] = rotvec_to_quaternion(rx, ry, rz);
+        assert!((w * w + x * x + y * y + z * z - 1.0).abs() < 1e-9, "Quaternion not normalized");
+        
+        // Rotating the TCP +Z axis by the quaternion must give the same pointing direction
+        let rotated_z = [
+            2.0 * (x * z + w * y),
+            2.0 * (y * z - w * x),
+            1.0 - 2.0 * (x * x + y * y),
+        ];
+        for i in 0..3 {
+            assert!((rotated_z[i] - direction[i]).abs() < 1e-9,
+                "Quaternion axis {} mismatch: calculated={}, expected={}", i, rotated_z[i], direction[i]);
+        }
+        
+        // Third column of Rz(yaw) * Ry(pitch) * Rx(roll) is the rotated +Z axis as well
+        let (yaw, pitch, roll) = quaternion_to_euler_zyx([w, x, y, z]);
+        let (yaw, pitch, roll) = (yaw.to_radians(), pitch.to_radians(), roll.to_radians());
+        let euler_z = [
+            yaw.cos() * pitch.sin() * roll.cos() + yaw.sin() * roll.sin(),
+            yaw.sin() * pitch.sin() * roll.cos() - yaw.cos() * roll.sin(),
+            pitch.cos() * roll.cos(),
+        ];
+        for i in 0..3 {
+            assert!((euler_z[i] - direction[i]).abs() < 1e-9,
+                "Euler axis {} mismatch: calculated={}, expected={}", i, euler_z[i], direction[i]);
+        }
+        
+        // Identity rotation
+        assert_eq!(rotvec_to_quaternion(0.0, 0.0, 0.0), [1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(quaternion_to_euler_zyx([1.0, 0.0, 0.0, 0.0]), (0.0, 0.0, 0.0));
+    }
+    
+    #[test]
+    fn test_quaternion_rotvec_round_trip() {
+        for rotvec in [[1.41407608, 0.51115312, -0.56129826], [0.0, 3.1, 0.0], [-0.2, 0.1, 0.05]] {
+            let back = quaternion_to_rotvec(rotvec_to_quaternion(rotvec[0], rotvec[1], rotvec[2]));
+            for i in 0..3 {
+                assert!((back[i] - rotvec[i]).abs() < 1e-9, "Round trip mismatch: {:?} -> {:?}", rotvec, back);
+            }
+        }
+        
+        // A negated quaternion is the same rotation
+        let [w, x, y, z] = rotvec_to_quaternion(0.0, 3.1, 0.0);
+        let back = quaternion_to_rotvec([-w, -x, -y, -z]);
+        assert!((back[1] - 3.1).abs() < 1e-9);
+        assert_eq!(quaternion_to_rotvec([1.0, 0.0, 0.0, 0.0]), [0.0, 0.0, 0.0]);
+    }
+    
+    #[test]
+    fn test_basic_directions() {
+        // Test cardinal directions
+        
+        // Pointing +X (East): azimuth=0°, elevation=0°
+        let direction_east = [1.0, 0.0, 0.0];
+        let (az, el) = direction_to_azimuth_elevation(direction_east);
+        assert!((az - 0.0).abs() < 0.01);
+        assert!((el - 0.0).abs() < 0.01);
+        
+        // Pointing +Y (North): azimuth=90°, elevation=0°
+        let direction_north = [0.0, 1.0, 0.0];
+        let (az, el) = direction_to_azimuth_elevation(direction_north);
+        assert!((az - 90.0).abs() < 0.01);
+        assert!((el - 0.0).abs() < 0.01);
+        
+        // Pointing -Y (South): azimuth=-90°, elevation=0°
+        let direction_south = [0.0, -1.0, 0.0];
+        let (az, el) = direction_to_azimuth_elevation(direction_south);
+        assert!((az - (-90.0)).abs() < 0.01);
+        assert!((el - 0.0).abs() < 0.01);
+        
+        // Pointing +Z (Up): azimuth=undefined, elevation=90°
+        let direction_up = [0.0, 0.0, 1.0];
+        let (_az, el) = direction_to_azimuth_elevation(direction_up);
+        assert!((el - 90.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,226 @@
+//! RTDE Recording and Replay
+//!
+//! Captures every decoded RTDE data package seen by the monitoring loop to a
+//! JSON lines file, and reads such files back so the monitoring output can be
+//! reproduced offline without a robot.
+
+use crate::config::RecordingConfig;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// One recorded RTDE data package
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// System timestamp when the package was received (Unix epoch)
+    pub stime: f64,
+    /// Decoded recipe variables, as returned by `read_data_package`
+    pub data: HashMap<String, Vec<f64>>,
+}
+
+/// Append-only JSON lines file with size-based rollover
+/// 
+/// Shared by the RTDE recorder and the command audit log.
+pub(crate) struct RotatingFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    bytes_written: u64,
+    max_file_bytes: Option<u64>,
+    max_files: u32,
+}
+
+impl RotatingFile {
+    /// Open (append to) `path`; `max_file_bytes` of None or 0 never rolls over
+    pub(crate) fn open(path: &str, max_file_bytes: Option<u64>, max_files: u32) -> Result<Self> {
+        let path = PathBuf::from(path);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let bytes_written = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+            bytes_written,
+            max_file_bytes: max_file_bytes.filter(|&bytes| bytes > 0),
+            max_files,
+        })
+    }
+
+    /// Append one line (a newline is added)
+    pub(crate) fn write_line(&mut self, line: &str) -> Result<()> {
+        let line_bytes = line.len() as u64 + 1;
+        if let Some(max_bytes) = self.max_file_bytes {
+            if self.bytes_written > 0 && self.bytes_written + line_bytes > max_bytes {
+                self.rotate()?;
+            }
+        }
+
+        self.writer.write_all(line.as_bytes())
+            .and_then(|_| self.writer.write_all(b"\n"))
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+        self.bytes_written += line_bytes;
+        Ok(())
+    }
+
+    /// Flush buffered lines to disk
+    pub(crate) fn flush(&mut self) -> Result<()> {
+        self.writer.flush().with_context(|| format!("Failed to flush {}", self.path.display()))
+    }
+
+    /// Shift `file` -> `file.1` -> `file.2` ..., dropping the oldest, and start a new file
+    fn rotate(&mut self) -> Result<()> {
+        self.flush()?;
+
+        let rotated = |n: u32| PathBuf::from(format!("{}.{}", self.path.display(), n));
+        let oldest = self.max_files.saturating_sub(1);
+        if oldest == 0 {
+            fs::remove_file(&self.path).ok();
+        } else {
+            fs::remove_file(rotated(oldest)).ok();
+            for n in (1..oldest).rev() {
+                fs::rename(rotated(n), rotated(n + 1)).ok();
+            }
+            fs::rename(&self.path, rotated(1))
+                .with_context(|| format!("Failed to rotate {}", self.path.display()))?;
+        }
+
+        let file = File::create(&self.path)
+            .with_context(|| format!("Failed to create {}", self.path.display()))?;
+        self.writer = BufWriter::new(file);
+        self.bytes_written = 0;
+        Ok(())
+    }
+}
+
+impl Drop for RotatingFile {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+/// Writes RTDE data packages to a JSON lines file with size-based rollover
+pub struct RtdeRecorder {
+    file: RotatingFile,
+}
+
+impl RtdeRecorder {
+    /// Open (append to) the recording file described by `config`
+    pub fn new(config: &RecordingConfig) -> Result<Self> {
+        let file = RotatingFile::open(&config.path, config.max_file_bytes, config.max_files())
+            .context("Failed to open RTDE recording")?;
+        Ok(Self { file })
+    }
+
+    /// Append one data package
+    ///
+    /// Flushed per package: the daemon exits on Ctrl+C without running destructors.
+    pub fn record(&mut self, data: &HashMap<String, Vec<f64>>, wire_timestamp: f64) -> Result<()> {
+        let frame = RecordedFrame { stime: wire_timestamp, data: data.clone() };
+        self.file.write_line(&serde_json::to_string(&frame)?)
+            .and_then(|_| self.file.flush())
+            .context("Failed to write RTDE recording")
+    }
+}
+
+/// Reads frames back from a recording file
+pub struct RtdeReplay {
+    lines: std::io::Lines<BufReader<File>>,
+    line_number: usize,
+}
+
+impl RtdeReplay {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open RTDE recording {}", path.display()))?;
+        Ok(Self { lines: BufReader::new(file).lines(), line_number: 0 })
+    }
+}
+
+impl Iterator for RtdeReplay {
+    type Item = Result<RecordedFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            self.line_number += 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(serde_json::from_str(&line)
+                .with_context(|| format!("Invalid recorded frame on line {}", self.line_number)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("urd-{}-{}.jsonl", name, std::process::id()));
+        for suffix in ["", ".1", ".2"] {
+            fs::remove_file(format!("{}{}", path.display(), suffix)).ok();
+        }
+        path
+    }
+
+    fn frame_data(mode: f64) -> HashMap<String, Vec<f64>> {
+        let mut data = HashMap::new();
+        data.insert("robot_mode".to_string(), vec![mode]);
+        data.insert("actual_q".to_string(), vec![0.0, -1.57, 1.57, 0.0, 0.0, 0.0]);
+        data
+    }
+
+    #[test]
+    fn test_record_and_replay_round_trip() {
+        let path = temp_path("roundtrip");
+        let config = RecordingConfig { path: path.display().to_string(), max_file_bytes: None, max_files: None };
+
+        let mut recorder = RtdeRecorder::new(&config).unwrap();
+        recorder.record(&frame_data(5.0), 100.0).unwrap();
+        recorder.record(&frame_data(7.0), 100.008).unwrap();
+
+        // Readable while the recorder is still open (it's never dropped on Ctrl+C)
+        let frames: Vec<RecordedFrame> = RtdeReplay::open(&path).unwrap().collect::<Result<_>>().unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].stime, 100.008);
+        assert_eq!(frames[1].data, frame_data(7.0));
+
+        drop(recorder);
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_rollover_keeps_max_files() {
+        let path = temp_path("rollover");
+        let config = RecordingConfig { path: path.display().to_string(), max_file_bytes: Some(150), max_files: Some(2) };
+
+        let mut recorder = RtdeRecorder::new(&config).unwrap();
+        for i in 0..5 {
+            recorder.record(&frame_data(i as f64), i as f64).unwrap();
+        }
+        drop(recorder);
+
+        let rotated = PathBuf::from(format!("{}.1", path.display()));
+        assert!(rotated.exists());
+        assert!(!PathBuf::from(format!("{}.2", path.display())).exists());
+
+        // The newest frame is in the live file
+        let last = RtdeReplay::open(&path).unwrap().last().unwrap().unwrap();
+        assert_eq!(last.stime, 4.0);
+
+        fs::remove_file(&path).ok();
+        fs::remove_file(&rotated).ok();
+    }
+}
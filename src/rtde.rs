@@ -5,7 +5,7 @@ use crate::{Result, URError};
 use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::net::TcpStream;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::watch;
 
 /// RTDE message types
@@ -20,6 +20,40 @@ pub enum RTDEMessage {
     ControlPackagePause = 84,
 }
 
+impl TryFrom<u8> for RTDEMessage {
+    type Error = URError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            86 => Ok(RTDEMessage::RequestProtocolVersion),
+            77 => Ok(RTDEMessage::TextMessage),
+            85 => Ok(RTDEMessage::DataPackage),
+            79 => Ok(RTDEMessage::ControlPackageSetupOutputs),
+            78 => Ok(RTDEMessage::ControlPackageSetupInputs),
+            83 => Ok(RTDEMessage::ControlPackageStart),
+            84 => Ok(RTDEMessage::ControlPackagePause),
+            _ => Err(URError::Protocol(format!("Unknown message type: {}", value))),
+        }
+    }
+}
+
+/// Decoded data package variables, keyed by RTDE variable name
+pub type DataValues = HashMap<String, Vec<f64>>;
+
+/// Raw bits of integer-typed data package variables, keyed by RTDE variable name
+pub type DataBits = HashMap<String, Vec<u64>>;
+
+/// Output recipe registered with `RTDEClient::setup_output_recipe`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputRecipe {
+    /// Recipe ID the controller prefixes this recipe's data packages with
+    pub id: u8,
+    pub variables: Vec<String>,
+    pub variable_types: Vec<String>,
+    /// Output frequency (Hz)
+    pub frequency: f64,
+}
+
 /// Robot state data structure
 #[derive(Debug, Clone)]
 pub struct RobotState {
@@ -46,6 +80,22 @@ impl Default for RobotState {
     }
 }
 
+/// Default RTDE output frequency (Hz), supported by every controller generation
+pub const DEFAULT_RTDE_FREQUENCY: f64 = 125.0;
+
+/// Highest RTDE output frequency the controller for `model` supports
+/// 
+/// e-Series robots (UR3e/UR5e/...) and later (UR15/UR20/UR30) stream at up to
+/// 500 Hz. CB3 robots, and an unknown or missing model, get the 125 Hz every
+/// controller supports.
+pub fn max_rtde_frequency(model: Option<&str>) -> f64 {
+    match model.map(|m| m.trim().to_ascii_uppercase()).as_deref() {
+        Some(m) if m.ends_with('E') => 500.0,
+        Some("UR15" | "UR20" | "UR30") => 500.0,
+        _ => DEFAULT_RTDE_FREQUENCY,
+    }
+}
+
 /// RTDE Subscriber for continuous data streaming
 pub struct RTDESubscriber {
     /// Receiver for robot state updates
@@ -55,8 +105,13 @@ pub struct RTDESubscriber {
 }
 
 impl RTDESubscriber {
-    /// Create a new RTDE subscriber
+    /// Create a new RTDE subscriber at the default frequency
     pub async fn new(client: &mut RTDEClient) -> Result<Self> {
+        Self::new_with_frequency(client, DEFAULT_RTDE_FREQUENCY).await
+    }
+    
+    /// Create a new RTDE subscriber streaming at `frequency` Hz
+    pub async fn new_with_frequency(client: &mut RTDEClient, frequency: f64) -> Result<Self> {
         // Setup RTDE connection
         client.connect()?;
         client.negotiate_protocol_version(2)?;
@@ -71,7 +126,7 @@ impl RTDESubscriber {
             "runtime_state".to_string(),
         ];
         
-        match client.setup_output_recipe(enhanced_variables.clone(), 125.0) {
+        match client.setup_output_recipe(enhanced_variables.clone(), frequency) {
             Ok(_) => {
                 tracing::info!("Enhanced robot state monitoring enabled");
                 enhanced_variables
@@ -79,7 +134,7 @@ impl RTDESubscriber {
             Err(_) => {
                 tracing::warn!("Enhanced monitoring unavailable, using basic monitoring");
                 let basic_variables = vec!["timestamp".to_string(), "actual_q".to_string(), "actual_TCP_pose".to_string()];
-                client.setup_output_recipe(basic_variables.clone(), 125.0)?;
+                client.setup_output_recipe(basic_variables.clone(), frequency)?;
                 basic_variables
             }
         };
@@ -91,8 +146,7 @@ impl RTDESubscriber {
         // Move client to async task
         let stream = client.stream.take()
             .ok_or_else(|| URError::Connection("No stream available".to_string()))?;
-        let variables = client.variables.clone();
-        let variable_types = client.variable_types.clone();
+        let recipes = client.recipes.clone();
         
         // Spawn background task for continuous data reading
         let task_handle = tokio::spawn(async move {
@@ -101,79 +155,72 @@ impl RTDESubscriber {
                 port: 0,
                 stream: Some(stream),
                 protocol_version: Some(2),
-                variables,
-                variable_types,
+                recipes,
+                read_timeout: None,
             };
             
             let mut sequence = 0u64;
             
-            loop {
-                match client_task.read_data_package() {
-                    Ok(data) => {
-                        // Use robot's timestamp if available, fallback to system time
-                        let timestamp = data.get("timestamp")
-                            .and_then(|v| v.first())
-                            .copied()
-                            .unwrap_or_else(|| {
-                                let raw_timestamp = SystemTime::now()
-                                    .duration_since(UNIX_EPOCH)
-                                    .unwrap_or_default()
-                                    .as_secs_f64();
-                                // Round to 6 decimal places for consistent formatting
-                                (raw_timestamp * 1_000_000.0).round() / 1_000_000.0
-                            });
-                        
-                        let mut state = RobotState {
-                            joint_positions: [0.0; 6],
-                            tcp_pose: [0.0; 6],
-                            robot_mode: -1,
-                            safety_mode: -1,
-                            runtime_state: -1,
-                            timestamp,
-                            sequence,
-                        };
-                        
-                        // Extract joint positions
-                        if let Some(joint_data) = data.get("actual_q") {
-                            for (i, &val) in joint_data.iter().enumerate().take(6) {
-                                state.joint_positions[i] = val;
-                            }
-                        }
-                        
-                        // Extract TCP pose
-                        if let Some(tcp_data) = data.get("actual_TCP_pose") {
-                            for (i, &val) in tcp_data.iter().enumerate().take(6) {
-                                state.tcp_pose[i] = val;
-                            }
-                        }
-                        
-                        // Extract robot state values (if available)
-                        if let Some(robot_mode_data) = data.get("robot_mode") {
-                            state.robot_mode = robot_mode_data.get(0).copied().unwrap_or(-1.0) as i32;
-                        }
-                        
-                        if let Some(safety_mode_data) = data.get("safety_mode") {
-                            state.safety_mode = safety_mode_data.get(0).copied().unwrap_or(-1.0) as i32;
-                        }
-                        
-                        if let Some(runtime_state_data) = data.get("runtime_state") {
-                            state.runtime_state = runtime_state_data.get(0).copied().unwrap_or(-1.0) as i32;
-                        }
-                        
-                        sequence += 1;
-                        
-                        // Send state update (non-blocking)
-                        if state_sender.send(state).is_err() {
-                            // Receiver dropped, exit task
-                            break;
-                        }
+            while let Ok(data) = client_task.read_data_package() {
+                // Use robot's timestamp if available, fallback to system time
+                let timestamp = data.get("timestamp")
+                    .and_then(|v| v.first())
+                    .copied()
+                    .unwrap_or_else(|| {
+                        let raw_timestamp = SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_secs_f64();
+                        // Round to 6 decimal places for consistent formatting
+                        (raw_timestamp * 1_000_000.0).round() / 1_000_000.0
+                    });
+                
+                let mut state = RobotState {
+                    joint_positions: [0.0; 6],
+                    tcp_pose: [0.0; 6],
+                    robot_mode: -1,
+                    safety_mode: -1,
+                    runtime_state: -1,
+                    timestamp,
+                    sequence,
+                };
+                
+                // Extract joint positions
+                if let Some(joint_data) = data.get("actual_q") {
+                    for (i, &val) in joint_data.iter().enumerate().take(6) {
+                        state.joint_positions[i] = val;
                     }
-                    Err(_) => {
-                        // Connection error, exit task
-                        break;
+                }
+                
+                // Extract TCP pose
+                if let Some(tcp_data) = data.get("actual_TCP_pose") {
+                    for (i, &val) in tcp_data.iter().enumerate().take(6) {
+                        state.tcp_pose[i] = val;
                     }
                 }
+                
+                // Extract robot state values (if available)
+                if let Some(robot_mode_data) = data.get("robot_mode") {
+                    state.robot_mode = robot_mode_data.first().copied().unwrap_or(-1.0) as i32;
+                }
+                
+                if let Some(safety_mode_data) = data.get("safety_mode") {
+                    state.safety_mode = safety_mode_data.first().copied().unwrap_or(-1.0) as i32;
+                }
+                
+                if let Some(runtime_state_data) = data.get("runtime_state") {
+                    state.runtime_state = runtime_state_data.first().copied().unwrap_or(-1.0) as i32;
+                }
+                
+                sequence += 1;
+                
+                // Send state update (non-blocking)
+                if state_sender.send(state).is_err() {
+                    // Receiver dropped, exit task
+                    break;
+                }
             }
+            // Connection error, exit task
         });
         
         Ok(Self {
@@ -200,14 +247,24 @@ impl Drop for RTDESubscriber {
     }
 }
 
+/// Input data package setting the speed slider (mask bit 0 enables the fraction)
+fn speed_slider_payload(recipe_id: u8, fraction: f64) -> Vec<u8> {
+    let mut payload = vec![recipe_id];
+    payload.extend_from_slice(&1u32.to_be_bytes());
+    payload.extend_from_slice(&fraction.to_be_bytes());
+    payload
+}
+
 /// RTDE Client for communicating with Universal Robots
 pub struct RTDEClient {
     host: String,
     port: u16,
     stream: Option<TcpStream>,
     protocol_version: Option<u16>,
-    variables: Vec<String>,
-    variable_types: Vec<String>,
+    /// Output recipes in registration order, kept for `reconnect`
+    recipes: Vec<OutputRecipe>,
+    /// Read timeout, kept for `reconnect`
+    read_timeout: Option<Duration>,
 }
 
 impl RTDEClient {
@@ -218,8 +275,8 @@ impl RTDEClient {
             port,
             stream: None,
             protocol_version: None,
-            variables: Vec::new(),
-            variable_types: Vec::new(),
+            recipes: Vec::new(),
+            read_timeout: None,
         })
     }
 
@@ -232,6 +289,44 @@ impl RTDEClient {
         Ok(())
     }
 
+    /// Set how long a read may block before failing (None blocks indefinitely)
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        let stream = self.stream.as_ref()
+            .ok_or_else(|| URError::Connection("Not connected".to_string()))?;
+        
+        stream.set_read_timeout(timeout)
+            .map_err(|e| URError::Connection(format!("Failed to set read timeout: {}", e)))?;
+        
+        self.read_timeout = timeout;
+        Ok(())
+    }
+
+    /// Drop the socket and repeat the handshake with the previous settings
+    /// 
+    /// A read that fails part-way through a package leaves the stream out of
+    /// step with the message framing, so the connection is rebuilt rather than
+    /// read again. The protocol version, read timeout and output recipes
+    /// (variables and frequency) are restored and synchronization restarted.
+    /// Recipes are registered in their original order, so they normally get
+    /// the same IDs back; check `output_recipes` if the ID matters.
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.stream = None;
+        let protocol_version = self.protocol_version.take().unwrap_or(2);
+        
+        self.connect()?;
+        self.set_read_timeout(self.read_timeout)?;
+        self.negotiate_protocol_version(protocol_version)?;
+        
+        let recipes = std::mem::take(&mut self.recipes);
+        if !recipes.is_empty() {
+            for recipe in recipes {
+                self.setup_output_recipe(recipe.variables, recipe.frequency)?;
+            }
+            self.start_data_synchronization()?;
+        }
+        Ok(())
+    }
+
     /// Send an RTDE message
     fn send_message(&mut self, msg_type: RTDEMessage, payload: &[u8]) -> Result<()> {
         let stream = self.stream.as_mut()
@@ -264,19 +359,7 @@ impl RTDEClient {
             .map_err(|e| URError::Connection(format!("Failed to read header: {}", e)))?;
 
         let size = u16::from_be_bytes([header[0], header[1]]);
-        let msg_type_raw = header[2];
-
-        // Convert message type
-        let msg_type = match msg_type_raw {
-            86 => RTDEMessage::RequestProtocolVersion,
-            77 => RTDEMessage::TextMessage,
-            85 => RTDEMessage::DataPackage,
-            79 => RTDEMessage::ControlPackageSetupOutputs,
-            78 => RTDEMessage::ControlPackageSetupInputs,
-            83 => RTDEMessage::ControlPackageStart,
-            84 => RTDEMessage::ControlPackagePause,
-            _ => return Err(URError::Protocol(format!("Unknown message type: {}", msg_type_raw))),
-        };
+        let msg_type = RTDEMessage::try_from(header[2])?;
 
         // Read payload
         let payload_size = size as usize - 3;
@@ -307,7 +390,56 @@ impl RTDEClient {
     }
 
     /// Setup output recipe (configure what data to receive)
-    pub fn setup_output_recipe(&mut self, variables: Vec<String>, frequency: f64) -> Result<()> {
+    /// 
+    /// Each call registers another recipe and returns its ID, so variables can
+    /// be streamed at different frequencies (e.g. pose at 125 Hz, modes at 10 Hz).
+    /// A recipe the controller assigns an existing ID replaces that recipe.
+    pub fn setup_output_recipe(&mut self, variables: Vec<String>, frequency: f64) -> Result<u8> {
+        let (recipe_id, variable_types) = self.request_output_recipe(&variables, frequency)?;
+        
+        // The controller reports unknown variables as NOT_FOUND rather than failing the setup
+        if let Some(missing) = variables.iter()
+            .zip(&variable_types)
+            .find(|(_, var_type)| *var_type == "NOT_FOUND")
+            .map(|(name, _)| name)
+        {
+            return Err(URError::Protocol(format!("Output variable not available: {}", missing)));
+        }
+        
+        let recipe = OutputRecipe {
+            id: recipe_id,
+            variables,
+            variable_types,
+            frequency,
+        };
+        match self.recipes.iter_mut().find(|existing| existing.id == recipe_id) {
+            Some(existing) => *existing = recipe,
+            None => self.recipes.push(recipe),
+        }
+        
+        Ok(recipe_id)
+    }
+    
+    /// Setup an output recipe of whichever `variables` the controller provides
+    /// 
+    /// Optional data differs between firmware versions, so rather than failing
+    /// on one unknown variable the full list is probed once and the recipe set
+    /// up again without those reported NOT_FOUND. Returns the recipe ID and the
+    /// variables left out.
+    pub fn setup_available_output_recipe(&mut self, variables: Vec<String>, frequency: f64) -> Result<(u8, Vec<String>)> {
+        let (_, variable_types) = self.request_output_recipe(&variables, frequency)?;
+        let (found, missing): (Vec<_>, Vec<_>) = variables.into_iter()
+            .zip(&variable_types)
+            .partition(|(_, var_type)| *var_type != "NOT_FOUND");
+        let missing: Vec<String> = missing.into_iter().map(|(name, _)| name).collect();
+        
+        let found = found.into_iter().map(|(name, _)| name).collect();
+        let recipe_id = self.setup_output_recipe(found, frequency)?;
+        Ok((recipe_id, missing))
+    }
+    
+    /// Send an output recipe setup and return the assigned ID and variable types
+    fn request_output_recipe(&mut self, variables: &[String], frequency: f64) -> Result<(u8, Vec<String>)> {
         let mut payload = Vec::new();
         
         // Add frequency as double (8 bytes, big-endian)
@@ -323,17 +455,48 @@ impl RTDEClient {
         
         if let RTDEMessage::ControlPackageSetupOutputs = msg_type {
             if !response_payload.is_empty() {
-                let _recipe_id = response_payload[0];
+                let variable_types = String::from_utf8_lossy(&response_payload[1..])
+                    .split(',')
+                    .map(|s| s.to_string())
+                    .collect();
+                return Ok((response_payload[0], variable_types));
+            }
+        }
+
+        Err(URError::Protocol("Output recipe setup failed".to_string()))
+    }
+
+    /// Setup input recipe (configure which controller inputs this client writes)
+    /// 
+    /// Returns the recipe ID to prefix input data packages with.
+    pub fn setup_input_recipe(&mut self, variables: &[&str]) -> Result<u8> {
+        self.send_message(RTDEMessage::ControlPackageSetupInputs, variables.join(",").as_bytes())?;
+
+        let (msg_type, response_payload) = self.receive_message()?;
+        
+        if let RTDEMessage::ControlPackageSetupInputs = msg_type {
+            if !response_payload.is_empty() {
+                let recipe_id = response_payload[0];
                 let variable_types_str = String::from_utf8_lossy(&response_payload[1..]);
                 
-                self.variables = variables;
-                self.variable_types = variable_types_str.split(',').map(|s| s.to_string()).collect();
+                // Inputs already written by another client (e.g. a fieldbus) are reported as IN_USE
+                if let Some((name, var_type)) = variables.iter()
+                    .zip(variable_types_str.split(','))
+                    .find(|(_, var_type)| *var_type == "NOT_FOUND" || *var_type == "IN_USE")
+                {
+                    return Err(URError::Protocol(format!("Input variable {} is {}", name, var_type)));
+                }
                 
-                return Ok(());
+                return Ok(recipe_id);
             }
         }
 
-        Err(URError::Protocol("Output recipe setup failed".to_string()))
+        Err(URError::Protocol("Input recipe setup failed".to_string()))
+    }
+
+    /// Write the speed slider through an input recipe of `speed_slider_mask,speed_slider_fraction`
+    pub fn send_speed_slider(&mut self, recipe_id: u8, fraction: f64) -> Result<()> {
+        self.send_message(RTDEMessage::DataPackage, &speed_slider_payload(recipe_id, fraction))
     }
 
     /// Start data synchronization
@@ -353,29 +516,86 @@ impl RTDEClient {
 
     /// Read and parse a data package
     pub fn read_data_package(&mut self) -> Result<HashMap<String, Vec<f64>>> {
+        let (values, _bits) = self.read_data_package_with_bits()?;
+        Ok(values)
+    }
+
+    /// Read and parse a data package, also returning integer variables losslessly
+    /// 
+    /// Every variable is present in the first map as f64. `UINT64` values above
+    /// 2^53 lose precision there, so unsigned, boolean and bitmask types
+    /// (`UINT8`, `UINT32`, `UINT64`, `BOOL`, `VECTOR6INT32`, `VECTOR6UINT32`) are
+    /// also returned as raw bits in the second map.
+    pub fn read_data_package_with_bits(&mut self) -> Result<(DataValues, DataBits)> {
+        let (_recipe_id, values, bits) = self.read_recipe_package()?;
+        Ok((values, bits))
+    }
+
+    /// Read and parse a data package, returning the ID of the recipe it belongs to
+    /// 
+    /// With several output recipes their packages arrive interleaved, each at
+    /// its own frequency.
+    pub fn read_recipe_package(&mut self) -> Result<(u8, DataValues, DataBits)> {
         let (msg_type, payload) = self.receive_message()?;
         
         if let RTDEMessage::DataPackage = msg_type {
-            if payload.is_empty() {
-                return Err(URError::Protocol("Empty data package".to_string()));
-            }
-
-            let _recipe_id = payload[0];
-            let data = &payload[1..];
-            
-            return self.parse_data_package(data);
+            return self.parse_recipe_package(&payload);
         }
 
         Err(URError::Protocol("Expected data package".to_string()))
     }
 
-    /// Parse binary data according to variable types
-    fn parse_data_package(&self, data: &[u8]) -> Result<HashMap<String, Vec<f64>>> {
+    /// Output recipes registered on this connection
+    pub fn output_recipes(&self) -> &[OutputRecipe] {
+        &self.recipes
+    }
+
+    /// Hand the synchronized connection to tokio for non-blocking reads
+    /// 
+    /// Call after `start_data_synchronization`; the handshake stays on the
+    /// blocking client since it runs once per connection.
+    pub fn into_async(mut self) -> Result<AsyncRTDEClient> {
+        let stream = self.stream.take()
+            .ok_or_else(|| URError::Connection("Not connected".to_string()))?;
+        stream.set_nonblocking(true)
+            .map_err(|e| URError::Connection(format!("Failed to make socket non-blocking: {}", e)))?;
+        let stream = tokio::net::TcpStream::from_std(stream)
+            .map_err(|e| URError::Connection(format!("Failed to register socket with tokio: {}", e)))?;
+        
+        Ok(AsyncRTDEClient { client: self, stream: Some(stream) })
+    }
+
+    /// Copy of the connection settings and recipe, without the socket
+    fn detached(&self) -> Self {
+        Self {
+            host: self.host.clone(),
+            port: self.port,
+            stream: None,
+            protocol_version: self.protocol_version,
+            recipes: self.recipes.clone(),
+            read_timeout: self.read_timeout,
+        }
+    }
+
+    /// Split a data package payload into its recipe ID and parsed variables
+    fn parse_recipe_package(&self, payload: &[u8]) -> Result<(u8, DataValues, DataBits)> {
+        let (&recipe_id, data) = payload.split_first()
+            .ok_or_else(|| URError::Protocol("Empty data package".to_string()))?;
+        let (values, bits) = self.parse_data_package(recipe_id, data)?;
+        Ok((recipe_id, values, bits))
+    }
+
+    /// Parse binary data according to the variable types of recipe `recipe_id`
+    fn parse_data_package(&self, recipe_id: u8, data: &[u8]) -> Result<(DataValues, DataBits)> {
+        let recipe = self.recipes.iter()
+            .find(|recipe| recipe.id == recipe_id)
+            .ok_or_else(|| URError::Protocol(format!("Data package for unknown recipe {}", recipe_id)))?;
         let mut result = HashMap::new();
+        let mut bits = HashMap::new();
         let mut offset = 0;
 
-        for (i, var_type) in self.variable_types.iter().enumerate() {
-            let var_name = self.variables.get(i)
+        for (i, var_type) in recipe.variable_types.iter().enumerate() {
+            let var_name = recipe.variables.get(i)
                 .ok_or_else(|| URError::Protocol("Variable name missing".to_string()))?;
 
             match var_type.as_str() {
@@ -438,15 +658,149 @@ impl RTDEClient {
                     
                     // Convert to f64 for consistent interface
                     result.insert(var_name.clone(), vec![value as f64]);
+                    bits.insert(var_name.clone(), vec![value as u64]);
                     offset += 4;
                 }
+                "UINT64" => {
+                    if offset + 8 > data.len() {
+                        return Err(URError::Protocol("Insufficient data for UINT64".to_string()));
+                    }
+                    
+                    let bytes = &data[offset..offset + 8];
+                    let value = u64::from_be_bytes([
+                        bytes[0], bytes[1], bytes[2], bytes[3],
+                        bytes[4], bytes[5], bytes[6], bytes[7],
+                    ]);
+                    
+                    // f64 is exact up to 2^53, raw bits are kept for the full range
+                    result.insert(var_name.clone(), vec![value as f64]);
+                    bits.insert(var_name.clone(), vec![value]);
+                    offset += 8;
+                }
+                "UINT8" | "BOOL" => {
+                    if offset + 1 > data.len() {
+                        return Err(URError::Protocol(format!("Insufficient data for {}", var_type)));
+                    }
+                    
+                    let value = data[offset];
+                    
+                    result.insert(var_name.clone(), vec![value as f64]);
+                    bits.insert(var_name.clone(), vec![value as u64]);
+                    offset += 1;
+                }
+                "VECTOR6INT32" | "VECTOR6UINT32" => {
+                    if offset + 24 > data.len() {
+                        return Err(URError::Protocol(format!("Insufficient data for {}", var_type)));
+                    }
+                    
+                    let mut values = Vec::new();
+                    let mut raw = Vec::new();
+                    for j in 0..6 {
+                        let start = offset + j * 4;
+                        let bytes = [data[start], data[start + 1], data[start + 2], data[start + 3]];
+                        if var_type == "VECTOR6INT32" {
+                            let value = i32::from_be_bytes(bytes);
+                            values.push(value as f64);
+                            raw.push(value as u32 as u64);
+                        } else {
+                            let value = u32::from_be_bytes(bytes);
+                            values.push(value as f64);
+                            raw.push(value as u64);
+                        }
+                    }
+                    
+                    result.insert(var_name.clone(), values);
+                    bits.insert(var_name.clone(), raw);
+                    offset += 24;
+                }
                 _ => {
                     return Err(URError::Protocol(format!("Unsupported variable type: {}", var_type)));
                 }
             }
         }
 
-        Ok(result)
+        Ok((result, bits))
+    }
+}
+
+/// RTDE client that reads data packages without blocking the async runtime
+/// 
+/// Created from a synchronized `RTDEClient` with `into_async`. At 500 Hz a
+/// blocking read would hold a runtime worker for most of every package
+/// interval; here the task yields while waiting for data instead.
+pub struct AsyncRTDEClient {
+    client: RTDEClient,
+    stream: Option<tokio::net::TcpStream>,
+}
+
+impl AsyncRTDEClient {
+    /// Receive an RTDE message, failing after the client's read timeout
+    async fn receive_message(&mut self) -> Result<(RTDEMessage, Vec<u8>)> {
+        use tokio::io::AsyncReadExt;
+
+        let stream = self.stream.as_mut()
+            .ok_or_else(|| URError::Connection("Not connected".to_string()))?;
+
+        let read = async {
+            let mut header = [0u8; 3];
+            stream.read_exact(&mut header).await
+                .map_err(|e| URError::Connection(format!("Failed to read header: {}", e)))?;
+
+            let size = u16::from_be_bytes([header[0], header[1]]);
+            let msg_type = RTDEMessage::try_from(header[2])?;
+
+            let mut payload = vec![0u8; (size as usize).saturating_sub(3)];
+            stream.read_exact(&mut payload).await
+                .map_err(|e| URError::Connection(format!("Failed to read payload: {}", e)))?;
+
+            Ok((msg_type, payload))
+        };
+
+        match self.client.read_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, read).await
+                .map_err(|_| URError::Connection(format!("No RTDE data within {} ms", timeout.as_millis())))?,
+            None => read.await,
+        }
+    }
+
+    /// Read and parse a data package
+    pub async fn read_data_package(&mut self) -> Result<DataValues> {
+        let (values, _bits) = self.read_data_package_with_bits().await?;
+        Ok(values)
+    }
+
+    /// Read and parse a data package, also returning integer variables losslessly
+    /// 
+    /// See `RTDEClient::read_data_package_with_bits`.
+    pub async fn read_data_package_with_bits(&mut self) -> Result<(DataValues, DataBits)> {
+        let (_recipe_id, values, bits) = self.read_recipe_package().await?;
+        Ok((values, bits))
+    }
+
+    /// Read and parse a data package with its recipe ID (see `RTDEClient::read_recipe_package`)
+    pub async fn read_recipe_package(&mut self) -> Result<(u8, DataValues, DataBits)> {
+        let (msg_type, payload) = self.receive_message().await?;
+
+        if let RTDEMessage::DataPackage = msg_type {
+            return self.client.parse_recipe_package(&payload);
+        }
+
+        Err(URError::Protocol("Expected data package".to_string()))
+    }
+
+    /// Rebuild the connection with the same recipe (see `RTDEClient::reconnect`)
+    /// 
+    /// The handshake runs on a blocking thread. On failure the client stays
+    /// disconnected and reads fail until a later reconnect succeeds.
+    pub async fn reconnect(&mut self) -> Result<()> {
+        self.stream = None;
+        let mut client = self.client.detached();
+
+        let client = tokio::task::spawn_blocking(move || client.reconnect().map(|_| client)).await
+            .map_err(|e| URError::Connection(format!("RTDE reconnect task failed: {}", e)))??;
+
+        *self = client.into_async()?;
+        Ok(())
     }
 }
 
@@ -454,4 +808,184 @@ impl Drop for RTDEClient {
     fn drop(&mut self) {
         // Connection will be automatically closed when TcpStream is dropped
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn client_with_recipe(variables: &[&str], types: &[&str]) -> RTDEClient {
+        let mut client = RTDEClient::new("localhost", 30004).unwrap();
+        client.recipes.push(OutputRecipe {
+            id: 1,
+            variables: variables.iter().map(|s| s.to_string()).collect(),
+            variable_types: types.iter().map(|s| s.to_string()).collect(),
+            frequency: DEFAULT_RTDE_FREQUENCY,
+        });
+        client
+    }
+    
+    #[test]
+    fn test_max_rtde_frequency_by_model() {
+        assert_eq!(max_rtde_frequency(Some("UR5")), 125.0);
+        assert_eq!(max_rtde_frequency(Some("ur10")), 125.0);
+        assert_eq!(max_rtde_frequency(Some("UR10e")), 500.0);
+        assert_eq!(max_rtde_frequency(Some("UR20")), 500.0);
+        assert_eq!(max_rtde_frequency(Some("ur5e")), 500.0);
+        assert_eq!(max_rtde_frequency(Some("unknown")), 125.0);
+        assert_eq!(max_rtde_frequency(None), 125.0);
+    }
+    
+    #[test]
+    fn test_parse_io_types() {
+        let client = client_with_recipe(
+            &["actual_digital_input_bits", "robot_status_bits", "is_running", "output_int_register_0"],
+            &["UINT64", "UINT32", "BOOL", "VECTOR6INT32"],
+        );
+        
+        let mut data = Vec::new();
+        data.extend_from_slice(&((1u64 << 60) | 0b101).to_be_bytes());
+        data.extend_from_slice(&0b11u32.to_be_bytes());
+        data.push(1);
+        for value in [1i32, -1, 0, 0, 0, 7] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        
+        let (values, bits) = client.parse_data_package(1, &data).unwrap();
+        
+        assert_eq!(bits["actual_digital_input_bits"], vec![(1u64 << 60) | 0b101]);
+        assert_eq!(values["robot_status_bits"], vec![3.0]);
+        assert_eq!(bits["robot_status_bits"], vec![3]);
+        assert_eq!(values["is_running"], vec![1.0]);
+        assert_eq!(values["output_int_register_0"], vec![1.0, -1.0, 0.0, 0.0, 0.0, 7.0]);
+        assert_eq!(bits["output_int_register_0"][1], u32::MAX as u64);
+    }
+    
+    /// Answer every control request with success until the client hangs up
+    fn fake_controller(listener: std::net::TcpListener, types: &'static str) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut header = [0u8; 3];
+                while stream.read_exact(&mut header).is_ok() {
+                    let mut payload = vec![0u8; u16::from_be_bytes([header[0], header[1]]) as usize - 3];
+                    stream.read_exact(&mut payload).unwrap();
+                    requests.push(header[2]);
+                    
+                    let reply = match header[2] {
+                        79 => [&[1u8][..], types.as_bytes()].concat(),
+                        _ => vec![1],
+                    };
+                    let size = (reply.len() + 3) as u16;
+                    stream.write_all(&[(size >> 8) as u8, size as u8, header[2]]).unwrap();
+                    stream.write_all(&reply).unwrap();
+                    if header[2] == RTDEMessage::ControlPackageStart as u8 {
+                        break;
+                    }
+                }
+            }
+            requests
+        })
+    }
+    
+    #[test]
+    fn test_reconnect_restores_recipe() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = fake_controller(listener, "DOUBLE,VECTOR6D");
+        
+        let mut client = RTDEClient::new("127.0.0.1", port).unwrap();
+        client.connect().unwrap();
+        client.negotiate_protocol_version(2).unwrap();
+        client.setup_output_recipe(vec!["timestamp".to_string(), "actual_q".to_string()], 250.0).unwrap();
+        client.start_data_synchronization().unwrap();
+        
+        client.reconnect().unwrap();
+        drop(client);
+        
+        // Version, outputs, start - twice
+        assert_eq!(server.join().unwrap(), vec![86, 79, 83, 86, 79, 83]);
+    }
+    
+    #[tokio::test]
+    async fn test_async_client_reads_after_handshake() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // Version, outputs, start
+            for (msg_type, reply) in [(86u8, &b"\x01"[..]), (79, &b"\x01DOUBLE"[..]), (83, &b"\x01"[..])] {
+                // Read the whole request - header and payload can arrive separately
+                let mut header = [0u8; 3];
+                stream.read_exact(&mut header).unwrap();
+                let mut payload = vec![0u8; u16::from_be_bytes([header[0], header[1]]) as usize - 3];
+                stream.read_exact(&mut payload).unwrap();
+                let size = (reply.len() + 3) as u16;
+                stream.write_all(&[(size >> 8) as u8, size as u8, msg_type]).unwrap();
+                stream.write_all(reply).unwrap();
+            }
+            let mut package = vec![0, 12, 85, 1];
+            package.extend_from_slice(&12.5f64.to_be_bytes());
+            stream.write_all(&package).unwrap();
+        });
+        
+        let mut client = RTDEClient::new("127.0.0.1", port).unwrap();
+        client.connect().unwrap();
+        client.negotiate_protocol_version(2).unwrap();
+        client.setup_output_recipe(vec!["timestamp".to_string()], 125.0).unwrap();
+        client.start_data_synchronization().unwrap();
+        client.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+        
+        let mut client = client.into_async().unwrap();
+        let data = client.read_data_package().await.unwrap();
+        assert_eq!(data["timestamp"], vec![12.5]);
+        server.join().unwrap();
+        
+        // The server hung up, so the next read fails rather than blocking
+        assert!(client.read_data_package().await.is_err());
+    }
+    
+    #[test]
+    fn test_speed_slider_payload() {
+        let payload = speed_slider_payload(2, 0.25);
+        assert_eq!(payload.len(), 13);
+        assert_eq!(payload[0], 2);
+        assert_eq!(payload[1..5], [0, 0, 0, 1]);
+        assert_eq!(f64::from_be_bytes(payload[5..].try_into().unwrap()), 0.25);
+    }
+    
+    #[test]
+    fn test_parse_truncated_package() {
+        let client = client_with_recipe(&["actual_digital_input_bits"], &["UINT64"]);
+        assert!(client.parse_data_package(1, &[0, 0, 0, 1]).is_err());
+    }
+    
+    #[test]
+    fn test_packages_dispatched_by_recipe_id() {
+        let mut client = client_with_recipe(&["actual_TCP_pose"], &["VECTOR6D"]);
+        client.recipes.push(OutputRecipe {
+            id: 2,
+            variables: vec!["robot_mode".to_string(), "safety_mode".to_string()],
+            variable_types: vec!["INT32".to_string(), "INT32".to_string()],
+            frequency: 10.0,
+        });
+        
+        let mut pose = vec![1];
+        for value in [0.1, -0.4, 0.3, 0.0, 3.1, 0.0f64] {
+            pose.extend_from_slice(&value.to_be_bytes());
+        }
+        let (recipe_id, values, _) = client.parse_recipe_package(&pose).unwrap();
+        assert_eq!(recipe_id, 1);
+        assert_eq!(values["actual_TCP_pose"], vec![0.1, -0.4, 0.3, 0.0, 3.1, 0.0]);
+        
+        let modes = [&[2u8][..], &7i32.to_be_bytes(), &1i32.to_be_bytes()].concat();
+        let (recipe_id, values, _) = client.parse_recipe_package(&modes).unwrap();
+        assert_eq!(recipe_id, 2);
+        assert_eq!(values["robot_mode"], vec![7.0]);
+        assert!(!values.contains_key("actual_TCP_pose"));
+        
+        assert!(client.parse_recipe_package(&[3, 0]).unwrap_err().to_string().contains("unknown recipe 3"));
+        assert!(client.parse_recipe_package(&[]).is_err());
+    }
+}
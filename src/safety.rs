@@ -0,0 +1,350 @@
+//! Motion Safety Limits
+//!
+//! Checks the acceleration and velocity of `movej`/`movel`/`movep`/`speedj`/
+//! `speedl` calls against configured ceilings before a command reaches the
+//! interpreter. Literal values are inspected whether given as keywords
+//! (`v=0.5`) or positionally (`movej(q, 1.4, 0.5)`); expressions
+//! (`v=my_speed`) are passed through unchanged.
+//!
+//! Motion targets can also be checked against a workspace box. Only a target
+//! given as a single literal pose (`movel(p[...])`, including
+//! `movej(get_inverse_kin(p[...]))`) can be verified; with a workspace
+//! configured, any other motion (joint targets, variables, computed poses,
+//! speed and servo commands) is refused. Only the target is checked, not the
+//! path the arm takes to reach it: a `movej` between two in-bounds poses can
+//! still swing outside the box.
+
+use crate::config::{SafetyConfig, WorkspaceConfig};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Result of checking a command against the configured motion limits
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitCheck {
+    /// No motion call exceeded a limit
+    WithinLimits,
+    /// Limits were exceeded and the command was rewritten with clamped values
+    Clamped { command: String, adjustments: Vec<String> },
+    /// Limits were exceeded in strict mode
+    Rejected(String),
+}
+
+fn motion_call_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b(movej|movel|movep|speedj|speedl)\s*\(").unwrap())
+}
+
+fn any_motion_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b(movej|movel|movep|movec|speedj|speedl|servoj|servoc)\s*\(").unwrap())
+}
+
+/// Whether a URScript line commands robot motion
+pub fn is_motion_command(command: &str) -> bool {
+    any_motion_regex().is_match(command)
+}
+
+fn joint_motion_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b(movej|speedj|servoj)\s*\(").unwrap())
+}
+
+/// Whether a URScript line commands joint-space motion
+/// 
+/// Force mode is defined in a Cartesian task frame, so these are refused while it is active.
+pub fn is_joint_motion_command(command: &str) -> bool {
+    joint_motion_regex().is_match(command)
+}
+
+fn pose_literal_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\s*p\s*\[([^\[\]]*)\]\s*$").unwrap())
+}
+
+fn inverse_kin_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\s*get_inverse_kin\s*\((.*)\)\s*$").unwrap())
+}
+
+/// The pose a motion argument moves to, if it is a literal `p[...]`
+/// 
+/// `get_inverse_kin(p[...], ...)` counts as its pose argument.
+fn literal_pose(arg: &str) -> Option<[f64; 3]> {
+    let arg = match inverse_kin_regex().captures(arg) {
+        Some(inner) => {
+            let inner = inner.get(1).unwrap().as_str();
+            let (start, end) = split_args(inner)[0];
+            &inner[start..end]
+        }
+        None => arg,
+    };
+    let pose = pose_literal_regex().captures(arg)?;
+    let values = pose[1].split(',').map(|v| v.trim().parse::<f64>()).collect::<Result<Vec<_>, _>>().ok()?;
+    (values.len() == 6).then(|| [values[0], values[1], values[2]])
+}
+
+fn keyword_arg_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\s*([av])\s*=\s*([0-9]*\.?[0-9]+(?:[eE][+-]?[0-9]+)?)\s*$").unwrap())
+}
+
+fn positional_arg_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\s*([0-9]*\.?[0-9]+(?:[eE][+-]?[0-9]+)?)\s*$").unwrap())
+}
+
+/// Name of the parameter at position `index` of `function`, if it is `a` or `v`
+/// 
+/// Follows the URScript signatures: `move*(target, a, v, ...)` and `speed*(speed, a, t, ...)`.
+fn positional_param(function: &str, index: usize) -> Option<&'static str> {
+    match (function, index) {
+        (_, 1) => Some("a"),
+        ("movej" | "movel" | "movep", 2) => Some("v"),
+        _ => None,
+    }
+}
+
+/// Byte ranges of the top-level comma-separated arguments in `args`
+fn split_args(args: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (offset, c) in args.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                ranges.push((start, offset));
+                start = offset + 1;
+            }
+            _ => {}
+        }
+    }
+    ranges.push((start, args.len()));
+    ranges
+}
+
+/// Find the byte offset of the parenthesis closing the one just before `start`
+fn matching_paren(command: &str, start: usize) -> usize {
+    let mut depth = 1;
+    for (offset, c) in command[start..].char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return start + offset;
+                }
+            }
+            _ => {}
+        }
+    }
+    command.len()
+}
+
+/// Find a motion call whose target lies outside `workspace` or can't be verified
+/// 
+/// Returns a description of the first offending target.
+pub fn check_workspace(command: &str, workspace: &WorkspaceConfig) -> Option<String> {
+    for call in any_motion_regex().captures_iter(command) {
+        let function = &call[1];
+        let args_start = call.get(0).unwrap().end();
+        let args = &command[args_start..matching_paren(command, args_start)];
+
+        // movec passes through a via pose on its way to the target
+        let targets = match function {
+            "movej" | "movel" | "movep" => 1,
+            "movec" => 2,
+            _ => return Some(format!("{} has no target pose to check against the workspace", function)),
+        };
+        for (start, end) in split_args(args).into_iter().take(targets) {
+            let target = args[start..end].trim();
+            let Some(position) = literal_pose(target) else {
+                return Some(format!("{} target '{}' is not a literal pose p[...] - can't check it against the workspace", function, target));
+            };
+
+            for (axis, value, bounds) in [("x", position[0], workspace.x), ("y", position[1], workspace.y), ("z", position[2], workspace.z)] {
+                if let Some([min, max]) = bounds.filter(|[min, max]| value < *min || value > *max) {
+                    return Some(format!("{} target {}={} outside workspace {} [{}, {}]", function, axis, value, axis, min, max));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Check a URScript line against the motion limits in `config`
+/// 
+/// Workspace violations are rejected in either mode, since a target can't be clamped safely.
+pub fn check_motion_limits(command: &str, config: &SafetyConfig) -> LimitCheck {
+    if let Some(violation) = config.workspace.as_ref().and_then(|workspace| check_workspace(command, workspace)) {
+        return LimitCheck::Rejected(violation);
+    }
+
+    let mut output = String::with_capacity(command.len());
+    let mut adjustments = Vec::new();
+    let mut copied_to = 0;
+
+    for call in motion_call_regex().captures_iter(command) {
+        let function = &call[1];
+        let args_start = call.get(0).unwrap().end();
+        if args_start < copied_to {
+            // Nested inside a call already processed
+            continue;
+        }
+        let args_end = matching_paren(command, args_start);
+        let joint_space = function.ends_with('j');
+
+        output.push_str(&command[copied_to..args_start]);
+        let mut arg_copied_to = args_start;
+
+        for (index, (start, end)) in split_args(&command[args_start..args_end]).into_iter().enumerate() {
+            let arg_start = args_start + start;
+            let arg = &command[arg_start..args_start + end];
+            let (name, value) = if let Some(keyword) = keyword_arg_regex().captures(arg) {
+                (keyword.get(1).unwrap().as_str(), keyword.get(2).unwrap())
+            } else if let (Some(name), Some(positional)) = (positional_param(function, index), positional_arg_regex().captures(arg)) {
+                (name, positional.get(1).unwrap())
+            } else {
+                continue;
+            };
+            let Ok(requested) = value.as_str().parse::<f64>() else { continue };
+
+            let (limit, limit_name) = match (name, joint_space) {
+                ("v", true) => (config.max_joint_vel, "max_joint_vel"),
+                ("a", true) => (config.max_joint_acc, "max_joint_acc"),
+                ("v", false) => (config.max_tcp_vel, "max_tcp_vel"),
+                _ => (config.max_tcp_acc, "max_tcp_acc"),
+            };
+            let Some(limit) = limit.filter(|&limit| requested > limit) else { continue };
+
+            let message = format!("{} {}={} exceeds {} {}", function, name, requested, limit_name, limit);
+            if config.strict() {
+                return LimitCheck::Rejected(message);
+            }
+
+            let value_start = arg_start + value.start();
+            output.push_str(&command[arg_copied_to..value_start]);
+            output.push_str(&limit.to_string());
+            arg_copied_to = arg_start + value.end();
+            adjustments.push(message);
+        }
+
+        output.push_str(&command[arg_copied_to..args_end]);
+        copied_to = args_end;
+    }
+
+    if adjustments.is_empty() {
+        return LimitCheck::WithinLimits;
+    }
+
+    output.push_str(&command[copied_to..]);
+    LimitCheck::Clamped { command: output, adjustments }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(mode: &str) -> SafetyConfig {
+        SafetyConfig {
+            max_joint_vel: Some(1.0),
+            max_joint_acc: Some(2.0),
+            max_tcp_vel: Some(0.25),
+            max_tcp_acc: Some(1.2),
+            mode: Some(mode.to_string()),
+            workspace: None,
+        }
+    }
+
+    #[test]
+    fn test_clamps_joint_and_tcp_limits() {
+        let check = check_motion_limits("speedj([0.2,0,0,0,0,0], a=100, t=0.5)", &limits("clamp"));
+        let LimitCheck::Clamped { command, adjustments } = check else { panic!("expected clamp: {:?}", check) };
+        assert_eq!(command, "speedj([0.2,0,0,0,0,0], a=2, t=0.5)");
+        assert_eq!(adjustments, vec!["speedj a=100 exceeds max_joint_acc 2"]);
+
+        let check = check_motion_limits("movel(p[0.1,0.2,0.3,0,3.1,0], a=0.5, v=1.5)", &limits("clamp"));
+        let LimitCheck::Clamped { command, .. } = check else { panic!("expected clamp: {:?}", check) };
+        assert_eq!(command, "movel(p[0.1,0.2,0.3,0,3.1,0], a=0.5, v=0.25)");
+    }
+
+    #[test]
+    fn test_clamps_positional_arguments() {
+        let check = check_motion_limits("movej([0,-1.57,0,0,0,0], 1.4, 3.0)", &limits("clamp"));
+        let LimitCheck::Clamped { command, adjustments } = check else { panic!("expected clamp: {:?}", check) };
+        assert_eq!(command, "movej([0,-1.57,0,0,0,0], 1.4, 1)");
+        assert_eq!(adjustments, vec!["movej v=3 exceeds max_joint_vel 1"]);
+
+        let check = check_motion_limits("movep(p[0.1,0.2,0.3,0,3.1,0], 5, 0.5, r=0.01)", &limits("clamp"));
+        let LimitCheck::Clamped { command, adjustments } = check else { panic!("expected clamp: {:?}", check) };
+        assert_eq!(command, "movep(p[0.1,0.2,0.3,0,3.1,0], 1.2, 0.25, r=0.01)");
+        assert_eq!(adjustments.len(), 2);
+
+        // speedj's third argument is the time, not a velocity
+        let check = check_motion_limits("speedj([0.2,0,0,0,0,0], 50, 10.0)", &limits("clamp"));
+        let LimitCheck::Clamped { command, .. } = check else { panic!("expected clamp: {:?}", check) };
+        assert_eq!(command, "speedj([0.2,0,0,0,0,0], 2, 10.0)");
+        assert_eq!(check_motion_limits("movel(target, acc, 3.0)", &limits("strict")), LimitCheck::Rejected("movel v=3 exceeds max_tcp_vel 0.25".to_string()));
+    }
+
+    #[test]
+    fn test_strict_mode_rejects() {
+        let check = check_motion_limits("movej([0,-1.57,0,0,0,0], v=3.0)", &limits("strict"));
+        assert_eq!(check, LimitCheck::Rejected("movej v=3 exceeds max_joint_vel 1".to_string()));
+    }
+
+    #[test]
+    fn test_workspace_rejects_targets_outside_box() {
+        let mut config = limits("clamp");
+        config.workspace = Some(WorkspaceConfig { x: Some([-0.5, 0.5]), y: None, z: Some([0.05, 0.8]) });
+
+        assert_eq!(check_motion_limits("movel(p[0.4, 0.9, 0.3, 0, 3.14, 0], a=0.5, v=0.1)", &config), LimitCheck::WithinLimits);
+        assert_eq!(
+            check_motion_limits("movel(p[0.51, 0.2, 0.3, 0, 3.14, 0], a=0.5, v=0.1)", &config),
+            LimitCheck::Rejected("movel target x=0.51 outside workspace x [-0.5, 0.5]".to_string())
+        );
+        assert_eq!(
+            check_motion_limits("movej(get_inverse_kin(p[0.1,0.2,0.01,0,3.14,0]))", &config),
+            LimitCheck::Rejected("movej target z=0.01 outside workspace z [0.05, 0.8]".to_string())
+        );
+        // Poses outside motion calls aren't targets
+        assert_eq!(check_motion_limits("home = p[2.0,0,0,0,0,0]", &config), LimitCheck::WithinLimits);
+        
+        // Targets that aren't a single literal pose can't be verified, so they are refused
+        for command in [
+            "movej([2.0,0,0,0,0,0])",
+            "movel(target)",
+            "movel(pose_trans(p[0.1,0.2,0.3,0,3.14,0], p[0,0,0.5,0,0,0]))",
+            "speedl([0,0,0.1,0,0,0], 0.5, 2)",
+        ] {
+            let LimitCheck::Rejected(reason) = check_motion_limits(command, &config) else { panic!("expected rejection: {}", command) };
+            assert!(reason.contains("workspace"), "{}", reason);
+        }
+        assert_eq!(check_motion_limits("movec(p[0.1,0.2,0.3,0,3.14,0], p[0.2,0.2,0.3,0,3.14,0], a=0.5)", &config), LimitCheck::WithinLimits);
+        assert!(matches!(
+            check_motion_limits("movec(p[0.1,0.2,0.3,0,3.14,0], p[0.2,0.2,0.9,0,3.14,0])", &config),
+            LimitCheck::Rejected(_)
+        ));
+    }
+
+    #[test]
+    fn test_is_motion_command() {
+        assert!(is_motion_command("movej([0,0,0,0,0,0])"));
+        assert!(is_motion_command("servoj(q, t=0.008)"));
+        assert!(!is_motion_command("freedrive_mode()"));
+        assert!(!is_motion_command("stopj(2.0)"));
+        assert!(is_joint_motion_command("movej([0,0,0,0,0,0])"));
+        assert!(!is_joint_motion_command("movel(get_inverse_kin(p[0.1,0.2,0.3,0,3.1,0]))"));
+    }
+
+    #[test]
+    fn test_leaves_safe_and_non_motion_commands_alone() {
+        let config = limits("strict");
+        assert_eq!(check_motion_limits("movej([0,0,0,0,0,0], a=1.0, v=0.5)", &config), LimitCheck::WithinLimits);
+        assert_eq!(check_motion_limits("set_digital_out(0, True)", &config), LimitCheck::WithinLimits);
+        assert_eq!(check_motion_limits("movel(target, a=acc, v=vel)", &config), LimitCheck::WithinLimits);
+        // a=/v= outside a motion call are not motion parameters
+        assert_eq!(check_motion_limits("v = 10", &config), LimitCheck::WithinLimits);
+    }
+}
@@ -3,151 +3,497 @@
 //! Handles stdin command processing, execution sequencing, and completion tracking.
 //! Based on the sendInterpreterFromFile.py pattern from the official examples.
 
-use crate::{controller::RobotController, json_output};
+use crate::{audit::{AuditEntry, AuditLog}, config::LintMode, controller::{ProgramState, RobotController}, json_output::{self, FailureReason}, metrics::CommandMetrics};
+use crate::interpreter::InterpreterReply;
+use crate::motion::{self, ForceMode, JointConfiguration, MoveParams};
+use crate::safety::{check_motion_limits, is_joint_motion_command, is_motion_command, LimitCheck};
+use crate::urscript_lint::Linter;
 use anyhow::{Context, Result};
-use tokio::io::{self, AsyncBufReadExt, BufReader};
+use tokio::io::{self, AsyncBufRead, AsyncBufReadExt, AsyncReadExt, BufReader};
 use tokio::time::{sleep, Duration};
 use tokio::signal;
-use tracing::{info, error};
+use tracing::{info, error, Instrument};
 use std::sync::{Arc, atomic::Ordering};
 
-/// Buffer clear limit - commands after which we clear the interpreter buffer
-/// This prevents "runtime too much behind" errors in interpreter mode
+/// Default buffer clear limit - commands after which we clear the interpreter buffer
+/// This prevents "runtime too much behind" errors in interpreter mode.
+/// Overridden by `interpreter.clear_buffer_limit` in the daemon config.
 const CLEAR_BUFFER_LIMIT: u32 = 500;
 
-/// Convert rotation vector (axis-angle) to forward direction vector
-fn rotvec_to_direction_vector(rx: f64, ry: f64, rz: f64) -> [f64; 3] {
-    // Rotation vector magnitude is the rotation angle
-    let angle = (rx * rx + ry * ry + rz * rz).sqrt();
+/// How often a `progress` event is emitted while a command is still executing
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Joint speed (rad/s) below which the arm counts as stopped
+const STOPPED_JOINT_SPEED: f64 = 0.01;
+
+/// How long `@pause` waits for the arm to come to rest
+const PAUSE_STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Upper bound for `@group end repeat=N` and `max=N`
+const MAX_GROUP_REPEAT: u32 = 1000;
+
+/// Iteration cap for `@group end until=<condition>` without `max=N`
+const DEFAULT_UNTIL_ITERATIONS: u32 = 100;
+
+/// How long a register readback script (`@ik`, `@var get`, `until=`) gets to execute
+const READBACK_EXECUTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sentinel commands understood by the command stream
+const SENTINEL_COMMANDS: &[&str] = &["@reconnect", "@status", "@info", "@health", "@clear", "@pose", "@session", "@pause", "@resume", "@load", "@program", "@group", "@freedrive", "@force", "@io", "@var", "@speed", "@movej", "@movel", "@ik", "@power", "@recover", "@reload", "@monitoring", "@history", "@errors", "@help"];
+
+/// Split an @ sentinel line into its command name and arguments
+/// 
+/// Returns None when nothing follows the `@`.
+fn parse_sentinel(command: &str) -> Option<(&str, Vec<&str>)> {
+    let mut parts = command.strip_prefix('@').unwrap_or(command).split_whitespace();
+    let cmd = parts.next()?;
+    Some((cmd, parts.collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
     
-    if angle < 1e-8 {
-        // No rotation, return default forward direction (+Z)
-        return [0.0, 0.0, 1.0];
+    #[test]
+    fn test_parse_program_action() {
+        assert_eq!(ProgramAction::parse(&["load", "pick_place"]), Some(ProgramAction::Load("pick_place".to_string())));
+        assert_eq!(ProgramAction::parse(&["play"]), Some(ProgramAction::Play));
+        assert_eq!(ProgramAction::parse(&["stop"]), Some(ProgramAction::Stop));
+        assert_eq!(ProgramAction::parse(&["state"]), Some(ProgramAction::State));
+        assert_eq!(ProgramAction::parse(&["load"]), None);
+        assert_eq!(ProgramAction::parse(&["rewind"]), None);
     }
     
-    // Normalize rotation axis
-    let kx = rx / angle;
-    let ky = ry / angle;
-    let kz = rz / angle;
+    #[test]
+    fn test_parse_sentinel() {
+        assert_eq!(parse_sentinel("@status"), Some(("status", vec![])));
+        assert_eq!(parse_sentinel("@clear now"), Some(("clear", vec!["now"])));
+        
+        // A bare @ carries no command name
+        assert_eq!(parse_sentinel("@"), None);
+        assert_eq!(parse_sentinel("@   "), None);
+    }
     
-    // Forward direction in TCP frame is +Z
-    let v = [0.0, 0.0, 1.0];
+    fn test_stream() -> CommandStream {
+        let controller = RobotController::new_with_config("config/default_config.yaml")
+            .expect("default config should load");
+        CommandStream::new(controller)
+    }
     
-    // Rodrigues' rotation formula: v_rot = v*cos(θ) + (k×v)*sin(θ) + k*(k·v)*(1-cos(θ))
-    let cos_angle = angle.cos();
-    let sin_angle = angle.sin();
-    let one_minus_cos = 1.0 - cos_angle;
+    #[test]
+    fn test_session_disables_auto_clear() {
+        let mut stream = test_stream();
+        stream.command_count = stream.clear_buffer_limit;
+        assert!(stream.should_auto_clear());
+        
+        stream.session_active = true;
+        assert!(!stream.should_auto_clear());
+        
+        stream.session_active = false;
+        stream.inside_brace_block = true;
+        assert!(!stream.should_auto_clear());
+    }
     
-    // k·v (dot product)
-    let k_dot_v = kx * v[0] + ky * v[1] + kz * v[2]; // = kz since v = [0,0,1]
+    #[tokio::test]
+    async fn test_buffer_stats_require_interpreter() {
+        let mut stream = test_stream();
+        let error = stream.get_buffer_stats().await.unwrap_err();
+        assert_eq!(crate::error_code_for(&error), "ROBOT_NOT_READY");
+        
+        let stats = BufferStats { last_interpreted_id: 120, last_executed_id: 100, pending_depth: 20, urscript_count: 42 };
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["pending_depth"], 20);
+    }
     
-    // k×v (cross product)  
-    let cross_x = ky * v[2] - kz * v[1]; // ky*1 - kz*0 = ky
-    let cross_y = kz * v[0] - kx * v[2]; // kz*0 - kx*1 = -kx  
-    let cross_z = kx * v[1] - ky * v[0]; // kx*0 - ky*0 = 0
+    #[tokio::test]
+    async fn test_group_collects_lines_into_one_command() {
+        let mut stream = test_stream();
+        
+        stream.handle_sentinel_command("@group begin").await.unwrap();
+        assert_eq!(stream.group_buffer, Some(Vec::new()));
+        
+        stream.group_buffer.as_mut().unwrap().extend([
+            "def wave():".to_string(),
+            "textmsg(\"hi\")".to_string(),
+            "end".to_string(),
+        ]);
+        let info = stream.handle_sentinel_command("@group end").await.unwrap();
+        assert!(matches!(info.status, CommandStatus::Completed));
+        assert_eq!(stream.group_buffer, None);
+        assert_eq!(stream.pending_script.as_deref(), Some("def wave(): textmsg(\"hi\") end"));
+        
+        // repeat=N runs the group N times; a bad count keeps the group open
+        stream.pending_script = None;
+        stream.handle_sentinel_command("@group begin").await.unwrap();
+        stream.group_buffer.as_mut().unwrap().push("textmsg(\"again\")".to_string());
+        let info = stream.handle_sentinel_command("@group end repeat=0").await.unwrap();
+        assert!(matches!(info.status, CommandStatus::Failed(FailureReason::InvalidRequest { .. })));
+        assert_eq!(stream.group_buffer.as_ref().map(Vec::len), Some(1));
+        stream.handle_sentinel_command("@group end repeat=3").await.unwrap();
+        assert_eq!(stream.pending_script.as_deref(), Some("textmsg(\"again\")"));
+        assert_eq!(stream.pending_repeat, 3);
+        
+        // until=<condition> reruns until the condition holds, capped by max=N
+        stream.pending_script = None;
+        stream.handle_sentinel_command("@group begin").await.unwrap();
+        stream.group_buffer.as_mut().unwrap().push("x = x + 1".to_string());
+        stream.handle_sentinel_command("@group end until=x >= 3 max=5").await.unwrap();
+        assert_eq!((stream.pending_repeat, stream.pending_until.as_deref()), (5, Some("x >= 3")));
+        assert_eq!(CommandStream::group_repeat(&["until=done", "==", "1"]), Some((DEFAULT_UNTIL_ITERATIONS, Some("done == 1".to_string()))));
+        assert_eq!(CommandStream::group_repeat(&["until=", "max=5"]), None);
+        assert_eq!(CommandStream::group_repeat(&["until=done", "max=0"]), None);
+        
+        // Ending without an open group is an error
+        let info = stream.handle_sentinel_command("@group end").await.unwrap();
+        assert!(matches!(info.status, CommandStatus::Failed(FailureReason::InvalidRequest { .. })));
+    }
     
-    // Apply Rodrigues' formula
-    let result_x = v[0] * cos_angle + cross_x * sin_angle + kx * k_dot_v * one_minus_cos;
-    let result_y = v[1] * cos_angle + cross_y * sin_angle + ky * k_dot_v * one_minus_cos;
-    let result_z = v[2] * cos_angle + cross_z * sin_angle + kz * k_dot_v * one_minus_cos;
+    #[tokio::test]
+    async fn test_group_strips_inline_comments() {
+        let mut stream = test_stream();
+        
+        stream.handle_sentinel_command("@group begin").await.unwrap();
+        stream.group_buffer.as_mut().unwrap().extend([
+            "x = 1  # start value".to_string(),
+            "textmsg(\"step #2\") # log it".to_string(),
+            "y = x + 1".to_string(),
+        ]);
+        stream.handle_sentinel_command("@group end").await.unwrap();
+        assert_eq!(stream.pending_script.as_deref(), Some("x = 1 textmsg(\"step #2\") y = x + 1"));
+    }
     
-    [result_x, result_y, result_z]
-}
-
-/// Convert direction vector to azimuth/elevation angles in degrees
-fn direction_to_azimuth_elevation(direction: [f64; 3]) -> (f64, f64) {
-    let [dx, dy, dz] = direction;
+    #[tokio::test]
+    async fn test_freedrive_blocks_motion_commands() {
+        let mut stream = test_stream();
+        stream.freedrive_active = true;
+        
+        let info = stream.process_command("movel(p[0.1,0.2,0.3,0,3.1,0])".to_string()).await.unwrap();
+        assert!(matches!(info.status, CommandStatus::Failed(FailureReason::InvalidRequest { .. })));
+        assert!(stream.get_stats().freedrive_active);
+        
+        // Reconnecting starts a fresh program without freedrive (the attempt itself fails offline)
+        let _ = stream.attempt_reconnection().await;
+        assert!(!stream.freedrive_active);
+    }
     
-    // Azimuth: angle in XY plane from +X axis (0° = +X, 90° = +Y)
-    // This is the compass bearing of where the robot is pointing horizontally
-    let azimuth_rad = dy.atan2(dx);
-    let azimuth_deg = azimuth_rad.to_degrees();
+    #[tokio::test]
+    async fn test_force_mode_blocks_joint_motion() {
+        let mut stream = test_stream();
+        stream.force_mode_active = true;
+        
+        let info = stream.process_command("movej([0,-1.57,0,0,0,0])".to_string()).await.unwrap();
+        assert!(matches!(info.status, CommandStatus::Failed(FailureReason::InvalidRequest { .. })));
+        assert!(stream.get_stats().force_mode_active);
+        
+        // Freedrive would fight the force controller
+        let info = stream.handle_sentinel_command("@freedrive on").await.unwrap();
+        assert!(matches!(info.status, CommandStatus::Failed(FailureReason::InvalidRequest { .. })));
+        assert!(!stream.freedrive_active);
+    }
     
-    // Elevation: angle from horizontal plane (0° = horizontal, 90° = +Z)
-    // This is how much the robot is pointing up (+) or down (-)
-    let horizontal_distance = (dx * dx + dy * dy).sqrt();
-    let elevation_rad = dz.atan2(horizontal_distance);
-    let elevation_deg = elevation_rad.to_degrees();
+    #[tokio::test]
+    async fn test_modes_and_variables_held_like_urscript() {
+        let mut stream = test_stream();
+        stream.paused = true;
+        
+        for sentinel in ["@freedrive on", "@force on p[0,0,0,0,0,0] [0,0,1,0,0,0] [0,0,-10,0,0,0] 2 [0.1,0.1,0.1,0.1,0.1,0.1]", "@var set count 3"] {
+            let info = stream.handle_sentinel_command(sentinel).await.unwrap();
+            let CommandStatus::Failed(FailureReason::InvalidRequest { message }) = info.status else {
+                panic!("expected refusal for {}: {:?}", sentinel, info.status);
+            };
+            assert!(message.contains("paused"), "{}", message);
+        }
+        assert!(!stream.freedrive_active && !stream.force_mode_active);
+        assert!(stream.variables.is_empty());
+    }
     
-    (azimuth_deg, elevation_deg)
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    async fn test_resume_without_pause_is_refused() {
+        let mut stream = test_stream();
+        
+        let info = stream.handle_sentinel_command("@resume").await.unwrap();
+        let CommandStatus::Failed(FailureReason::InvalidRequest { message }) = info.status else {
+            panic!("expected refusal: {:?}", info.status);
+        };
+        assert!(message.contains("Nothing paused"), "{}", message);
+    }
+    
+    #[test]
+    fn test_parse_force_args() {
+        let mode = parse_force_args(&["p[0,0,0,0,0,0]", "[0,0,1,0,0,0]", "[0,", "0,", "-10,0,0,0]", "2", "[0.1,0.1,0.15,0.3,0.3,0.3]"]).unwrap();
+        assert_eq!(mode.selection_vector, [false, false, true, false, false, false]);
+        assert_eq!(mode.wrench[2], -10.0);
+        assert_eq!(mode.force_type, 2);
+        
+        // Selection entries are 0/1, lists have six entries, the frame is a pose
+        assert_eq!(parse_force_args(&["p[0,0,0,0,0,0]", "[0,0,2,0,0,0]", "[0,0,-10,0,0,0]", "2", "[0.1,0.1,0.1,0.1,0.1,0.1]"]), None);
+        assert_eq!(parse_force_args(&["p[0,0,0,0,0,0]", "[0,0,1,0,0]", "[0,0,-10,0,0,0]", "2", "[0.1,0.1,0.1,0.1,0.1,0.1]"]), None);
+        assert_eq!(parse_force_args(&["[0,0,0,0,0,0]", "[0,0,1,0,0,0]", "[0,0,-10,0,0,0]", "2", "[0.1,0.1,0.1,0.1,0.1,0.1]"]), None);
+    }
+    
+    #[tokio::test]
+    async fn test_commands_refused_while_not_running() {
+        let mut stream = test_stream();
+        let mut data = std::collections::HashMap::new();
+        data.insert("robot_mode".to_string(), vec![3.0]);
+        data.insert("safety_mode".to_string(), vec![1.0]);
+        stream.controller.as_mut().unwrap().process_rtde_package(&data, 0.0);
+        
+        let info = stream.process_command("textmsg(\"hi\")".to_string()).await.unwrap();
+        let CommandStatus::Failed(FailureReason::InvalidRequest { message }) = info.status else {
+            panic!("expected refusal: {:?}", info.status);
+        };
+        assert_eq!(message, "robot not in RUNNING mode: current mode POWER_OFF");
+    }
+    
+    #[tokio::test]
+    async fn test_recover_refuses_emergency_stop() {
+        let mut stream = test_stream();
+        stream.controller.as_mut().unwrap()
+            .process_monitoring_data([0.0; 6], [0.0; 6], 7, 7, 1, None, 0.0);
+        
+        let info = stream.handle_sentinel_command("@recover").await.unwrap();
+        let CommandStatus::Failed(FailureReason::NotReady { message }) = info.status else {
+            panic!("expected failure: {:?}", info.status);
+        };
+        assert!(message.contains("ROBOT_EMERGENCY_STOP"), "{}", message);
+    }
+    
+    #[tokio::test]
+    async fn test_drain_without_backlog_signals_shutdown() {
+        let controller = RobotController::new_with_config("config/default_config.yaml")
+            .expect("default config should load");
+        let shutdown = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let mut stream = CommandStream::new_with_shared_controller(
+            Arc::new(tokio::sync::Mutex::new(controller)),
+            shutdown.clone()
+        );
+        stream.group_buffer = Some(vec!["textmsg(\"unfinished\")".to_string()]);
+        
+        stream.drain().await.unwrap();
+        assert!(shutdown.load(Ordering::Relaxed));
+        assert!(stream.group_buffer.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_drain_deadline_bounds_command_wait() {
+        let controller = RobotController::new_with_config("config/default_config.yaml")
+            .expect("default config should load");
+        let shutdown = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let mut stream = CommandStream::new_with_shared_controller(
+            Arc::new(tokio::sync::Mutex::new(controller)),
+            shutdown.clone()
+        );
+        
+        // No per-command timeout; the drain deadline from SIGTERM has already passed
+        stream.drain_deadline = Some(std::time::Instant::now());
+        let outcome = stream.wait_for_completion_with_timeout(5, None, None).await.unwrap();
+        assert_eq!(outcome, WaitOutcome::Interrupted);
+        assert!(stream.shutdown_requested);
+        assert!(stream.drain_deadline.is_none());
+        assert!(shutdown.load(Ordering::Relaxed));
+    }
+    
+    #[tokio::test]
+    async fn test_io_parsing_and_pin_validation() {
+        assert_eq!(IoAction::parse(&["set", "3", "on"]), Some(IoAction::Set { pin: 3, value: true }));
+        assert_eq!(IoAction::parse(&["set", "0", "False"]), Some(IoAction::Set { pin: 0, value: false }));
+        assert_eq!(IoAction::parse(&["get"]), Some(IoAction::Get));
+        assert_eq!(IoAction::parse(&["analog"]), Some(IoAction::Analog));
+        assert_eq!(IoAction::parse(&["set", "3"]), None);
+        assert_eq!(IoAction::parse(&["set", "x", "on"]), None);
+        
+        let mut stream = test_stream();
+        let info = stream.handle_sentinel_command("@io set 8 on").await.unwrap();
+        let CommandStatus::Failed(FailureReason::InvalidRequest { message }) = info.status else {
+            panic!("expected failure: {:?}", info.status);
+        };
+        assert!(message.contains("out of range"), "{}", message);
+        
+        // Writes are held while paused, like URScript
+        stream.paused = true;
+        let info = stream.handle_sentinel_command("@io set 3 on").await.unwrap();
+        let CommandStatus::Failed(FailureReason::InvalidRequest { message }) = info.status else {
+            panic!("expected failure: {:?}", info.status);
+        };
+        assert!(message.contains("paused"), "{}", message);
+        stream.paused = false;
+        
+        // Inputs come from RTDE bit 0 = DI0
+        let controller = stream.controller.as_mut().unwrap();
+        let mut data = std::collections::HashMap::new();
+        data.insert("actual_digital_input_bits".to_string(), vec![0b101 as f64]);
+        controller.process_rtde_package(&data, 0.0);
+        assert_eq!(controller.get_digital_inputs().unwrap(), [true, false, true, false, false, false, false, false]);
+        
+        // Analog inputs only when the recipe has them; tool voltage is optional on top
+        assert!(controller.get_analog_inputs().is_err());
+        for (name, value) in [("standard_analog_input0", 4.2), ("standard_analog_input1", 0.0), ("tool_analog_input0", 0.012), ("tool_analog_input1", 1.5)] {
+            data.insert(name.to_string(), vec![value]);
+        }
+        controller.process_rtde_package(&data, 0.0);
+        let analog = controller.get_analog_inputs().unwrap();
+        assert_eq!((analog.standard, analog.tool, analog.tool_output_voltage), ([4.2, 0.0], [0.012, 1.5], None));
+        data.insert("tool_output_voltage".to_string(), vec![24.0]);
+        controller.process_rtde_package(&data, 0.0);
+        assert_eq!(controller.get_analog_inputs().unwrap().tool_output_voltage, Some(24));
+    }
+    
+    #[tokio::test]
+    async fn test_script_input_handles_bom_and_crlf() {
+        let mut stream = test_stream();
+        let script: &[u8] = "\u{feff}@session begin\r\n# comment\r\n\r\n".as_bytes();
+        stream.run_input(script, true).await.unwrap();
+        assert!(stream.session_active);
+        
+        let path = std::env::temp_dir().join(format!("urd-script-{}.script", std::process::id()));
+        std::fs::write(&path, "@session end\r\n").unwrap();
+        stream.run_script(path.to_str().unwrap(), false).await.unwrap();
+        assert!(!stream.session_active);
+        std::fs::remove_file(&path).ok();
+    }
+    
+    #[test]
+    fn test_var_parsing() {
+        let VarAction::Set { name, value, literal } = VarAction::parse(&["set", "pose_target", "p[0.1,", "-0.2,", "0.3,", "0,", "3.1,", "0]"]).unwrap() else {
+            panic!("expected set");
+        };
+        assert_eq!(name, "pose_target");
+        assert_eq!(value, serde_json::json!({ "pose": [0.1, -0.2, 0.3, 0.0, 3.1, 0.0] }));
+        assert_eq!(literal, "p[0.1,-0.2,0.3,0,3.1,0]");
+        
+        let literal = |args: &[&str]| match VarAction::parse(args) {
+            Some(VarAction::Set { literal, .. }) => Some(literal),
+            _ => None,
+        };
+        assert_eq!(literal(&["set", "count", "3"]).as_deref(), Some("3"));
+        assert_eq!(literal(&["set", "gain", "2"]).as_deref(), Some("2"));
+        assert_eq!(literal(&["set", "gain", "0.5"]).as_deref(), Some("0.5"));
+        assert_eq!(literal(&["set", "gain", "1e3"]).as_deref(), Some("1000.0"));
+        assert_eq!(literal(&["set", "done", "true"]).as_deref(), Some("True"));
+        assert_eq!(literal(&["set", "q", "[0,-1.57,0,0,0,0]"]).as_deref(), Some("[0,-1.57,0,0,0,0]"));
+        
+        assert_eq!(VarAction::parse(&["get", "count"]), Some(VarAction::Get { name: "count".to_string(), kind: None }));
+        assert_eq!(VarAction::parse(&["get", "q", "list:3"]), Some(VarAction::Get { name: "q".to_string(), kind: Some(VarKind::List(3)) }));
+        assert_eq!(VarAction::parse(&["get", "q", "list:7"]), None);
+        assert_eq!(VarAction::parse(&["get", "q", "string"]), None);
+        // Names must be identifiers and values literals, never arbitrary script
+        assert_eq!(VarAction::parse(&["set", "1x", "3"]), None);
+        assert_eq!(VarAction::parse(&["set", "x", "popup(\"hi\")"]), None);
+        assert_eq!(VarAction::parse(&["set", "x", "p[1,2,3]"]), None);
+        assert_eq!(VarAction::parse(&["set", "x"]), None);
+    }
+    
+    #[test]
+    fn test_var_readback_scripts() {
+        let registers = (18, 23);
+        assert_eq!(
+            VarKind::Float.readback_script("gain", 4, registers),
+            "write_output_float_register(18, gain) write_output_integer_register(23, 4)"
+        );
+        assert_eq!(
+            VarKind::Bool.readback_script("done", 5, registers),
+            "if done: write_output_float_register(18, 1) else: write_output_float_register(18, 0) end write_output_integer_register(23, 5)"
+        );
+        assert!(VarKind::Pose.readback_script("target", 6, registers)
+            .starts_with("write_output_float_register(18, target[0]) write_output_float_register(19, target[1]) "));
+        
+        // Without a hint the kind follows the last @var set
+        assert_eq!(VarKind::of(&serde_json::json!(3)), VarKind::Int);
+        assert_eq!(VarKind::of(&serde_json::json!({ "pose": [0.1, -0.2, 0.3, 0.0, 3.1, 0.0] })), VarKind::Pose);
+        assert_eq!(VarKind::Int.decode(&[3.0000001]), serde_json::json!(3));
+        assert_eq!(VarKind::List(2).decode(&[0.5, 1.0]), serde_json::json!([0.5, 1.0]));
+    }
+    
+    #[test]
+    fn test_ik_parsing() {
+        let (pose, config) = parse_ik_args(&["p[0.1,", "-0.4,", "0.3,", "0,", "3.1,", "0]"]).unwrap();
+        assert_eq!(pose, [0.1, -0.4, 0.3, 0.0, 3.1, 0.0]);
+        assert_eq!(config, JointConfiguration::Any);
+        
+        let (_, config) = parse_ik_args(&["p[0.1,-0.4,0.3,0,3.1,0]", "current"]).unwrap();
+        assert_eq!(config, JointConfiguration::Current);
+        let (_, config) = parse_ik_args(&["p[0.1,-0.4,0.3,0,3.1,0]", "[0,", "-1.57,1.57,0,0,0]"]).unwrap();
+        assert_eq!(config, JointConfiguration::Near([0.0, -1.57, 1.57, 0.0, 0.0, 0.0]));
+        
+        assert_eq!(parse_ik_args(&[]), None);
+        assert_eq!(parse_ik_args(&["[0.1,-0.4,0.3,0,3.1,0]"]), None);
+        assert_eq!(parse_ik_args(&["p[0.1,-0.4,0.3]"]), None);
+        assert_eq!(parse_ik_args(&["p[0.1,-0.4,0.3,0,3.1,0]", "[0,0,0]"]), None);
+        assert_eq!(parse_ik_args(&["p[0.1,-0.4,0.3,0,3.1,0]", "nearby"]), None);
+    }
     
     #[test]
-    fn test_pose_azimuth_elevation_calculation() {
-        // Test data from actual robot output
-        // TCP pose: [-0.19005552,-0.91001301,0.91996543,1.41407608,0.51115312,-0.56129826]
-        let rx = 1.41407608;
-        let ry = 0.51115312;
-        let rz = -0.56129826;
-        
-        // Expected pointing direction from Python reference
-        let expected_direction_x = -0.0003620138906880177;
-        let expected_direction_y = -0.995729840111155;
-        let expected_direction_z = 0.09231443255611105;
-        
-        // Expected azimuth/elevation from Python reference  
-        let expected_azimuth = -90.02083081807142;
-        let expected_elevation = 5.296768755647904;
-        
-        // Calculate direction vector from rotation vector
-        let calculated_direction = rotvec_to_direction_vector(rx, ry, rz);
-        
-        // Calculate azimuth/elevation from direction
-        let (calculated_azimuth, calculated_elevation) = direction_to_azimuth_elevation(calculated_direction);
-        
-        // Test direction vector calculation (tolerance for floating point precision)
-        let direction_tolerance = 1e-6;
-        assert!((calculated_direction[0] - expected_direction_x).abs() < direction_tolerance,
-            "Direction X mismatch: calculated={}, expected={}", calculated_direction[0], expected_direction_x);
-        assert!((calculated_direction[1] - expected_direction_y).abs() < direction_tolerance,
-            "Direction Y mismatch: calculated={}, expected={}", calculated_direction[1], expected_direction_y);
-        assert!((calculated_direction[2] - expected_direction_z).abs() < direction_tolerance,
-            "Direction Z mismatch: calculated={}, expected={}", calculated_direction[2], expected_direction_z);
-        
-        // Test azimuth/elevation calculation (tolerance for floating point precision)
-        let angle_tolerance = 0.01; // 0.01 degree tolerance
-        assert!((calculated_azimuth - expected_azimuth).abs() < angle_tolerance,
-            "Azimuth mismatch: calculated={:.6}, expected={:.6}", calculated_azimuth, expected_azimuth);
-        assert!((calculated_elevation - expected_elevation).abs() < angle_tolerance,
-            "Elevation mismatch: calculated={:.6}, expected={:.6}", calculated_elevation, expected_elevation);
-        
-        println!("✓ Direction vector: [{:.12}, {:.12}, {:.12}]", 
-            calculated_direction[0], calculated_direction[1], calculated_direction[2]);
-        println!("✓ Azimuth: {:.6}° (expected: {:.6}°)", calculated_azimuth, expected_azimuth);
-        println!("✓ Elevation: {:.6}° (expected: {:.6}°)", calculated_elevation, expected_elevation);
+    fn test_parse_move_args() {
+        let parsed = MoveArgs::parse(&["[0,", "-1.57,", "1.57,", "-1.57,", "-1.57,", "0]", "v=0.5"], false).unwrap();
+        assert_eq!(parsed.target, [0.0, -1.57, 1.57, -1.57, -1.57, 0.0]);
+        assert_eq!(parsed.params(MoveParams::joint_defaults()), MoveParams { acceleration: 1.4, velocity: 0.5, blend_radius: 0.0 });
+        
+        let parsed = MoveArgs::parse(&["p[0.1,-0.4,0.3,0,3.1,0]", "a=0.2", "r=0.01", "[0,", "-1.57,1.57,0,0,0]"], true).unwrap();
+        assert_eq!(parsed.acceleration, Some(0.2));
+        assert_eq!(parsed.blend_radius, Some(0.01));
+        assert_eq!(parsed.config, JointConfiguration::Near([0.0, -1.57, 1.57, 0.0, 0.0, 0.0]));
+        assert_eq!(MoveArgs::parse(&["p[0.1,-0.4,0.3,0,3.1,0]", "current"], true).unwrap().config, JointConfiguration::Current);
+        
+        // Wrong target kind or length, bad parameters, joint hints on movej
+        assert_eq!(MoveArgs::parse(&["p[0.1,-0.4,0.3,0,3.1,0]"], false), None);
+        assert_eq!(MoveArgs::parse(&["[0.1,-0.4,0.3,0,3.1,0]"], true), None);
+        assert_eq!(MoveArgs::parse(&["[0,0,0]"], false), None);
+        assert_eq!(MoveArgs::parse(&["[0,0,0,0,0,0]", "v=-1"], false), None);
+        assert_eq!(MoveArgs::parse(&["[0,0,0,0,0,0]", "t=2"], false), None);
+        assert_eq!(MoveArgs::parse(&["[0,0,0,0,0,0]", "current"], false), None);
+    }
+    
+    #[tokio::test]
+    async fn test_speed_fraction_validated_before_connecting() {
+        let mut stream = test_stream();
+        for line in ["@speed 1.5", "@speed -0.1", "@speed fast", "@speed"] {
+            let info = stream.handle_sentinel_command(line).await.unwrap();
+            assert!(matches!(info.status, CommandStatus::Failed(_)), "{}: {:?}", line, info.status);
+        }
+        assert_eq!(stream.controller.as_ref().unwrap().speed_scaling(), None);
+    }
+    
+    #[test]
+    fn test_classify_execution_sequences() {
+        let run = |target: u32, polls: &[u32]| {
+            let mut previous = None;
+            polls.iter().map(|&executed| {
+                let progress = classify_execution(target, previous, executed);
+                previous = Some(executed);
+                progress
+            }).collect::<Vec<_>>()
+        };
+        
+        // Monotonic progress up to the target
+        assert_eq!(run(5, &[3, 4, 5]), vec![ExecutionProgress::Pending, ExecutionProgress::Pending, ExecutionProgress::Completed]);
+        
+        // Gap: several commands finished between polls
+        assert_eq!(run(5, &[2, 9]), vec![ExecutionProgress::Pending, ExecutionProgress::Completed]);
+        
+        // A reset after a clear is a fault rather than something to keep waiting on
+        assert_eq!(run(5, &[4, 1]), vec![ExecutionProgress::Pending, ExecutionProgress::Reset { from: 4, to: 1 }]);
+        assert_eq!(run(2, &[1, 0]), vec![ExecutionProgress::Pending, ExecutionProgress::Reset { from: 1, to: 0 }]);
+        
+        // Repeated IDs just keep waiting
+        assert_eq!(run(5, &[4, 4, 4]), vec![ExecutionProgress::Pending; 3]);
     }
     
     #[test]
-    fn test_basic_directions() {
-        // Test cardinal directions
-        
-        // Pointing +X (East): azimuth=0°, elevation=0°
-        let direction_east = [1.0, 0.0, 0.0];
-        let (az, el) = direction_to_azimuth_elevation(direction_east);
-        assert!((az - 0.0).abs() < 0.01);
-        assert!((el - 0.0).abs() < 0.01);
-        
-        // Pointing +Y (North): azimuth=90°, elevation=0°
-        let direction_north = [0.0, 1.0, 0.0];
-        let (az, el) = direction_to_azimuth_elevation(direction_north);
-        assert!((az - 90.0).abs() < 0.01);
-        assert!((el - 0.0).abs() < 0.01);
-        
-        // Pointing -Y (South): azimuth=-90°, elevation=0°
-        let direction_south = [0.0, -1.0, 0.0];
-        let (az, el) = direction_to_azimuth_elevation(direction_south);
-        assert!((az - (-90.0)).abs() < 0.01);
-        assert!((el - 0.0).abs() < 0.01);
-        
-        // Pointing +Z (Up): azimuth=undefined, elevation=90°
-        let direction_up = [0.0, 0.0, 1.0];
-        let (_az, el) = direction_to_azimuth_elevation(direction_up);
-        assert!((el - 90.0).abs() < 0.01);
+    fn test_configured_clear_buffer_limit() {
+        let mut stream = test_stream();
+        assert_eq!(stream.clear_buffer_limit, CLEAR_BUFFER_LIMIT);
+        
+        stream.clear_buffer_limit = 5;
+        let cleared: Vec<u32> = (1..=12)
+            .filter(|&count| {
+                stream.command_count = count;
+                stream.should_auto_clear()
+            })
+            .collect();
+        assert_eq!(cleared, vec![5, 10]);
     }
+
 }
 
 /// Status of a command execution
@@ -155,7 +501,7 @@ mod tests {
 pub enum CommandStatus {
     Sent,
     Completed,
-    Failed(String),
+    Failed(FailureReason),
 }
 
 /// Information about an executed command
@@ -164,7 +510,356 @@ pub struct CommandInfo {
     pub id: u32,
     pub command: String,
     pub status: CommandStatus,
-    pub termination_id: Option<u32>,  // ID of the time(0) termination token
+    pub termination_id: Option<u32>,  // ID of the termination token (interpreter.termination_token)
+}
+
+/// Outcome of waiting for an interpreter command to execute
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WaitOutcome {
+    Completed,
+    Interrupted,
+    TimedOut,
+}
+
+/// What one poll of the interpreter's last executed ID says about a command
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExecutionProgress {
+    /// The interpreter hasn't reached the command yet
+    Pending,
+    /// The command (or a later one) has executed
+    Completed,
+    /// The executed ID went backwards - the buffer was cleared or the program restarted,
+    /// so the awaited ID may never be reached
+    Reset { from: u32, to: u32 },
+}
+
+/// Classify a poll of `last_executed` while waiting for `target_id`
+/// 
+/// `previous_executed` is the value from the previous poll of the same wait.
+/// IDs may skip (several commands finish between polls), so anything at or
+/// past the target counts as completed.
+fn classify_execution(target_id: u32, previous_executed: Option<u32>, last_executed: u32) -> ExecutionProgress {
+    match previous_executed {
+        Some(previous) if last_executed < previous => ExecutionProgress::Reset { from: previous, to: last_executed },
+        _ if last_executed >= target_id => ExecutionProgress::Completed,
+        _ => ExecutionProgress::Pending,
+    }
+}
+
+/// How the daemon was asked to stop
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ShutdownKind {
+    /// Ctrl+C: abort motion immediately
+    Abort,
+    /// SIGTERM: finish commands already sent, then exit
+    Drain,
+}
+
+/// Dashboard program control requested through a sentinel
+#[derive(Debug, Clone, PartialEq)]
+enum ProgramAction {
+    Load(String),
+    Play,
+    Stop,
+    Pause,
+    State,
+}
+
+impl ProgramAction {
+    /// Parse `@program` arguments: `load <name>`, `play`, `stop` or `state`
+    fn parse(args: &[&str]) -> Option<Self> {
+        match args {
+            ["load", name] => Some(ProgramAction::Load(name.to_string())),
+            ["play"] => Some(ProgramAction::Play),
+            ["stop"] => Some(ProgramAction::Stop),
+            ["state"] => Some(ProgramAction::State),
+            _ => None,
+        }
+    }
+    
+    /// Action name as used in `@program` and JSON output
+    fn name(&self) -> &'static str {
+        match self {
+            ProgramAction::Load(_) => "load",
+            ProgramAction::Play => "play",
+            ProgramAction::Stop => "stop",
+            ProgramAction::Pause => "pause",
+            ProgramAction::State => "state",
+        }
+    }
+}
+
+/// Digital I/O operation requested through `@io`
+#[derive(Debug, Clone, PartialEq)]
+enum IoAction {
+    Set { pin: u8, value: bool },
+    Get,
+    Analog,
+}
+
+impl IoAction {
+    /// Parse `@io` arguments: `set <pin> <on|off|true|false|1|0>`, `get` or `analog`
+    fn parse(args: &[&str]) -> Option<Self> {
+        match args {
+            ["get"] => Some(IoAction::Get),
+            ["analog"] => Some(IoAction::Analog),
+            ["set", pin, value] => {
+                let pin = pin.parse().ok()?;
+                let value = match value.to_ascii_lowercase().as_str() {
+                    "on" | "true" | "1" => true,
+                    "off" | "false" | "0" => false,
+                    _ => return None,
+                };
+                Some(IoAction::Set { pin, value })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parse a bracketed list of finite numbers such as `[1,-2.5,3]`
+fn parse_number_list(list: &str) -> Option<Vec<f64>> {
+    list.strip_prefix('[')?.strip_suffix(']')?
+        .split(',')
+        .map(|n| n.parse::<f64>().ok().filter(|n| n.is_finite()))
+        .collect()
+}
+
+/// Parse `@ik` arguments: `p[x,y,z,rx,ry,rz] [current | [q1,..,q6]]`
+/// 
+/// Spaces inside brackets are allowed.
+fn parse_ik_args(args: &[&str]) -> Option<([f64; 6], JointConfiguration)> {
+    let joined = args.concat();
+    let pose_end = joined.find(']')? + 1;
+    let pose: [f64; 6] = joined[..pose_end].strip_prefix('p').and_then(parse_number_list)?.try_into().ok()?;
+    
+    let config = match &joined[pose_end..] {
+        "" => JointConfiguration::Any,
+        "current" => JointConfiguration::Current,
+        qnear => JointConfiguration::Near(parse_number_list(qnear)?.try_into().ok()?),
+    };
+    Some((pose, config))
+}
+
+/// Rejoin bracketed lists split on spaces so each token is one argument
+fn rejoin_lists(args: &[&str]) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    for arg in args {
+        match tokens.last_mut() {
+            Some(last) if last.matches('[').count() > last.matches(']').count() => last.push_str(arg),
+            _ => tokens.push(arg.to_string()),
+        }
+    }
+    tokens
+}
+
+/// Parse `@force on` arguments: `p[frame] [selection] [wrench] <type> [limits]`
+/// 
+/// Each list has six entries and selection entries are 0 or 1. Spaces inside
+/// brackets are allowed. Value ranges are left to `ForceMode::validate`.
+fn parse_force_args(args: &[&str]) -> Option<ForceMode> {
+    let tokens = rejoin_lists(args);
+    let [frame, selection, wrench, force_type, limits] = tokens.as_slice() else { return None };
+    let list = |token: &str| -> Option<[f64; 6]> { parse_number_list(token)?.try_into().ok() };
+    
+    let selection = list(selection)?;
+    if selection.iter().any(|&axis| axis != 0.0 && axis != 1.0) {
+        return None;
+    }
+    Some(ForceMode {
+        task_frame: list(frame.strip_prefix('p')?)?,
+        selection_vector: selection.map(|axis| axis == 1.0),
+        wrench: list(wrench)?,
+        force_type: force_type.parse().ok()?,
+        limits: list(limits)?,
+    })
+}
+
+/// Target and parameter overrides of a `@movej` or `@movel`
+#[derive(Debug, Clone, PartialEq)]
+struct MoveArgs {
+    target: [f64; 6],
+    acceleration: Option<f64>,
+    velocity: Option<f64>,
+    blend_radius: Option<f64>,
+    config: JointConfiguration,
+}
+
+impl MoveArgs {
+    /// Parse `[q1,..,q6]` (joint target) or `p[x,y,z,rx,ry,rz] [current | [q1,..,q6]]`
+    /// (pose target), each followed by optional `a=`, `v=` and `r=`
+    /// 
+    /// Spaces inside brackets are allowed. Accelerations and speeds must be
+    /// positive and the blend radius not negative.
+    fn parse(args: &[&str], pose_target: bool) -> Option<Self> {
+        let tokens = rejoin_lists(args);
+        let mut tokens = tokens.iter().map(String::as_str);
+        
+        let target = tokens.next()?;
+        let target = if pose_target { target.strip_prefix('p')? } else { target };
+        let mut parsed = Self {
+            target: parse_number_list(target)?.try_into().ok()?,
+            acceleration: None,
+            velocity: None,
+            blend_radius: None,
+            config: JointConfiguration::Any,
+        };
+        
+        for token in tokens {
+            match token.split_once('=') {
+                Some((key, value)) => {
+                    let value = value.parse::<f64>().ok().filter(|v| v.is_finite())?;
+                    match key {
+                        "a" if value > 0.0 => parsed.acceleration = Some(value),
+                        "v" if value > 0.0 => parsed.velocity = Some(value),
+                        "r" if value >= 0.0 => parsed.blend_radius = Some(value),
+                        _ => return None,
+                    }
+                }
+                None if pose_target && token == "current" => parsed.config = JointConfiguration::Current,
+                None if pose_target => parsed.config = JointConfiguration::Near(parse_number_list(token)?.try_into().ok()?),
+                None => return None,
+            }
+        }
+        Some(parsed)
+    }
+    
+    /// Motion parameters, taking whatever wasn't given from `defaults`
+    fn params(&self, defaults: MoveParams) -> MoveParams {
+        MoveParams {
+            acceleration: self.acceleration.unwrap_or(defaults.acceleration),
+            velocity: self.velocity.unwrap_or(defaults.velocity),
+            blend_radius: self.blend_radius.unwrap_or(defaults.blend_radius),
+        }
+    }
+}
+
+/// How `@var get` reads a variable back, which decides the script and registers used
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VarKind {
+    Int,
+    Float,
+    Bool,
+    /// List of numbers, at most one per readback double register
+    List(usize),
+    Pose,
+}
+
+impl VarKind {
+    /// Parse a `@var get` type hint: `int`, `float`, `bool`, `pose` or `list:N`
+    fn parse(hint: &str) -> Option<Self> {
+        match hint {
+            "int" => Some(VarKind::Int),
+            "float" => Some(VarKind::Float),
+            "bool" => Some(VarKind::Bool),
+            "pose" => Some(VarKind::Pose),
+            _ => hint.strip_prefix("list:")?.parse().ok()
+                .filter(|len| (1..=6).contains(len))
+                .map(VarKind::List),
+        }
+    }
+    
+    /// The kind of a value assigned with `@var set`
+    fn of(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Bool(_) => VarKind::Bool,
+            serde_json::Value::Number(n) if n.is_i64() => VarKind::Int,
+            serde_json::Value::Array(list) => VarKind::List(list.len()),
+            serde_json::Value::Object(_) => VarKind::Pose,
+            _ => VarKind::Float,
+        }
+    }
+    
+    /// Double registers needed to read the value back
+    fn register_count(self) -> usize {
+        match self {
+            VarKind::List(len) => len,
+            VarKind::Pose => 6,
+            _ => 1,
+        }
+    }
+    
+    /// Script writing `name` to the double registers, then `sequence` to the int register
+    fn readback_script(self, name: &str, sequence: i32, registers: (u8, u8)) -> String {
+        let (result_register, status_register) = registers;
+        let writes = match self {
+            VarKind::Bool => format!(
+                "if {name}: write_output_float_register({reg}, 1) else: write_output_float_register({reg}, 0) end",
+                name = name, reg = result_register
+            ),
+            VarKind::List(_) | VarKind::Pose => (0..self.register_count())
+                .map(|i| format!("write_output_float_register({}, {}[{}])", result_register as usize + i, name, i))
+                .collect::<Vec<_>>()
+                .join(" "),
+            VarKind::Int | VarKind::Float => format!("write_output_float_register({}, {})", result_register, name),
+        };
+        format!("{} write_output_integer_register({}, {})", writes, status_register, sequence)
+    }
+    
+    /// JSON value from the registers, in the same form `@var set` reports
+    fn decode(self, values: &[f64]) -> serde_json::Value {
+        match self {
+            VarKind::Int => serde_json::json!(values[0].round() as i64),
+            VarKind::Float => serde_json::json!(values[0]),
+            VarKind::Bool => serde_json::json!(values[0] != 0.0),
+            VarKind::List(_) => serde_json::json!(values),
+            VarKind::Pose => serde_json::json!({ "pose": values }),
+        }
+    }
+}
+
+/// Interpreter variable operation requested through `@var`
+#[derive(Debug, Clone, PartialEq)]
+enum VarAction {
+    /// Assign `name`; `value` is the JSON form, `literal` the URScript form
+    Set { name: String, value: serde_json::Value, literal: String },
+    /// Read `name` back from the robot; without a `kind` hint, as last set with `@var set`
+    Get { name: String, kind: Option<VarKind> },
+}
+
+impl VarAction {
+    /// Parse `@var` arguments: `set <name> <value>` or `get <name> [kind]`
+    /// 
+    /// Values may be integers, floats, `True`/`False`, arrays of numbers
+    /// (`[1,2,3]`) or poses (`p[x,y,z,rx,ry,rz]`); spaces inside brackets are allowed.
+    fn parse(args: &[&str]) -> Option<Self> {
+        let (name, value, kind) = match args {
+            ["get", name] => (*name, None, None),
+            ["get", name, kind] => (*name, None, Some(VarKind::parse(kind)?)),
+            ["set", name, value @ ..] if !value.is_empty() => (*name, Some(value.concat()), None),
+            _ => return None,
+        };
+        
+        let mut chars = name.chars();
+        if !chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return None;
+        }
+        let name = name.to_string();
+        
+        let Some(value) = value else { return Some(VarAction::Get { name, kind }) };
+        
+        let numbers = parse_number_list;
+        let format_list = |list: &[f64]| list.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",");
+        
+        let (value, literal) = if let Ok(int) = value.parse::<i64>() {
+            (serde_json::json!(int), int.to_string())
+        } else if let Some(float) = value.parse::<f64>().ok().filter(|n| n.is_finite()) {
+            (serde_json::json!(float), format!("{:?}", float))
+        } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            let flag = value.eq_ignore_ascii_case("true");
+            (serde_json::json!(flag), if flag { "True" } else { "False" }.to_string())
+        } else if let Some(pose) = value.strip_prefix('p').and_then(numbers).filter(|pose| pose.len() == 6) {
+            (serde_json::json!({ "pose": pose }), format!("p[{}]", format_list(&pose)))
+        } else if let Some(list) = numbers(&value) {
+            (serde_json::json!(list), format!("[{}]", format_list(&list)))
+        } else {
+            return None;
+        };
+        
+        Some(VarAction::Set { name, value, literal })
+    }
 }
 
 /// Command streaming processor that reads from stdin and executes commands
@@ -173,22 +868,70 @@ pub struct CommandStream {
     shared_controller: Option<Arc<tokio::sync::Mutex<RobotController>>>,
     shutdown_signal: Option<Arc<std::sync::atomic::AtomicBool>>,
     command_count: u32,
+    clear_buffer_limit: u32,
+    accepted_count: u32,
     pending_commands: Vec<CommandInfo>,
     eof_logged: bool,
     inside_brace_block: bool,
+    session_active: bool,
+    paused: bool,
+    freedrive_active: bool,
+    /// `force_mode` started with `@force on` and not yet ended
+    force_mode_active: bool,
+    /// Pre-check for `command.lint`; remembers functions defined earlier in the stream
+    linter: Linter,
+    /// When the interpreter accepted the current URScript command, for the audit's wait/exec split
+    sent_at: Option<std::time::Instant>,
+    group_buffer: Option<Vec<String>>,
+    /// URScript built by a sentinel (closed @group, @movej, @movel), run right after it
+    pending_script: Option<String>,
+    /// Times `pending_script` runs (`@group end repeat=N`), or at most runs with `pending_until`
+    pending_repeat: u32,
+    /// URScript condition ending the repeat once true after an iteration (`@group end until=...`)
+    pending_until: Option<String>,
+    shutdown_requested: bool,
+    /// SIGTERM arrived while a command was executing; drain once it finishes,
+    /// aborting if the drain timeout (counted from the signal) runs out first
+    drain_deadline: Option<std::time::Instant>,
+    command_timeout: Option<Duration>,
+    metrics: Option<Arc<CommandMetrics>>,
+    audit: Option<AuditLog>,
+    /// Values assigned with `@var set` since the interpreter was last cleared
+    /// 
+    /// Only decides how `@var get` reads a variable back when no kind is given.
+    variables: std::collections::HashMap<String, serde_json::Value>,
 }
 
 impl CommandStream {
     /// Create a new command stream with an initialized robot controller
     pub fn new(controller: RobotController) -> Self {
+        let clear_buffer_limit = controller.interpreter_config().clear_buffer_limit();
         Self {
             controller: Some(controller),
             shared_controller: None,
             shutdown_signal: None,
             command_count: 0,
+            clear_buffer_limit,
+            accepted_count: 0,
             pending_commands: Vec::new(),
             eof_logged: false,
             inside_brace_block: false,
+            session_active: false,
+            paused: false,
+            freedrive_active: false,
+            force_mode_active: false,
+            linter: Linter::new(),
+            sent_at: None,
+            group_buffer: None,
+            pending_script: None,
+            pending_repeat: 1,
+            pending_until: None,
+            shutdown_requested: false,
+            drain_deadline: None,
+            command_timeout: None,
+            metrics: None,
+            audit: None,
+            variables: std::collections::HashMap::new(),
         }
     }
     
@@ -199,9 +942,27 @@ impl CommandStream {
             shared_controller: Some(controller),
             shutdown_signal: None,
             command_count: 0,
+            clear_buffer_limit: CLEAR_BUFFER_LIMIT,
+            accepted_count: 0,
             pending_commands: Vec::new(),
             eof_logged: false,
             inside_brace_block: false,
+            session_active: false,
+            paused: false,
+            freedrive_active: false,
+            force_mode_active: false,
+            linter: Linter::new(),
+            sent_at: None,
+            group_buffer: None,
+            pending_script: None,
+            pending_repeat: 1,
+            pending_until: None,
+            shutdown_requested: false,
+            drain_deadline: None,
+            command_timeout: None,
+            metrics: None,
+            audit: None,
+            variables: std::collections::HashMap::new(),
         }
     }
     
@@ -215,12 +976,42 @@ impl CommandStream {
             shared_controller: Some(controller),
             shutdown_signal: Some(shutdown_signal),
             command_count: 0,
+            clear_buffer_limit: CLEAR_BUFFER_LIMIT,
+            accepted_count: 0,
             pending_commands: Vec::new(),
             eof_logged: false,
             inside_brace_block: false,
+            session_active: false,
+            paused: false,
+            freedrive_active: false,
+            force_mode_active: false,
+            linter: Linter::new(),
+            sent_at: None,
+            group_buffer: None,
+            pending_script: None,
+            pending_repeat: 1,
+            pending_until: None,
+            shutdown_requested: false,
+            drain_deadline: None,
+            command_timeout: None,
+            metrics: None,
+            audit: None,
+            variables: std::collections::HashMap::new(),
         }
     }
     
+    /// Override the per-command completion timeout from configuration
+    pub fn with_command_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.command_timeout = timeout;
+        self
+    }
+    
+    /// Record command execution statistics into `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<CommandMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+    
     /// Get mutable access to controller (for owned case)
     async fn with_controller_mut<F, R>(&mut self, f: F) -> Result<R>
     where
@@ -230,9 +1021,9 @@ impl CommandStream {
             f(controller)
         } else if let Some(ref shared) = self.shared_controller {
             let mut guard = shared.lock().await;
-            f(&mut *guard)
+            f(&mut guard)
         } else {
-            Err(anyhow::anyhow!("No controller available"))
+            Err(crate::URError::NotReady("No controller available".to_string()).into())
         }
     }
     
@@ -244,24 +1035,77 @@ impl CommandStream {
     pub async fn run(&mut self) -> Result<()> {
         info!("Command streaming active - Enter URScript commands");
         info!("Commands will be executed sequentially with completion tracking");
-        info!("Use Ctrl+C to abort immediately");
+        info!("Use Ctrl+C to abort immediately, SIGTERM to finish sent commands and exit");
+        
+        self.run_input(BufReader::new(io::stdin()), false).await
+    }
+    
+    /// Execute a URScript file line by line, returning once the end is reached
+    /// 
+    /// The file is streamed, so size is not limited by memory. With `group` the
+    /// whole file is submitted as one command, as if wrapped in `@group begin`/`@group end`.
+    /// Sentinel lines in the file are handled as usual.
+    pub async fn run_script(&mut self, path: &str, group: bool) -> Result<()> {
+        let file = tokio::fs::File::open(path).await
+            .with_context(|| format!("Failed to open {}", path))?;
+        info!("Executing {}{}", path, if group { " as a single group" } else { "" });
+        
+        if group {
+            let (begin, end): (&[u8], &[u8]) = (b"@group begin\n", b"\n@group end\n");
+            self.run_input(BufReader::new(begin.chain(file).chain(end)), true).await
+        } else {
+            self.run_input(BufReader::new(file), true).await
+        }
+    }
+    
+    /// Read and execute commands from `reader`
+    /// 
+    /// Stdin keeps waiting at end of input for more commands; scripts
+    /// (`stop_at_eof`) finish there.
+    async fn run_input<R: AsyncBufRead + Unpin>(&mut self, mut reader: R, stop_at_eof: bool) -> Result<()> {
+        // Pick up the configured clear interval (the shared controller can't be read at construction)
+        self.clear_buffer_limit = self.with_controller_mut(|controller| {
+            Ok(controller.interpreter_config().clear_buffer_limit())
+        }).await?;
+        info!("Interpreter buffer will be cleared every {} commands", self.clear_buffer_limit);
         
-        // Set up async stdin reader
-        let stdin = io::stdin();
-        let mut reader = BufReader::new(stdin);
-        let mut buffer = String::new();
+        if let Some(audit_config) = self.with_controller_mut(|controller| {
+            Ok(controller.daemon_config().audit.clone())
+        }).await? {
+            info!("Auditing commands to {}", audit_config.path);
+            self.audit = Some(AuditLog::new(&audit_config));
+        }
+        
+        // read_until keeps a partly read line when another select branch (SIGHUP) wins
+        let mut buffer = Vec::new();
         
         // Set up signal handlers
         let shutdown = Self::setup_shutdown_handler();
         tokio::pin!(shutdown);
+        #[cfg(unix)]
+        let mut hangup = signal::unix::signal(signal::unix::SignalKind::hangup())
+            .map_err(|e| error!("Failed to install SIGHUP handler, config reload only via @reload: {}", e))
+            .ok();
+        #[cfg(not(unix))]
+        let mut hangup = None;
         
         loop {
-            buffer.clear();
+            if self.drain_deadline.is_some() {
+                self.drain().await?;
+                break;
+            }
             
             tokio::select! {
-                // Try to read a line from stdin
-                line_result = reader.read_line(&mut buffer) => {
+                // Try to read a line of input
+                line_result = reader.read_until(b'\n', &mut buffer) => {
                     match line_result {
+                        Ok(0) if stop_at_eof => {
+                            if self.group_buffer.is_some() {
+                                error!("End of script inside @group - collected lines were not executed");
+                            }
+                            info!("End of script reached");
+                            break;
+                        }
                         Ok(0) => {
                             // EOF reached - log once, then continue silently
                             if !self.eof_logged {
@@ -277,7 +1121,10 @@ impl CommandStream {
                             continue;
                         }
                         Ok(_) => {
-                            let command = buffer.trim();
+                            // A byte order mark (Windows editors) would hide a leading @ or #;
+                            // trim also drops the \r of CRLF line endings
+                            let line = String::from_utf8_lossy(&std::mem::take(&mut buffer)).into_owned();
+                            let command = line.trim_start_matches('\u{feff}').trim();
                             
                             // Reset EOF flag since we got actual input
                             self.eof_logged = false;
@@ -287,6 +1134,14 @@ impl CommandStream {
                                 continue;
                             }
                             
+                            // Inside @group, URScript lines are collected until @group end
+                            if let Some(group) = &mut self.group_buffer {
+                                if !command.starts_with('@') {
+                                    group.push(command.to_string());
+                                    continue;
+                                }
+                            }
+                            
                             // Track braces in the command (after filtering comments)
                             self.update_brace_tracking(command);
                             
@@ -296,7 +1151,7 @@ impl CommandStream {
                                 match self.handle_sentinel_command(command).await {
                                     Ok(command_info) => {
                                         // Sentinel commands don't need completion JSON output since they handle their own
-                                        if matches!(command_info.status, CommandStatus::Failed(ref msg) if msg.contains("shutdown signal")) {
+                                        if matches!(command_info.status, CommandStatus::Failed(FailureReason::ShutdownInterrupted)) {
                                             info!("Command processing interrupted by shutdown signal");
                                             break;
                                         }
@@ -305,38 +1160,34 @@ impl CommandStream {
                                         error!("Sentinel command failed: {}", e);
                                     }
                                 }
-                            } else {
-                                // Handle URScript commands (with buffer management)
-                                match self.process_command(command.to_string()).await {
-                                    Ok(command_info) => {
-                                        // Check if shutdown was signaled during command processing
-                                        if matches!(command_info.status, CommandStatus::Failed(ref msg) if msg.contains("shutdown signal")) {
-                                            info!("Command processing interrupted by shutdown signal");
-                                            break;
-                                        }
-                                        
-                                        json_output::output::command_completed(command_info.id);
-                                        
-                                        // Check if we need to clear the buffer (only for URScript commands and not inside brace blocks)
-                                        if self.command_count % CLEAR_BUFFER_LIMIT == 0 && !self.inside_brace_block {
-                                            self.periodic_clear().await?;
-                                        }
-                                    }
-                                    Err(e) => {
-                                        error!("Command failed: {}", e);
-                                        // Continue with next command even if one fails
-                                    }
-                                }
+                            } else if Self::is_interrupted(&self.execute_urscript(command).await?) {
+                                break;
+                            }
+                            
+                            // A closed @group is submitted as a single command, like @movej/@movel scripts
+                            if !self.run_pending_script().await? {
+                                break;
                             }
                         }
                         Err(e) => {
-                            error!("Failed to read from stdin: {}", e);
+                            error!("Failed to read input: {}", e);
                             break;
                         }
                     }
                 }
+                // Between commands, SIGHUP reloads the live config settings
+                _ = Self::reload_requested(&mut hangup) => {
+                    info!("SIGHUP received - reloading configuration");
+                    self.handle_sentinel_command("@reload").await?;
+                }
                 // Handle shutdown signals immediately
-                _ = &mut shutdown => {
+                kind = &mut shutdown => {
+                    if kind == ShutdownKind::Drain {
+                        info!("Termination signal received - draining sent commands");
+                        self.drain().await?;
+                        break;
+                    }
+                    
                     info!("Shutdown signal received - sending immediate abort");
                     
                     // Signal global shutdown immediately
@@ -344,29 +1195,7 @@ impl CommandStream {
                         signal.store(true, Ordering::Relaxed);
                     }
                     
-                    // Send immediate abort through primary socket (bypasses interpreter queue)
-                    let abort_result = self.with_controller_mut(|controller| {
-                        controller.emergency_abort()
-                    }).await;
-                    
-                    if let Err(e) = abort_result {
-                        error!("Failed to send emergency abort: {}", e);
-                        
-                        // Fallback to interpreter abort if primary socket fails
-                        let fallback_result = self.with_controller_mut(|controller| {
-                            controller.interpreter_mut().and_then(|interpreter| {
-                                interpreter.abort_move()
-                            })
-                        }).await;
-                        
-                        if let Ok(abort_id) = fallback_result {
-                            json_output::output::command_sent(abort_id, "abort");
-                            info!("Fallback interpreter abort sent (ID: {})", abort_id);
-                        }
-                    } else {
-                        // Output JSON for emergency abort (use ID 0 since primary socket doesn't return ID)
-                        json_output::output::command_sent(0, "emergency_abort");
-                    }
+                    self.send_emergency_abort().await;
                     
                     // Exit immediately to avoid terminal state issues
                     drop(reader);
@@ -381,8 +1210,257 @@ impl CommandStream {
         Ok(())
     }
     
+    /// Whether a shutdown signal interrupted the command and the stream should stop
+    fn is_interrupted(status: &CommandStatus) -> bool {
+        matches!(status, CommandStatus::Failed(FailureReason::ShutdownInterrupted))
+    }
+    
+    /// Run the URScript a sentinel left in `pending_script`, `pending_repeat` times
+    /// 
+    /// Each iteration is a separate command with its own accepted/completed
+    /// events; the first one that doesn't complete ends the repeat. With
+    /// `pending_until` the condition is read back after every iteration and
+    /// the repeat ends once it holds. Returns false when the stream should stop.
+    async fn run_pending_script(&mut self) -> Result<bool> {
+        let Some(script) = self.pending_script.take() else { return Ok(true) };
+        let iterations = std::mem::replace(&mut self.pending_repeat, 1);
+        let until = self.pending_until.take();
+        self.update_brace_tracking(&script);
+        
+        for iteration in 1..=iterations {
+            if iterations > 1 {
+                json_output::output_event(&serde_json::json!({
+                    "timestamp": json_output::current_timestamp(),
+                    "type": "group_iteration",
+                    "iteration": iteration,
+                    "iterations": iterations,
+                    "until": until,
+                }));
+            }
+            
+            let status = self.execute_urscript(&script).await?;
+            if Self::is_interrupted(&status) {
+                return Ok(false);
+            }
+            if !matches!(status, CommandStatus::Completed) {
+                if iteration < iterations || until.is_some() {
+                    let message = format!("Group repeat stopped after iteration {} of {}", iteration, iterations);
+                    error!("{}", message);
+                    json_output::output::error(json_output::ErrorEvent::new(&message, None));
+                }
+                break;
+            }
+            
+            let Some(condition) = &until else { continue };
+            // Parenthesized so the condition reads back as one boolean expression
+            match self.read_variable(&format!("({})", condition), VarKind::Bool).await {
+                Ok(serde_json::Value::Bool(true)) => {
+                    info!("Group until condition '{}' met after {} iteration(s)", condition, iteration);
+                    json_output::output_event(&serde_json::json!({
+                        "timestamp": json_output::current_timestamp(),
+                        "type": "group_until",
+                        "condition": condition,
+                        "met": true,
+                        "iterations": iteration,
+                    }));
+                    return Ok(true);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Group until condition '{}' could not be read: {}", condition, e);
+                    json_output::output::error(json_output::ErrorEvent::from_error(
+                        &format!("Group until condition '{}' could not be read", condition),
+                        &e
+                    ));
+                    return Ok(true);
+                }
+            }
+            
+            if iteration == iterations {
+                let message = format!("Group until condition '{}' not met after {} iterations", condition, iterations);
+                error!("{}", message);
+                json_output::output::error(json_output::ErrorEvent::new(&message, None).with_error_code("TIMEOUT"));
+                json_output::output_event(&serde_json::json!({
+                    "timestamp": json_output::current_timestamp(),
+                    "type": "group_until",
+                    "condition": condition,
+                    "met": false,
+                    "iterations": iteration,
+                }));
+            }
+        }
+        Ok(true)
+    }
+    
+    /// Why URScript (and interpreter writes such as `@io set`) can't run now, if anything
+    /// 
+    /// Covers `@pause` and the controller's checks (fault hold, robot not running).
+    async fn urscript_blocked_reason(&mut self) -> Result<Option<String>> {
+        if self.paused {
+            return Ok(Some("Robot paused - send @resume before further commands".to_string()));
+        }
+        self.with_controller_mut(|controller| {
+            controller.check_rtde_watchdog();
+            Ok(controller.command_blocked_reason())
+        }).await
+    }
+    
+    /// Execute one URScript command with status output, metrics and buffer management
+    /// 
+    /// Returns the command's final status; `ShutdownInterrupted` means the
+    /// stream should stop.
+    async fn execute_urscript(&mut self, command: &str) -> Result<CommandStatus> {
+        if self.paused {
+            // Hold URScript while paused; sentinels (queries, @resume) still run
+            error!("Robot paused - refusing command: {}", command);
+            let message = "Robot paused - send @resume before further commands";
+            json_output::output::error(json_output::ErrorEvent::new(message, None).with_error_code("INVALID_REQUEST"));
+            return Ok(CommandStatus::Failed(FailureReason::InvalidRequest { message: message.to_string() }));
+        }
+        
+        // Acknowledge receipt before the (potentially long) execution wait
+        self.accepted_count += 1;
+        json_output::output::command_accepted(self.accepted_count, command);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_accepted();
+        }
+        
+        // Every log line emitted while this command runs carries the sequence
+        // number from the `accepted` event
+        let span = tracing::info_span!("command", sequence = self.accepted_count);
+        self.run_urscript(command).instrument(span).await
+    }
+    
+    /// Execute an accepted URScript command and report its outcome
+    async fn run_urscript(&mut self, command: &str) -> Result<CommandStatus> {
+        // Handle URScript commands (with buffer management)
+        let started = std::time::Instant::now();
+        self.sent_at = None;
+        match self.process_command_with_retry(command.to_string()).await {
+            Ok(command_info) => {
+                self.record_metrics(&command_info.status, started.elapsed());
+                self.record_audit(command, command_info.id, &command_info.status, started);
+                
+                // Check if shutdown was signaled during command processing
+                if Self::is_interrupted(&command_info.status) {
+                    info!("Command processing interrupted by shutdown signal");
+                    return Ok(command_info.status);
+                }
+                
+                if matches!(command_info.status, CommandStatus::Completed) {
+                    json_output::output::command_completed(command_info.id);
+                }
+                
+                // Check if we need to clear the buffer (only for URScript commands)
+                if self.should_auto_clear() {
+                    self.periodic_clear().await?;
+                }
+                Ok(command_info.status)
+            }
+            Err(e) => {
+                error!("Command failed: {}", e);
+                let reason = FailureReason::from_error(&e);
+                let status = CommandStatus::Failed(reason.clone());
+                self.record_metrics(&status, started.elapsed());
+                self.record_audit(command, 0, &status, started);
+                json_output::output::command_failed(0, reason);
+                // Continue with next command even if one fails
+                Ok(status)
+            }
+        }
+    }
+    
+    /// Send an immediate abort through the primary socket, falling back to the interpreter
+    async fn send_emergency_abort(&mut self) {
+        // Primary socket bypasses the interpreter queue
+        let abort_result = self.with_controller_mut(|controller| {
+            controller.emergency_abort()
+        }).await;
+        
+        if let Err(e) = abort_result {
+            error!("Failed to send emergency abort: {}", e);
+            
+            // Fallback to interpreter abort if primary socket fails
+            let fallback_result = self.with_controller_mut(|controller| {
+                controller.interpreter_mut().and_then(|interpreter| {
+                    interpreter.abort_move()
+                })
+            }).await;
+            
+            if let Ok(abort_id) = fallback_result {
+                json_output::output::command_sent(abort_id, "abort");
+                info!("Fallback interpreter abort sent (ID: {})", abort_id);
+            }
+        } else {
+            // Output JSON for emergency abort (use ID 0 since primary socket doesn't return ID)
+            json_output::output::command_sent(0, "emergency_abort");
+        }
+    }
+    
+    /// Stop reading input and let commands already sent to the interpreter finish
+    /// 
+    /// Waits up to `interpreter.drain_timeout_seconds` from the signal, then falls
+    /// back to an emergency abort. Lines still unread on stdin are discarded.
+    async fn drain(&mut self) -> Result<()> {
+        let timeout = match self.drain_deadline.take() {
+            Some(deadline) => deadline.saturating_duration_since(std::time::Instant::now()),
+            None => self.with_controller_mut(|controller| {
+                Ok(controller.interpreter_config().drain_timeout())
+            }).await?,
+        };
+        
+        if let Some(group) = self.group_buffer.take() {
+            error!("Discarding unfinished @group of {} lines", group.len());
+        }
+        
+        let stats = self.get_buffer_stats().await.ok();
+        let remaining = stats.as_ref().map_or(0, |stats| stats.pending_depth);
+        crate::json_output::output_event(&serde_json::json!({
+            "timestamp": crate::json_output::current_timestamp(),
+            "type": "draining",
+            "remaining": remaining,
+            "timeout_seconds": timeout.as_secs(),
+        }));
+        
+        if let Some(stats) = stats.filter(|stats| stats.pending_depth > 0) {
+            match self.wait_for_completion_with_timeout(stats.last_interpreted_id, Some(timeout), None).await? {
+                WaitOutcome::Completed => info!("Drain complete"),
+                WaitOutcome::Interrupted => info!("Drain interrupted"),
+                WaitOutcome::TimedOut => {
+                    error!("Drain timed out after {}s - aborting", timeout.as_secs());
+                    json_output::output::error(json_output::ErrorEvent::new(
+                        &format!("Drain timed out after {}s with commands still executing - aborting", timeout.as_secs()),
+                        None
+                    ).with_error_code("TIMEOUT"));
+                    self.send_emergency_abort().await;
+                }
+            }
+        }
+        
+        if let Some(signal) = &self.shutdown_signal {
+            signal.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+    
+    /// Wait for the next SIGHUP (config reload request); never resolves off Unix
+    #[cfg(unix)]
+    async fn reload_requested(hangup: &mut Option<signal::unix::Signal>) {
+        if let Some(hangup) = hangup {
+            if hangup.recv().await.is_some() {
+                return;
+            }
+        }
+        std::future::pending().await
+    }
+    
+    #[cfg(not(unix))]
+    async fn reload_requested(_hangup: &mut Option<()>) {
+        std::future::pending().await
+    }
+    
     /// Set up signal handlers for graceful shutdown
-    async fn setup_shutdown_handler() {
+    async fn setup_shutdown_handler() -> ShutdownKind {
         let ctrl_c = async {
             signal::ctrl_c()
                 .await
@@ -401,20 +1479,139 @@ impl CommandStream {
         let terminate = std::future::pending::<()>();
 
         tokio::select! {
-            _ = ctrl_c => {},
-            _ = terminate => {},
+            _ = ctrl_c => ShutdownKind::Abort,
+            _ = terminate => ShutdownKind::Drain,
         }
     }
     
     /// Process a single command through the interpreter
     async fn process_command(&mut self, command: String) -> Result<CommandInfo> {
+        // The arm is being moved by hand - motion commands would fight the operator.
+        // Joint moves ignore the force frame, so force mode only allows tool-space motion.
+        let refusal = if self.freedrive_active && is_motion_command(&command) {
+            Some("Freedrive is active - send @freedrive off before motion commands")
+        } else if self.force_mode_active && is_joint_motion_command(&command) {
+            Some("Force mode is active - joint-space motion (movej, speedj, servoj) is refused until @force off")
+        } else {
+            None
+        };
+        if let Some(message) = refusal {
+            let reason = FailureReason::InvalidRequest { message: message.to_string() };
+            error!("{}", reason);
+            json_output::output::command_failed(0, reason.clone());
+            return Ok(CommandInfo {
+                id: 0,
+                command,
+                status: CommandStatus::Failed(reason),
+                termination_id: None,
+            });
+        }
+        
+        // A powered-off or stopped robot would silently never execute the command;
+        // check freshness here too, the monitoring loop only does so on failed reads
+        let blocked = self.with_controller_mut(|controller| {
+            controller.check_rtde_watchdog();
+            Ok(controller.command_blocked_reason())
+        }).await?;
+        if let Some(message) = blocked {
+            let reason = FailureReason::InvalidRequest { message };
+            error!("{}", reason);
+            json_output::output::command_failed(0, reason.clone());
+            return Ok(CommandInfo {
+                id: 0,
+                command,
+                status: CommandStatus::Failed(reason),
+                termination_id: None,
+            });
+        }
+        
+        // Catch typos before the interpreter does (opt-in, warn only unless "reject")
+        let lint_mode = self.with_controller_mut(|controller| {
+            Ok(controller.daemon_config().command.lint())
+        }).await?;
+        if lint_mode != LintMode::Off {
+            let issues: Vec<String> = self.linter.check_line(&command).iter().map(ToString::to_string).collect();
+            if !issues.is_empty() && lint_mode == LintMode::Reject {
+                let reason = FailureReason::InvalidRequest { message: format!("Lint: {}", issues.join("; ")) };
+                error!("{}", reason);
+                json_output::output::command_failed(0, reason.clone());
+                return Ok(CommandInfo {
+                    id: 0,
+                    command,
+                    status: CommandStatus::Failed(reason),
+                    termination_id: None,
+                });
+            }
+            if !issues.is_empty() {
+                info!("Lint warnings for '{}': {}", command.trim(), issues.join("; "));
+                json_output::output_event(&serde_json::json!({
+                    "timestamp": json_output::current_timestamp(),
+                    "type": "lint_warning",
+                    "command": command.trim(),
+                    "issues": issues,
+                }));
+            }
+        }
+        
+        // Enforce configured motion limits before anything reaches the interpreter
+        let safety = self.with_controller_mut(|controller| {
+            Ok(controller.daemon_config().safety.clone())
+        }).await?;
+        let command = match safety.map(|config| check_motion_limits(&command, &config)) {
+            Some(LimitCheck::Rejected(reason)) => {
+                error!("Command exceeds motion limits: {}", reason);
+                json_output::output::command_rejected(command.trim(), &reason);
+                return Ok(CommandInfo {
+                    id: 0,
+                    command,
+                    status: CommandStatus::Failed(FailureReason::Rejected { raw_reply: reason }),
+                    termination_id: None,
+                });
+            }
+            Some(LimitCheck::Clamped { command: clamped, adjustments }) => {
+                for adjustment in &adjustments {
+                    info!("Clamped to motion limit: {}", adjustment);
+                }
+                json_output::output_event(&serde_json::json!({
+                    "timestamp": json_output::current_timestamp(),
+                    "type": "motion_clamped",
+                    "command": command.trim(),
+                    "clamped_command": clamped.trim(),
+                    "adjustments": adjustments,
+                }));
+                clamped
+            }
+            _ => command,
+        };
+        
         // Execute command and get termination token
-        let result = self.with_controller_mut(|controller| {
+        let mut result = self.with_controller_mut(|controller| {
             controller.interpreter_mut()?
                 .execute_command(&command)
                 .context("Failed to execute command")
         }).await?;
         
+        // A full buffer is transient: make room and submit once more. A session
+        // keeps its state in the buffer, so there it is refused as BufferFull.
+        if result.reply == InterpreterReply::BufferFull && self.session_active {
+            info!("Interpreter buffer full during a session - not clearing: {}", command.trim());
+        } else if result.reply == InterpreterReply::BufferFull {
+            info!("Interpreter buffer full - clearing and resubmitting: {}", command.trim());
+            if !self.periodic_clear().await? {
+                return Ok(CommandInfo {
+                    id: 0,
+                    command,
+                    status: CommandStatus::Failed(FailureReason::ShutdownInterrupted),
+                    termination_id: None,
+                });
+            }
+            result = self.with_controller_mut(|controller| {
+                controller.interpreter_mut()?
+                    .execute_command(&command)
+                    .context("Failed to execute command")
+            }).await?;
+        }
+        
         let mut command_info = CommandInfo {
             id: result.id,
             command: command.clone(),
@@ -422,21 +1619,26 @@ impl CommandStream {
             termination_id: None,
         };
         
-        // Check if command was rejected
+        // Check if command was rejected (syntax errors are never retried)
         if result.rejected {
-            // Output JSON for rejected command
-            json_output::output::command_rejected(&command.trim(), &result.raw_reply);
-            command_info.status = CommandStatus::Failed("Command rejected by interpreter".to_string());
+            let reason = match result.reply {
+                InterpreterReply::BufferFull => FailureReason::BufferFull { raw_reply: result.raw_reply.clone() },
+                _ => FailureReason::Rejected { raw_reply: result.raw_reply.clone() },
+            };
+            json_output::output::command_refused(command.trim(), reason.clone());
+            command_info.status = CommandStatus::Failed(reason);
             return Ok(command_info);
         }
         
         // Output JSON for command sent
-        json_output::output::command_sent(result.id, &command.trim());
+        self.sent_at = Some(std::time::Instant::now());
+        json_output::output::command_sent(result.id, command.trim());
         
         // Send termination token
         let termination_result = self.with_controller_mut(|controller| {
+            let token = controller.interpreter_config().termination_token();
             controller.interpreter_mut()?
-                .execute_command("time(0)")
+                .execute_command(&token)
                 .context("Failed to execute termination token")
         }).await?;
         
@@ -446,27 +1648,142 @@ impl CommandStream {
         
         // Wait for command to complete (can be interrupted by Ctrl+C)
         let wait_id = command_info.termination_id.unwrap_or(result.id);
-        let completed = self.wait_for_completion(wait_id).await?;
+        let timeout = self.effective_command_timeout().await;
+        let progress = (command_info.id, self.accepted_count);
+        let outcome = self.wait_for_completion_with_timeout(wait_id, timeout, Some(progress)).await?;
         
-        if completed {
+        if outcome == WaitOutcome::TimedOut {
+            let secs = timeout.map(|t| t.as_secs_f64()).unwrap_or_default();
+            error!("Command {} timed out after {:.1}s - aborting and clearing buffer", wait_id, secs);
+            json_output::output::command_failed(command_info.id, FailureReason::Timeout { seconds: secs });
+            
+            // Stop the hung command so the buffer clear is not queued behind it.
+            // The clear still goes out if the abort fails.
+            let recovery = self.with_controller_mut(|controller| {
+                let interpreter = controller.interpreter_mut()?;
+                Ok((interpreter.abort_move(), interpreter.clear()))
+            }).await;
+            self.variables.clear();
+            match recovery {
+                Ok((abort, clear)) => {
+                    if let Err(e) = abort {
+                        error!("Failed to abort timed-out command: {}", e);
+                    }
+                    if let Err(e) = clear {
+                        error!("Failed to clear buffer after timeout: {}", e);
+                    }
+                }
+                Err(e) => error!("Failed to clear buffer after timeout: {}", e),
+            }
+            
+            command_info.status = CommandStatus::Failed(FailureReason::Timeout { seconds: secs });
+        } else if outcome == WaitOutcome::Completed {
             command_info.status = CommandStatus::Completed;
             self.command_count += 1;
-        } else {
+        } else if self.shutdown_requested {
             // Shutdown was signaled during wait
-            command_info.status = CommandStatus::Failed("Interrupted by shutdown signal".to_string());
+            command_info.status = CommandStatus::Failed(FailureReason::ShutdownInterrupted);
+        } else {
+            // Emergency abort from outside the stream - keep reading input so @reconnect can recover
+            json_output::output::command_failed(command_info.id, FailureReason::EmergencyAbort);
+            self.freedrive_active = false;
+            self.force_mode_active = false;
+            command_info.status = CommandStatus::Failed(FailureReason::EmergencyAbort);
         }
         
         Ok(command_info)
     }
     
+    /// Process a command, reconnecting and resubmitting it after a connection loss
+    /// 
+    /// Opt-in via `command.retry_on_connection_loss`. Only connection-level errors
+    /// are retried: rejections are deterministic, and nothing is retried after an
+    /// emergency halt or shutdown. A command the interpreter already acknowledged
+    /// may have run, so a loss while waiting for it is reported, never resubmitted.
+    async fn process_command_with_retry(&mut self, command: String) -> Result<CommandInfo> {
+        let (enabled, max_attempts, retry_delay) = self.with_controller_mut(|controller| {
+            let config = controller.daemon_config();
+            Ok((
+                config.command.retry_on_connection_loss.unwrap_or(false),
+                config.robot.connection.retry_attempts,
+                config.robot.connection.retry_delay,
+            ))
+        }).await?;
+        
+        let mut attempt = 0;
+        loop {
+            self.sent_at = None;
+            let error = match self.process_command(command.clone()).await {
+                Err(e) if enabled && attempt < max_attempts => e,
+                result => return result,
+            };
+            
+            let halted = self.with_controller_mut(|controller| {
+                Ok(controller.is_emergency_halted())
+            }).await.unwrap_or(true);
+            if self.shutdown_requested || halted || !FailureReason::from_error(&error).is_retryable() {
+                return Err(error);
+            }
+            // Resubmitting could run a move or I/O write twice
+            if self.sent_at.is_some() {
+                return Err(crate::URError::Connection(format!(
+                    "lost after the command was sent, not resubmitted: {:#}", error
+                )).into());
+            }
+            
+            // Back off linearly, retrying the reconnect itself until attempts run out
+            loop {
+                attempt += 1;
+                error!("Connection lost during command ({}), reconnect attempt {}/{}", error, attempt, max_attempts);
+                crate::json_output::output_event(&serde_json::json!({
+                    "timestamp": crate::json_output::current_timestamp(),
+                    "type": "reconnect_attempt",
+                    "attempt": attempt,
+                    "max_attempts": max_attempts,
+                }));
+                sleep(Duration::from_secs_f64(retry_delay.max(0.0) * attempt as f64)).await;
+                
+                match self.attempt_reconnection().await {
+                    Ok(_) => break,
+                    Err(e) if attempt < max_attempts => error!("Reconnect attempt {} failed: {}", attempt, e),
+                    Err(e) => return Err(e.context(format!("Giving up after {} reconnect attempts", attempt))),
+                }
+            }
+            
+            info!("Reconnected - resubmitting command: {}", command.trim());
+            crate::json_output::output_event(&serde_json::json!({
+                "timestamp": crate::json_output::current_timestamp(),
+                "type": "retry_command",
+                "attempt": attempt,
+                "command": command.trim(),
+            }));
+        }
+    }
+    
     /// Wait for a specific command to be executed by the robot
     /// Can be interrupted by shutdown signals for immediate abort
     async fn wait_for_completion(&mut self, command_id: u32) -> Result<bool> {
+        let outcome = self.wait_for_completion_with_timeout(command_id, None, None).await?;
+        Ok(outcome == WaitOutcome::Completed)
+    }
+    
+    /// Wait for a specific command to be executed, giving up after `timeout`
+    /// 
+    /// `progress` is the command ID and sequence to report `progress` events
+    /// for; waits that aren't for a user command (drain, buffer clear) pass None.
+    async fn wait_for_completion_with_timeout(
+        &mut self,
+        command_id: u32,
+        timeout: Option<Duration>,
+        progress: Option<(u32, u32)>,
+    ) -> Result<WaitOutcome> {
         // Don't wait for rejected commands (ID 0)
         if command_id == 0 {
-            return Ok(true);
+            return Ok(WaitOutcome::Completed);
         }
         
+        let deadline = timeout.map(|t| std::time::Instant::now() + t);
+        
         // Get abort signal from interpreter for immediate exit on emergency abort
         let abort_signal = self.with_controller_mut(|controller| {
             Ok(controller.interpreter_mut().ok().map(|interpreter| {
@@ -479,13 +1796,36 @@ impl CommandStream {
         tokio::pin!(shutdown);
         
         // Poll until the command is executed or shutdown is signaled
+        let mut previous_executed = None;
+        let wait_started = std::time::Instant::now();
+        let mut next_progress = wait_started + PROGRESS_INTERVAL;
         loop {
             // Check for emergency abort signal first (fastest exit)
             if let Some(signal) = &abort_signal {
                 if signal.load(std::sync::atomic::Ordering::Relaxed) {
                     info!("Emergency abort detected during command wait - exiting immediately");
-                    return Ok(false);
+                    return Ok(WaitOutcome::Interrupted);
+                }
+            }
+            
+            if deadline.is_some_and(|d| std::time::Instant::now() >= d) {
+                return Ok(WaitOutcome::TimedOut);
+            }
+            
+            // SIGTERM bounds this wait too, whatever the command's own timeout
+            if self.drain_deadline.is_some_and(|d| std::time::Instant::now() >= d) {
+                error!("Drain timed out while command {} was executing - aborting", command_id);
+                json_output::output::error(json_output::ErrorEvent::new(
+                    "Drain timed out with a command still executing - aborting",
+                    None
+                ).with_error_code("TIMEOUT"));
+                self.drain_deadline = None;
+                self.shutdown_requested = true;
+                if let Some(signal) = &self.shutdown_signal {
+                    signal.store(true, Ordering::Relaxed);
                 }
+                self.send_emergency_abort().await;
+                return Ok(WaitOutcome::Interrupted);
             }
             
             tokio::select! {
@@ -495,13 +1835,31 @@ impl CommandStream {
                         let interpreter = controller.interpreter_mut()?;
                         let last_executed = interpreter.get_last_executed_id()
                             .context("Failed to get last executed ID")?;
-                        Ok::<bool, anyhow::Error>(last_executed >= command_id)
+                        Ok::<u32, anyhow::Error>(last_executed)
                     }).await
                 } => {
-                    match completion_result {
-                        Ok(true) => return Ok(true), // Command completed
-                        Ok(false) => {
+                    match completion_result.map(|last_executed| {
+                        (classify_execution(command_id, previous_executed, last_executed), last_executed)
+                    }) {
+                        Ok((ExecutionProgress::Completed, _)) => return Ok(WaitOutcome::Completed),
+                        Ok((ExecutionProgress::Reset { from, to }, _)) => {
+                            // Waiting on would hang on an ID the interpreter will never reach
+                            return Err(crate::URError::RobotState(format!(
+                                "Interpreter executed ID went backwards ({} -> {}) while waiting for command {} - buffer cleared or program restarted",
+                                from, to, command_id
+                            )).into());
+                        }
+                        Ok((ExecutionProgress::Pending, last_executed)) => {
                             // Command not yet completed, continue polling
+                            previous_executed = Some(last_executed);
+                            if let Some((progress_id, sequence)) = progress.filter(|_| std::time::Instant::now() >= next_progress) {
+                                json_output::output::progress(json_output::ProgressEvent::new(
+                                    progress_id,
+                                    sequence,
+                                    wait_started.elapsed(),
+                                ));
+                                next_progress += PROGRESS_INTERVAL;
+                            }
                             sleep(Duration::from_millis(100)).await;
                         }
                         Err(e) => {
@@ -509,7 +1867,7 @@ impl CommandStream {
                             if let Some(signal) = &abort_signal {
                                 if signal.load(std::sync::atomic::Ordering::Relaxed) {
                                     info!("Interpreter error after emergency abort (expected): {}", e);
-                                    return Ok(false);
+                                    return Ok(WaitOutcome::Interrupted);
                                 }
                             }
                             return Err(e);
@@ -517,8 +1875,22 @@ impl CommandStream {
                     }
                 }
                 // Handle shutdown signal
-                _ = &mut shutdown => {
+                kind = &mut shutdown => {
+                    if kind == ShutdownKind::Drain {
+                        // Let this command finish; the stream drains before reading more input
+                        info!("Termination signal during command wait - draining after it completes");
+                        if self.drain_deadline.is_none() {
+                            let timeout = self.with_controller_mut(|controller| {
+                                Ok(controller.interpreter_config().drain_timeout())
+                            }).await?;
+                            self.drain_deadline = Some(std::time::Instant::now() + timeout);
+                        }
+                        shutdown.set(Self::setup_shutdown_handler());
+                        continue;
+                    }
+                    
                     info!("Shutdown signal during command wait - sending abort");
+                    self.shutdown_requested = true;
                     
                     // Signal global shutdown immediately
                     if let Some(signal) = &self.shutdown_signal {
@@ -540,201 +1912,1074 @@ impl CommandStream {
                             })
                         }).await;
                         
-                        if let Ok(abort_id) = fallback_result {
-                            json_output::output::command_sent(abort_id, "abort");
-                            info!("Fallback interpreter abort sent during wait (ID: {})", abort_id);
-                        }
-                    } else {
-                        json_output::output::command_sent(0, "emergency_abort");
+                        if let Ok(abort_id) = fallback_result {
+                            json_output::output::command_sent(abort_id, "abort");
+                            info!("Fallback interpreter abort sent during wait (ID: {})", abort_id);
+                        }
+                    } else {
+                        json_output::output::command_sent(0, "emergency_abort");
+                    }
+                    
+                    return Ok(WaitOutcome::Interrupted);
+                }
+            }
+        }
+    }
+    
+    /// Handle @-based sentinel commands
+    async fn handle_sentinel_command(&mut self, command: &str) -> Result<CommandInfo> {
+        let Some((cmd, args)) = parse_sentinel(command) else {
+            error!("Sentinel command missing a command name");
+            crate::json_output::output::error(
+                crate::json_output::ErrorEvent::new("Missing sentinel command name after '@'", None)
+                    .with_error_code("INVALID_REQUEST")
+                    .with_available(SENTINEL_COMMANDS)
+            );
+            
+            return Ok(CommandInfo {
+                id: 0,
+                command: command.to_string(),
+                status: CommandStatus::Failed(FailureReason::InvalidRequest { message: "Missing sentinel command name".to_string() }),
+                termination_id: None,
+            });
+        };
+        
+        match cmd {
+            "reconnect" => {
+                info!("Executing @reconnect command");
+                
+                // Output JSON notification
+                crate::json_output::output_event(&serde_json::json!({
+                    "timestamp": crate::json_output::current_timestamp(),
+                    "type": "sentinel_command",
+                    "command": "reconnect",
+                    "message": "Manual reconnection requested",
+                }));
+                
+                match self.attempt_reconnection().await {
+                    Ok(_) => {
+                        info!("Manual reconnection successful");
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "reconnection_success",
+                            "message": "Manual reconnection successful",
+                        }));
+                        
+                        Ok(CommandInfo {
+                            id: 0,
+                            command: command.to_string(),
+                            status: CommandStatus::Completed,
+                            termination_id: None,
+                        })
+                    }
+                    Err(e) => {
+                        error!("Manual reconnection failed: {}", e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("Manual reconnection failed", &e));
+                        
+                        Ok(CommandInfo {
+                            id: 0,
+                            command: command.to_string(),
+                            status: CommandStatus::Failed(FailureReason::ConnectionLost { message: format!("Manual reconnection failed: {}", e) }),
+                            termination_id: None,
+                        })
+                    }
+                }
+            }
+            "status" => {
+                info!("Executing @status command");
+                
+                // Interpreter backlog (null if the interpreter can't be queried)
+                let buffer_stats = match self.get_buffer_stats().await {
+                    Ok(stats) => Some(stats),
+                    Err(e) => {
+                        error!("Failed to query interpreter buffer: {}", e);
+                        None
+                    }
+                };
+                
+                // Loaded program (null if the dashboard can't be queried)
+                let program = match self.get_program_state().await {
+                    Ok(program) => Some(program),
+                    Err(e) => {
+                        error!("Failed to query program state: {}", e);
+                        None
+                    }
+                };
+                
+                let status_info = self.with_controller_mut(|controller| {
+                    let state = controller.state();
+                    let is_ready = controller.is_ready();
+                    let host = &controller.config().robot.host;
+                    let robot_status = controller.get_robot_status();
+                    
+                    Ok(serde_json::json!({
+                        "timestamp": crate::json_output::current_timestamp(),
+                        "type": "status",
+                        "robot_state": format!("{:?}", state),
+                        "ready": is_ready,
+                        "host": host,
+                        "robot_info": controller.robot_info(),
+                        "speed_scaling": controller.speed_scaling(),
+                        "robot_mode_name": robot_status.robot_mode_name,
+                        "safety_mode_name": robot_status.safety_mode_name,
+                        "runtime_state_name": robot_status.runtime_state_name,
+                        "last_updated": robot_status.last_updated,
+                        "joint_speeds": robot_status.joint_speeds,
+                        "tcp_speed": robot_status.tcp_speed,
+                        "interpreter": buffer_stats,
+                        "program": program,
+                        "published_events": crate::json_output::publish_counts(),
+                    }))
+                }).await.unwrap_or_else(|_| serde_json::json!({"error": "Failed to get status"}));
+                
+                crate::json_output::output_event(&status_info);
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status: CommandStatus::Completed,
+                    termination_id: None,
+                })
+            }
+            "info" => {
+                info!("Executing @info command");
+                
+                let robot_info = self.with_controller_mut(|controller| {
+                    Ok(controller.robot_info().clone())
+                }).await?;
+                let event = serde_json::json!({
+                    "timestamp": crate::json_output::current_timestamp(),
+                    "type": "robot_info",
+                    "model": robot_info.model,
+                    "serial_number": robot_info.serial_number,
+                    "software_version": robot_info.software_version,
+                });
+                crate::json_output::output_event(&event);
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status: CommandStatus::Completed,
+                    termination_id: None,
+                })
+            }
+            "health" => {
+                info!("Executing @health command");
+                
+                let health_info = self.with_controller_mut(|controller| {
+                    let (interpreter_available, primary_connected, dashboard_connected, monitoring_active) = 
+                        controller.get_connection_health();
+                    
+                    Ok(serde_json::json!({
+                        "timestamp": crate::json_output::current_timestamp(),
+                        "type": "health",
+                        "interpreter": interpreter_available,
+                        "primary_socket": primary_connected,
+                        "dashboard_socket": dashboard_connected,
+                        "monitoring": monitoring_active,
+                        "monitoring_stale": controller.is_monitoring_stale(),
+                        "rtde_age_ms": controller.rtde_data_age().map(|age| age.as_millis() as u64),
+                    }))
+                }).await.unwrap_or_else(|_| serde_json::json!({"error": "Failed to get health info"}));
+                
+                crate::json_output::output_event(&health_info);
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status: CommandStatus::Completed,
+                    termination_id: None,
+                })
+            }
+            "clear" => {
+                info!("Executing @clear command");
+                
+                if self.session_active {
+                    error!("Buffer clear refused while a session is active");
+                    crate::json_output::output::error(crate::json_output::ErrorEvent::new(
+                        "Buffer clear refused while a session is active (use @session end first)",
+                        None
+                    ).with_error_code("INVALID_REQUEST"));
+                    
+                    return Ok(CommandInfo {
+                        id: 0,
+                        command: command.to_string(),
+                        status: CommandStatus::Failed(FailureReason::InvalidRequest { message: "Buffer clear refused while a session is active".to_string() }),
+                        termination_id: None,
+                    });
+                }
+                
+                // Output JSON notification
+                crate::json_output::output_event(&serde_json::json!({
+                    "timestamp": crate::json_output::current_timestamp(),
+                    "type": "sentinel_command",
+                    "command": "clear",
+                    "message": "Manual buffer clear requested",
+                }));
+                
+                // Clear buffer only (no emergency abort)
+                match self.periodic_clear().await {
+                    Ok(false) => Ok(CommandInfo {
+                        id: 0,
+                        command: command.to_string(),
+                        status: CommandStatus::Failed(FailureReason::ShutdownInterrupted),
+                        termination_id: None,
+                    }),
+                    Ok(true) => {
+                        info!("Manual buffer clear successful");
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "clear_success",
+                            "message": "Buffer cleared successfully",
+                        }));
+                        
+                        Ok(CommandInfo {
+                            id: 0,
+                            command: command.to_string(),
+                            status: CommandStatus::Completed,
+                            termination_id: None,
+                        })
+                    }
+                    Err(e) => {
+                        error!("Manual buffer clear failed: {}", e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("Manual buffer clear failed", &e));
+                        
+                        Ok(CommandInfo {
+                            id: 0,
+                            command: command.to_string(),
+                            status: CommandStatus::Failed(FailureReason::from_error(&e)),
+                            termination_id: None,
+                        })
+                    }
+                }
+            }
+            "pose" => {
+                info!("Executing @pose command");
+                
+                let pose = self.with_controller_mut(|controller| {
+                    let robot_status = controller.get_robot_status();
+                    Ok(json_output::PoseEvent::new(robot_status.tcp_pose, robot_status.joint_positions, robot_status.last_updated))
+                }).await;
+                
+                let status = match pose {
+                    Ok(event) => {
+                        json_output::output::pose(event);
+                        CommandStatus::Completed
+                    }
+                    Err(e) => {
+                        error!("@pose failed: {}", e);
+                        json_output::output::error(json_output::ErrorEvent::from_error("@pose failed", &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "session" => {
+                info!("Executing @session command");
+                
+                let status = match args.first().copied() {
+                    Some(action @ ("begin" | "end")) => {
+                        self.session_active = action == "begin";
+                        let message = if self.session_active {
+                            "Session started - interpreter buffer pinned, auto-clear disabled"
+                        } else {
+                            "Session ended - auto-clear re-enabled"
+                        };
+                        info!("{}", message);
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "session",
+                            "active": self.session_active,
+                            "message": message,
+                        }));
+                        CommandStatus::Completed
+                    }
+                    _ => {
+                        let message = "Usage: @session begin|end";
+                        error!("{}", message);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::new(message, None).with_error_code("INVALID_REQUEST"));
+                        CommandStatus::Failed(FailureReason::InvalidRequest { message: message.to_string() })
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "pause" | "resume" => {
+                let pause = cmd == "pause";
+                info!("Executing @{} command", cmd);
+                
+                let action = if pause { ProgramAction::Pause } else { ProgramAction::Play };
+                // @resume also releases a fault hold, once the robot has recovered
+                let fault_cleared = if pause {
+                    Ok(false)
+                } else {
+                    self.with_controller_mut(|controller| controller.clear_fault_hold()).await
+                };
+                let result = match fault_cleared {
+                    // Nothing was paused from here, so there's no program to play; a stray
+                    // play would start whatever .urp happens to be loaded
+                    Ok(true) if !self.paused => Ok(true),
+                    Ok(false) if !pause && !self.paused => Err(crate::URError::InvalidRequest(
+                        "Nothing paused - @resume only continues after @pause or a fault hold".to_string()
+                    ).into()),
+                    Ok(fault_cleared) => self.program_action(&action).await.map(|_| fault_cleared),
+                    Err(e) => Err(e),
+                };
+                let status = match result {
+                    Ok(fault_cleared) => {
+                        self.paused = pause;
+                        // A pause decelerates the arm; report whether it has actually stopped
+                        // (null without joint speeds from RTDE)
+                        let stopped = if pause {
+                            self.wait_until_stopped(PAUSE_STOP_TIMEOUT).await.unwrap_or(None)
+                        } else {
+                            None
+                        };
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "sentinel_command",
+                            "command": cmd,
+                            "paused": self.paused,
+                            "stopped": stopped,
+                            "fault_cleared": fault_cleared,
+                        }));
+                        CommandStatus::Completed
+                    }
+                    Err(e) => {
+                        error!("@{} failed: {}", cmd, e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error(&format!("@{} failed", cmd), &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "load" | "program" => {
+                info!("Executing @{} command", cmd);
+                
+                // @load <name> is shorthand for @program load <name>
+                let action = if cmd == "load" {
+                    match args.as_slice() {
+                        [name] => Some(ProgramAction::Load(name.to_string())),
+                        _ => None,
+                    }
+                } else {
+                    ProgramAction::parse(&args)
+                };
+                
+                let status = match action {
+                    Some(ProgramAction::State) => match self.get_program_state().await {
+                        Ok(program) => {
+                            crate::json_output::output_event(&serde_json::json!({
+                                "timestamp": crate::json_output::current_timestamp(),
+                                "type": "program_state",
+                                "loaded": program.loaded,
+                                "running": program.running,
+                                "state": program.state,
+                            }));
+                            CommandStatus::Completed
+                        }
+                        Err(e) => {
+                            error!("@program state failed: {}", e);
+                            crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@program state failed", &e));
+                            CommandStatus::Failed(FailureReason::from_error(&e))
+                        }
+                    },
+                    Some(action) => match self.program_action(&action).await {
+                        Ok(_) => {
+                            if action == ProgramAction::Stop {
+                                self.paused = false;
+                            }
+                            let event = serde_json::json!({
+                                "timestamp": crate::json_output::current_timestamp(),
+                                "type": "program",
+                                "action": action.name(),
+                                "program": if let ProgramAction::Load(name) = &action { Some(name) } else { None },
+                                "message": "Dashboard program command accepted",
+                            });
+                            crate::json_output::output_event(&event);
+                            CommandStatus::Completed
+                        }
+                        Err(e) => {
+                            error!("@{} failed: {}", cmd, e);
+                            crate::json_output::output::error(crate::json_output::ErrorEvent::from_error(&format!("@{} failed", cmd), &e));
+                            CommandStatus::Failed(FailureReason::from_error(&e))
+                        }
+                    },
+                    None => {
+                        let message = "Usage: @load <name> | @program load <name>|play|stop|state";
+                        error!("{}", message);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::new(message, None).with_error_code("INVALID_REQUEST"));
+                        CommandStatus::Failed(FailureReason::InvalidRequest { message: message.to_string() })
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "io" => {
+                info!("Executing @io command");
+                
+                let result = match IoAction::parse(&args) {
+                    Some(IoAction::Set { pin, value }) => match self.urscript_blocked_reason().await? {
+                        // A write runs on the interpreter, so it is held like URScript
+                        Some(message) => Err(crate::URError::InvalidRequest(message).into()),
+                        None => {
+                            let result = self.with_controller_mut(|controller| {
+                                controller.set_digital_output(pin, value)?;
+                                Ok(serde_json::json!({
+                                    "timestamp": crate::json_output::current_timestamp(),
+                                    "type": "io",
+                                    "action": "set",
+                                    "pin": pin,
+                                    "value": value,
+                                }))
+                            }).await;
+                            if result.is_ok() {
+                                self.command_count += 1;
+                            }
+                            result
+                        }
+                    },
+                    Some(IoAction::Get) => self.with_controller_mut(|controller| {
+                        Ok(serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "io",
+                            "action": "get",
+                            "digital_inputs": controller.get_digital_inputs()?,
+                        }))
+                    }).await,
+                    Some(IoAction::Analog) => self.with_controller_mut(|controller| {
+                        let analog = controller.get_analog_inputs()?;
+                        Ok(serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "io",
+                            "action": "analog",
+                            "standard_analog_inputs": analog.standard,
+                            "tool_analog_inputs": analog.tool,
+                            "tool_output_voltage": analog.tool_output_voltage,
+                        }))
+                    }).await,
+                    None => Err(crate::URError::InvalidRequest(
+                        "Usage: @io set <pin> <on|off> | @io get | @io analog".to_string()
+                    ).into()),
+                };
+                
+                let status = match result {
+                    Ok(event) => {
+                        crate::json_output::output_event(&event);
+                        CommandStatus::Completed
+                    }
+                    Err(e) => {
+                        error!("@io failed: {}", e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@io failed", &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "var" => {
+                info!("Executing @var command");
+                
+                let result = match VarAction::parse(&args) {
+                    Some(VarAction::Set { name, value, literal }) => match self.urscript_blocked_reason().await? {
+                        // The assignment runs on the interpreter, so it is held like URScript
+                        Some(message) => Err(crate::URError::InvalidRequest(message).into()),
+                        None => self.with_controller_mut(|controller| {
+                            controller.set_variable(&name, &literal)
+                        }).await.map(|_| {
+                            // The assignment occupies the interpreter buffer like any statement
+                            self.command_count += 1;
+                            self.variables.insert(name.clone(), value.clone());
+                            ("set", name, value)
+                        }),
+                    },
+                    Some(VarAction::Get { name, kind }) => {
+                        let kind = kind.unwrap_or_else(|| self.variables.get(&name).map_or(VarKind::Float, VarKind::of));
+                        self.read_variable(&name, kind).await.map(|value| ("get", name, value))
+                    }
+                    None => Err(crate::URError::InvalidRequest(
+                        "Usage: @var set <name> <int|float|True|False|[..]|p[..]> | @var get <name> [int|float|bool|pose|list:N]".to_string()
+                    ).into()),
+                };
+                
+                let status = match result {
+                    Ok((action, name, value)) => {
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "var",
+                            "action": action,
+                            "name": name,
+                            "value": value,
+                        }));
+                        CommandStatus::Completed
+                    }
+                    Err(e) => {
+                        error!("@var failed: {}", e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@var failed", &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "speed" => {
+                info!("Executing @speed command");
+                
+                let result = match args.as_slice() {
+                    [fraction] => match fraction.parse::<f64>() {
+                        Ok(fraction) => self.with_controller_mut(|controller| {
+                            controller.set_speed_scaling(fraction)
+                        }).await.map(|_| fraction),
+                        Err(_) => Err(crate::URError::InvalidRequest(
+                            format!("Invalid speed fraction '{}'", fraction)
+                        ).into()),
+                    },
+                    _ => Err(crate::URError::InvalidRequest(
+                        "Usage: @speed <fraction 0.0-1.0>".to_string()
+                    ).into()),
+                };
+                
+                let status = match result {
+                    Ok(fraction) => {
+                        let event = serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "speed",
+                            "speed_scaling": fraction,
+                        });
+                        crate::json_output::output_event(&event);
+                        CommandStatus::Completed
+                    }
+                    Err(e) => {
+                        error!("@speed failed: {}", e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@speed failed", &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "movej" | "movel" => {
+                info!("Executing @{} command", cmd);
+                
+                // Built here, then run like any URScript line (motion limits, completion wait)
+                let pose_target = cmd == "movel";
+                let result = match MoveArgs::parse(&args, pose_target) {
+                    Some(parsed) if pose_target => self.with_controller_mut(|controller| {
+                        Ok(MoveParams::from(&controller.config().robot.movement))
+                    }).await.map(|defaults| motion::movel(parsed.target, &parsed.params(defaults), &parsed.config)),
+                    Some(parsed) => Ok(motion::movej(parsed.target, &parsed.params(MoveParams::joint_defaults()))),
+                    None if pose_target => Err(crate::URError::InvalidRequest(
+                        "Usage: @movel p[x,y,z,rx,ry,rz] [current | [q1,..,q6]] [a=<m/s^2>] [v=<m/s>] [r=<m>]".to_string()
+                    ).into()),
+                    None => Err(crate::URError::InvalidRequest(
+                        "Usage: @movej [q1,..,q6] [a=<rad/s^2>] [v=<rad/s>] [r=<m>]".to_string()
+                    ).into()),
+                };
+                
+                let status = match result {
+                    Ok(script) => {
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "move",
+                            "motion": cmd,
+                            "command": script,
+                        }));
+                        self.pending_script = Some(script);
+                        CommandStatus::Completed
+                    }
+                    Err(e) => {
+                        error!("@{} failed: {}", cmd, e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error(&format!("@{} failed", cmd), &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "ik" => {
+                info!("Executing @ik command");
+                
+                let result = match parse_ik_args(&args) {
+                    Some((pose, config)) => self.inverse_kinematics(pose, &config).await.map(|joints| (pose, joints)),
+                    None => Err(crate::URError::InvalidRequest(
+                        "Usage: @ik p[x,y,z,rx,ry,rz] [current | [q1,..,q6]]".to_string()
+                    ).into()),
+                };
+                
+                let status = match result {
+                    Ok((pose, joints)) => {
+                        let event = serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "ik",
+                            "pose": pose,
+                            "joint_positions": joints,
+                        });
+                        crate::json_output::output_event(&event);
+                        CommandStatus::Completed
+                    }
+                    Err(e) => {
+                        error!("@ik failed: {}", e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@ik failed", &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "reload" => {
+                info!("Executing @reload command");
+                
+                let result = self.with_controller_mut(|controller| controller.reload_config()).await;
+                let status = match result {
+                    Ok(reload) => {
+                        // The stream keeps its own copy of the clear interval
+                        self.clear_buffer_limit = self.with_controller_mut(|controller| {
+                            Ok(controller.interpreter_config().clear_buffer_limit())
+                        }).await?;
+                        
+                        let message = if reload.ignored.is_empty() {
+                            format!("{} changed field(s) applied", reload.applied.len())
+                        } else {
+                            format!("Restart required for: {}", reload.ignored.join(", "))
+                        };
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "config_reload",
+                            "applied": reload.applied,
+                            "ignored": reload.ignored,
+                            "message": message,
+                        }));
+                        CommandStatus::Completed
                     }
-                    
-                    return Ok(false); // Return false to indicate shutdown
-                }
+                    Err(e) => {
+                        error!("@reload failed: {}", e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@reload failed", &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
             }
-        }
-    }
-    
-    /// Handle @-based sentinel commands
-    async fn handle_sentinel_command(&mut self, command: &str) -> Result<CommandInfo> {
-        let parts: Vec<&str> = command[1..].split_whitespace().collect(); // Remove @ and split
-        let cmd = parts.get(0).unwrap_or(&"");
-        
-        match *cmd {
-            "reconnect" => {
-                info!("Executing @reconnect command");
+            "power" => {
+                info!("Executing @power command");
                 
-                // Output JSON notification
-                println!("{{\"timestamp\":{:.6},\"type\":\"sentinel_command\",\"command\":\"reconnect\",\"message\":\"Manual reconnection requested\"}}", 
-                    crate::json_output::current_timestamp());
+                let result = match args.as_slice() {
+                    ["on"] => self.power_on().await,
+                    _ => Err(crate::URError::InvalidRequest("Usage: @power on".to_string()).into()),
+                };
                 
-                match self.attempt_reconnection().await {
-                    Ok(_) => {
-                        info!("Manual reconnection successful");
-                        println!("{{\"timestamp\":{:.6},\"type\":\"reconnection_success\",\"message\":\"Manual reconnection successful\"}}", 
-                            crate::json_output::current_timestamp());
-                        
-                        Ok(CommandInfo {
-                            id: 0,
-                            command: command.to_string(),
-                            status: CommandStatus::Completed,
-                            termination_id: None,
-                        })
+                let status = match result {
+                    Ok(()) => {
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "power",
+                            "powered": true,
+                            "message": "Robot powered on, brakes released and interpreter mode running",
+                        }));
+                        CommandStatus::Completed
                     }
                     Err(e) => {
-                        error!("Manual reconnection failed: {}", e);
-                        crate::json_output::output::error(crate::json_output::ErrorEvent::new(
-                            &format!("Manual reconnection failed: {}", e),
-                            None
-                        ));
-                        
-                        Ok(CommandInfo {
-                            id: 0,
-                            command: command.to_string(),
-                            status: CommandStatus::Failed(format!("Manual reconnection failed: {}", e)),
-                            termination_id: None,
-                        })
+                        error!("@power failed: {}", e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@power failed", &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
                     }
-                }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
             }
-            "status" => {
-                info!("Executing @status command");
+            "monitoring" => {
+                info!("Executing @monitoring command");
                 
-                let status_info = self.with_controller_mut(|controller| {
-                    let state = controller.state();
-                    let is_ready = controller.is_ready();
-                    let host = &controller.config().robot.host;
-                    let robot_status = controller.get_robot_status();
-                    
-                    Ok(format!(
-                        "{{\"timestamp\":{:.6},\"type\":\"status\",\"robot_state\":\"{:?}\",\"ready\":{},\"host\":\"{}\",\"robot_mode_name\":\"{}\",\"safety_mode_name\":\"{}\",\"runtime_state_name\":\"{}\",\"last_updated\":{:.6}}}",
-                        crate::json_output::current_timestamp(),
-                        state,
-                        is_ready,
-                        host,
-                        robot_status.robot_mode_name,
-                        robot_status.safety_mode_name,
-                        robot_status.runtime_state_name,
-                        robot_status.last_updated
-                    ))
-                }).await.unwrap_or_else(|_| "{{\"error\":\"Failed to get status\"}}".to_string());
-                
-                println!("{}", status_info);
+                let result = match args.as_slice() {
+                    [state @ ("on" | "off")] => {
+                        let enabled = *state == "on";
+                        self.set_monitoring_enabled(enabled).await.map(|changed| (enabled, changed))
+                    }
+                    _ => Err(crate::URError::InvalidRequest("Usage: @monitoring on|off".to_string()).into()),
+                };
+                
+                let status = match result {
+                    Ok((enabled, changed)) => {
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "monitoring",
+                            "enabled": enabled,
+                            "changed": changed,
+                            "message": format!("RTDE monitoring {}{}", if enabled { "on" } else { "off" }, if changed { "" } else { " (unchanged)" }),
+                        }));
+                        CommandStatus::Completed
+                    }
+                    Err(e) => {
+                        error!("@monitoring failed: {}", e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@monitoring failed", &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
+                    }
+                };
                 
                 Ok(CommandInfo {
                     id: 0,
                     command: command.to_string(),
-                    status: CommandStatus::Completed,
+                    status,
                     termination_id: None,
                 })
             }
-            "health" => {
-                info!("Executing @health command");
+            "recover" => {
+                info!("Executing @recover command");
                 
-                let health_info = self.with_controller_mut(|controller| {
-                    let (interpreter_available, primary_connected, dashboard_connected, monitoring_active) = 
-                        controller.get_connection_health();
-                    
-                    Ok(format!(
-                        "{{\"timestamp\":{:.6},\"type\":\"health\",\"interpreter\":{},\"primary_socket\":{},\"dashboard_socket\":{},\"monitoring\":{}}}",
-                        crate::json_output::current_timestamp(),
-                        interpreter_available,
-                        primary_connected, 
-                        dashboard_connected,
-                        monitoring_active
-                    ))
-                }).await.unwrap_or_else(|_| "{{\"error\":\"Failed to get health info\"}}".to_string());
-                
-                println!("{}", health_info);
+                let status = match self.recover_robot().await {
+                    Ok(message) => {
+                        let event = serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "recover",
+                            "message": message,
+                        });
+                        crate::json_output::output_event(&event);
+                        CommandStatus::Completed
+                    }
+                    Err(e) => {
+                        error!("@recover failed: {}", e);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@recover failed", &e));
+                        CommandStatus::Failed(FailureReason::from_error(&e))
+                    }
+                };
                 
                 Ok(CommandInfo {
                     id: 0,
                     command: command.to_string(),
-                    status: CommandStatus::Completed,
+                    status,
                     termination_id: None,
                 })
             }
-            "clear" => {
-                info!("Executing @clear command");
+            "group" => {
+                info!("Executing @group command");
                 
-                // Output JSON notification
-                println!("{{\"timestamp\":{:.6},\"type\":\"sentinel_command\",\"command\":\"clear\",\"message\":\"Manual buffer clear requested\"}}", 
-                    crate::json_output::current_timestamp());
+                let result = match (args.first().copied(), self.group_buffer.take()) {
+                    (Some("begin"), None) => {
+                        self.group_buffer = Some(Vec::new());
+                        Ok("Group started - lines are collected until @group end".to_string())
+                    }
+                    (Some("end"), Some(lines)) if !lines.is_empty() => match Self::group_repeat(&args[1..]) {
+                        Some((repeat, until)) => {
+                            // The interpreter needs multi-line constructs (def, loops) on one line
+                            let mut message = format!("Group of {} lines submitted as one command", lines.len());
+                            match &until {
+                                Some(condition) => message.push_str(&format!(", until {} (at most {} times)", condition, repeat)),
+                                None if repeat > 1 => message.push_str(&format!(", {} times", repeat)),
+                                None => {}
+                            }
+                            // Joined lines would fall into a trailing comment
+                            let lines: Vec<&str> = lines.iter()
+                                .map(|line| crate::urscript_lint::strip_comment(line))
+                                .filter(|line| !line.is_empty())
+                                .collect();
+                            self.pending_script = Some(lines.join(" "));
+                            self.pending_repeat = repeat;
+                            self.pending_until = until;
+                            Ok(message)
+                        }
+                        None => {
+                            self.group_buffer = Some(lines);
+                            Err("Usage: @group end [repeat=N | until=<condition> [max=N]] (N from 1 to 1000)")
+                        }
+                    },
+                    (Some("end"), Some(_)) => Err("Empty group discarded"),
+                    (Some("begin"), Some(lines)) => {
+                        self.group_buffer = Some(lines);
+                        Err("Group already open - finish it with @group end")
+                    }
+                    (Some("end"), None) => Err("No group open - start one with @group begin"),
+                    (_, lines) => {
+                        self.group_buffer = lines;
+                        Err("Usage: @group begin|end [repeat=N | until=<condition> [max=N]]")
+                    }
+                };
                 
-                // Clear buffer only (no emergency abort)
-                match self.periodic_clear().await {
-                    Ok(_) => {
-                        info!("Manual buffer clear successful");
-                        println!("{{\"timestamp\":{:.6},\"type\":\"clear_success\",\"message\":\"Buffer cleared successfully\"}}", 
-                            crate::json_output::current_timestamp());
+                let status = match result {
+                    Ok(message) => {
+                        info!("{}", message);
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "group",
+                            "active": self.group_buffer.is_some(),
+                            "message": message,
+                        }));
+                        CommandStatus::Completed
+                    }
+                    Err(message) => {
+                        error!("{}", message);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::new(message, None).with_error_code("INVALID_REQUEST"));
+                        CommandStatus::Failed(FailureReason::InvalidRequest { message: message.to_string() })
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "freedrive" => {
+                info!("Executing @freedrive command");
+                
+                let status = match args.first().copied() {
+                    Some("on") if self.force_mode_active => {
+                        let message = "Force mode is active - send @force off before @freedrive on";
+                        error!("{}", message);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::new(message, None).with_error_code("INVALID_REQUEST"));
+                        CommandStatus::Failed(FailureReason::InvalidRequest { message: message.to_string() })
+                    }
+                    Some(state @ ("on" | "off")) => {
+                        let enable = state == "on";
+                        let script = if enable { "freedrive_mode()" } else { "end_freedrive_mode()" };
+                        // Held like URScript; ending the mode is always let through
+                        let blocked = if enable { self.urscript_blocked_reason().await? } else { None };
+                        let result = match blocked {
+                            Some(message) => Err(crate::URError::InvalidRequest(message).into()),
+                            None => self.with_controller_mut(|controller| {
+                                controller.interpreter_mut()?.execute_command(script)
+                            }).await,
+                        };
                         
-                        Ok(CommandInfo {
-                            id: 0,
-                            command: command.to_string(),
-                            status: CommandStatus::Completed,
-                            termination_id: None,
-                        })
+                        match result {
+                            Ok(result) if !result.rejected => {
+                                self.freedrive_active = enable;
+                                crate::json_output::output_event(&serde_json::json!({
+                                    "timestamp": crate::json_output::current_timestamp(),
+                                    "type": "freedrive",
+                                    "active": self.freedrive_active,
+                                }));
+                                CommandStatus::Completed
+                            }
+                            Ok(result) => {
+                                json_output::output::command_rejected(script, &result.raw_reply);
+                                CommandStatus::Failed(FailureReason::Rejected { raw_reply: result.raw_reply })
+                            }
+                            Err(e) => {
+                                error!("@freedrive failed: {}", e);
+                                crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@freedrive failed", &e));
+                                CommandStatus::Failed(FailureReason::from_error(&e))
+                            }
+                        }
                     }
-                    Err(e) => {
-                        error!("Manual buffer clear failed: {}", e);
+                    _ => {
+                        let message = "Usage: @freedrive on|off";
+                        error!("{}", message);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::new(message, None).with_error_code("INVALID_REQUEST"));
+                        CommandStatus::Failed(FailureReason::InvalidRequest { message: message.to_string() })
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "force" => {
+                info!("Executing @force command");
+                
+                let usage = "Usage: @force on p[x,y,z,rx,ry,rz] [s1,..,s6] [Fx,Fy,Fz,Tx,Ty,Tz] <type 1-3> [l1,..,l6] | @force off";
+                let enable = args.first() == Some(&"on");
+                let script = match args.split_first() {
+                    Some((&"on", _)) if self.freedrive_active => Err("Freedrive is active - send @freedrive off before @force on".to_string()),
+                    Some((&"on", rest)) => match parse_force_args(rest) {
+                        Some(mode) => mode.validate()
+                            .map(|_| motion::force_mode(&mode))
+                            .map_err(|e| format!("@force on: {}", e)),
+                        None => Err(usage.to_string()),
+                    },
+                    Some((&"off", [])) => Ok(motion::END_FORCE_MODE.to_string()),
+                    _ => Err(usage.to_string()),
+                };
+                
+                let status = match script {
+                    Ok(script) => {
+                        // Held like URScript; ending the mode is always let through
+                        let blocked = if enable { self.urscript_blocked_reason().await? } else { None };
+                        let result = match blocked {
+                            Some(message) => Err(crate::URError::InvalidRequest(message).into()),
+                            None => self.with_controller_mut(|controller| {
+                                controller.interpreter_mut()?.execute_command(&script)
+                            }).await,
+                        };
+                        
+                        match result {
+                            Ok(result) if !result.rejected => {
+                                self.force_mode_active = enable;
+                                crate::json_output::output_event(&serde_json::json!({
+                                    "timestamp": crate::json_output::current_timestamp(),
+                                    "type": "force_mode",
+                                    "active": self.force_mode_active,
+                                    "command": script,
+                                }));
+                                CommandStatus::Completed
+                            }
+                            Ok(result) => {
+                                json_output::output::command_rejected(&script, &result.raw_reply);
+                                CommandStatus::Failed(FailureReason::Rejected { raw_reply: result.raw_reply })
+                            }
+                            Err(e) => {
+                                error!("@force failed: {}", e);
+                                crate::json_output::output::error(crate::json_output::ErrorEvent::from_error("@force failed", &e));
+                                CommandStatus::Failed(FailureReason::from_error(&e))
+                            }
+                        }
+                    }
+                    Err(message) => {
+                        error!("{}", message);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::new(&message, None).with_error_code("INVALID_REQUEST"));
+                        CommandStatus::Failed(FailureReason::InvalidRequest { message })
+                    }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
+            }
+            "history" => {
+                info!("Executing @history command");
+                
+                let limit = match args.as_slice() {
+                    [] => Ok(10),
+                    [limit] => limit.parse::<usize>()
+                        .map_err(|_| format!("Invalid history limit '{}'", limit)),
+                    _ => Err("Usage: @history [limit]".to_string()),
+                };
+                
+                let status = match (limit, &self.audit) {
+                    (Ok(limit), Some(audit)) => {
+                        let event = serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "history",
+                            "entries": audit.recent(limit),
+                        });
+                        crate::json_output::output_event(&event);
+                        CommandStatus::Completed
+                    }
+                    (limit, _) => {
+                        let message = limit.err()
+                            .unwrap_or_else(|| "Audit log not configured - add an audit section to the config".to_string());
+                        error!("@history failed: {}", message);
                         crate::json_output::output::error(crate::json_output::ErrorEvent::new(
-                            &format!("Manual buffer clear failed: {}", e),
+                            &format!("@history failed: {}", message),
                             None
-                        ));
-                        
-                        Ok(CommandInfo {
-                            id: 0,
-                            command: command.to_string(),
-                            status: CommandStatus::Failed(format!("Manual buffer clear failed: {}", e)),
-                            termination_id: None,
-                        })
+                        ).with_error_code("INVALID_REQUEST"));
+                        CommandStatus::Failed(FailureReason::InvalidRequest { message })
                     }
-                }
+                };
+                
+                Ok(CommandInfo {
+                    id: 0,
+                    command: command.to_string(),
+                    status,
+                    termination_id: None,
+                })
             }
-            "pose" => {
-                info!("Executing @pose command");
+            "errors" => {
+                info!("Executing @errors command");
                 
-                let pose_info = self.with_controller_mut(|controller| {
-                    let robot_status = controller.get_robot_status();
-                    let tcp_pose = robot_status.tcp_pose;
-                    
-                    // Extract position and rotation
-                    let [x, y, z, rx, ry, rz] = tcp_pose;
-                    
-                    // Calculate pointing direction and angles
-                    let direction = rotvec_to_direction_vector(rx, ry, rz);
-                    let (azimuth, elevation) = direction_to_azimuth_elevation(direction);
-                    
-                    Ok(format!(
-                        "{{\"timestamp\":{:.6},\"type\":\"pose\",\"position\":{{\"x\":{:.3},\"y\":{:.3},\"z\":{:.3}}},\"rotation_vector\":{{\"rx\":{:.6},\"ry\":{:.6},\"rz\":{:.6}}},\"pointing_direction\":{{\"x\":{:.6},\"y\":{:.6},\"z\":{:.6}}},\"azimuth_deg\":{:.1},\"elevation_deg\":{:.1},\"joint_positions\":[{:.4},{:.4},{:.4},{:.4},{:.4},{:.4}],\"last_updated\":{:.6}}}",
-                        crate::json_output::current_timestamp(),
-                        x, y, z,
-                        rx, ry, rz,
-                        direction[0], direction[1], direction[2],
-                        azimuth, elevation,
-                        robot_status.joint_positions[0],
-                        robot_status.joint_positions[1], 
-                        robot_status.joint_positions[2],
-                        robot_status.joint_positions[3],
-                        robot_status.joint_positions[4],
-                        robot_status.joint_positions[5],
-                        robot_status.last_updated
-                    ))
-                }).await.unwrap_or_else(|_| "{{\"error\":\"Failed to get pose\"}}".to_string());
-                
-                println!("{}", pose_info);
+                let limit = match args.as_slice() {
+                    [] => Ok(10),
+                    [limit] => limit.parse::<usize>().map_err(|_| format!("Invalid error limit '{}'", limit)),
+                    _ => Err("Usage: @errors [limit]".to_string()),
+                };
+                let status = match limit {
+                    Ok(limit) => {
+                        crate::json_output::output_event(&serde_json::json!({
+                            "timestamp": crate::json_output::current_timestamp(),
+                            "type": "recent_errors",
+                            "errors": crate::json_output::recent_errors(limit),
+                        }));
+                        CommandStatus::Completed
+                    }
+                    Err(message) => {
+                        error!("@errors failed: {}", message);
+                        crate::json_output::output::error(crate::json_output::ErrorEvent::new(
+                            &format!("@errors failed: {}", message),
+                            None
+                        ).with_error_code("INVALID_REQUEST"));
+                        CommandStatus::Failed(FailureReason::InvalidRequest { message })
+                    }
+                };
                 
                 Ok(CommandInfo {
                     id: 0,
                     command: command.to_string(),
-                    status: CommandStatus::Completed,
+                    status,
                     termination_id: None,
                 })
             }
             "help" => {
                 info!("Executing @help command");
                 
-                println!("{{\"timestamp\":{:.6},\"type\":\"help\",\"commands\":[\"@reconnect\",\"@status\",\"@health\",\"@clear\",\"@pose\",\"@help\"],\"message\":\"Available urd sentinel commands\"}}", 
-                    crate::json_output::current_timestamp());
+                crate::json_output::output_event(&serde_json::json!({
+                    "timestamp": crate::json_output::current_timestamp(),
+                    "type": "help",
+                    "commands": SENTINEL_COMMANDS,
+                    "message": "Available urd sentinel commands",
+                }));
                 
                 Ok(CommandInfo {
                     id: 0,
@@ -745,13 +2990,16 @@ impl CommandStream {
             }
             _ => {
                 error!("Unknown sentinel command: {}", cmd);
-                println!("{{\"timestamp\":{:.6},\"type\":\"error\",\"message\":\"Unknown sentinel command: {}\",\"available\":[\"@reconnect\",\"@status\",\"@health\",\"@clear\",\"@pose\",\"@help\"]}}", 
-                    crate::json_output::current_timestamp(), cmd);
+                crate::json_output::output::error(
+                    crate::json_output::ErrorEvent::new(&format!("Unknown sentinel command: {}", cmd), None)
+                        .with_error_code("UNKNOWN_COMMAND")
+                        .with_available(SENTINEL_COMMANDS)
+                );
                 
                 Ok(CommandInfo {
                     id: 0,
                     command: command.to_string(),
-                    status: CommandStatus::Failed(format!("Unknown sentinel command: {}", cmd)),
+                    status: CommandStatus::Failed(FailureReason::InvalidRequest { message: format!("Unknown sentinel command: {}", cmd) }),
                     termination_id: None,
                 })
             }
@@ -800,8 +3048,60 @@ impl CommandStream {
         }
     }
     
+    /// Update execution metrics with the outcome of a URScript command
+    fn record_metrics(&self, status: &CommandStatus, elapsed: Duration) {
+        let Some(metrics) = &self.metrics else { return };
+        match status {
+            CommandStatus::Completed => metrics.record_completed(elapsed),
+            CommandStatus::Failed(FailureReason::Rejected { .. } | FailureReason::BufferFull { .. }) => metrics.record_rejected(),
+            CommandStatus::Failed(_) => metrics.record_failed(),
+            CommandStatus::Sent => {}
+        }
+    }
+    
+    /// Append the outcome of the current URScript command to the audit log
+    fn record_audit(&mut self, command: &str, command_id: u32, status: &CommandStatus, started: std::time::Instant) {
+        let sequence = self.accepted_count;
+        let sent_after = self.sent_at.map(|sent| sent.saturating_duration_since(started));
+        if let Some(audit) = &mut self.audit {
+            audit.record(AuditEntry::new(sequence, command, command_id, status, started.elapsed(), sent_after));
+        }
+    }
+    
+    /// Per-command completion timeout, if any applies to the next command
+    /// 
+    /// The `--timeout-secs` override wins over `interpreter.execution_timeout_seconds`.
+    /// Inside brace blocks no timeout applies unless `timeout_inside_blocks` is set,
+    /// since a block may legitimately run for a long time.
+    async fn effective_command_timeout(&mut self) -> Option<Duration> {
+        let interpreter_config = self.with_controller_mut(|controller| {
+            Ok(controller.interpreter_config())
+        }).await.ok()?;
+        
+        if self.inside_brace_block && !interpreter_config.timeout_inside_blocks() {
+            return None;
+        }
+        
+        self.command_timeout.or_else(|| interpreter_config.execution_timeout())
+    }
+    
+    /// Check whether the interpreter buffer should be cleared after a URScript command
+    /// 
+    /// Clearing is deferred inside brace blocks and while a session is active, since
+    /// either would lose interpreter state the following commands rely on.
+    fn should_auto_clear(&self) -> bool {
+        self.command_count.is_multiple_of(self.clear_buffer_limit)
+            && !self.inside_brace_block
+            && !self.session_active
+    }
+    
     /// Attempt reconnection to the robot
     async fn attempt_reconnection(&mut self) -> Result<()> {
+        // A fresh interpreter program starts without freedrive, force mode or variables
+        self.freedrive_active = false;
+        self.force_mode_active = false;
+        self.variables.clear();
+        
         // We need to handle the async reconnection outside the closure
         if let Some(ref shared) = self.shared_controller {
             let mut guard = shared.lock().await;
@@ -809,13 +3109,184 @@ impl CommandStream {
         } else if let Some(ref mut controller) = self.controller {
             controller.reconnect().await
         } else {
-            Err(anyhow::anyhow!("No controller available for reconnection"))
+            Err(crate::URError::NotReady("No controller available for reconnection".to_string()).into())
+        }
+    }
+    
+    /// Run a dashboard program action on the controller
+    async fn program_action(&mut self, action: &ProgramAction) -> Result<()> {
+        async fn run(controller: &mut RobotController, action: &ProgramAction) -> Result<()> {
+            match action {
+                ProgramAction::Load(name) => controller.load_program(name).await,
+                ProgramAction::Play => controller.play_program().await,
+                ProgramAction::Stop => controller.stop_program().await,
+                ProgramAction::Pause => controller.pause_program().await,
+                ProgramAction::State => controller.get_program_state().await.map(|_| ()),
+            }
+        }
+        
+        if let Some(ref shared) = self.shared_controller {
+            let mut guard = shared.lock().await;
+            run(&mut guard, action).await
+        } else if let Some(ref mut controller) = self.controller {
+            run(controller, action).await
+        } else {
+            Err(crate::URError::NotReady("No controller available".to_string()).into())
+        }
+    }
+    
+    /// Query the dashboard program state on the controller
+    async fn get_program_state(&mut self) -> Result<ProgramState> {
+        if let Some(ref shared) = self.shared_controller {
+            shared.lock().await.get_program_state().await
+        } else if let Some(ref mut controller) = self.controller {
+            controller.get_program_state().await
+        } else {
+            Err(crate::URError::NotReady("No controller available".to_string()).into())
+        }
+    }
+    
+    /// Power on the robot and start interpreter mode on the controller
+    /// 
+    /// As with recovery, the shared controller is not locked while waiting
+    /// for the robot to power on.
+    async fn power_on(&mut self) -> Result<()> {
+        if let Some(ref shared) = self.shared_controller {
+            let power_on = shared.lock().await.power_on_task();
+            let interpreter = power_on.await?;
+            shared.lock().await.complete_power_on(interpreter);
+            Ok(())
+        } else if let Some(ref mut controller) = self.controller {
+            controller.power_on().await
+        } else {
+            Err(crate::URError::NotReady("No controller available".to_string()).into())
+        }
+    }
+    
+    /// Start or stop RTDE monitoring on the controller
+    async fn set_monitoring_enabled(&mut self, enabled: bool) -> Result<bool> {
+        if let Some(ref shared) = self.shared_controller {
+            shared.lock().await.set_monitoring_enabled(enabled).await
+        } else if let Some(ref mut controller) = self.controller {
+            controller.set_monitoring_enabled(enabled).await
+        } else {
+            Err(crate::URError::NotReady("No controller available".to_string()).into())
+        }
+    }
+    
+    /// Parse the optional `repeat=N` or `until=<condition> [max=N]` of `@group end`
+    /// 
+    /// Returns the iteration count (the cap with a condition, 1 when omitted)
+    /// and the condition. The condition runs to the end of the line, bar `max=N`.
+    fn group_repeat(args: &[&str]) -> Option<(u32, Option<String>)> {
+        let count = |n: &str| n.parse().ok().filter(|n| (1..=MAX_GROUP_REPEAT).contains(n));
+        match args {
+            [] => Some((1, None)),
+            [repeat] if repeat.starts_with("repeat=") => Some((count(&repeat["repeat=".len()..])?, None)),
+            [first, ..] if first.starts_with("until=") => {
+                let (max, condition) = match args.split_last() {
+                    Some((last, rest)) if last.starts_with("max=") => (count(&last["max=".len()..])?, rest),
+                    _ => (DEFAULT_UNTIL_ITERATIONS, args),
+                };
+                let condition = condition.join(" ")["until=".len()..].trim().to_string();
+                (!condition.is_empty()).then_some((max, Some(condition)))
+            }
+            _ => None,
+        }
+    }
+    
+    /// Solve inverse kinematics on the controller
+    async fn inverse_kinematics(&mut self, pose: [f64; 6], config: &JointConfiguration) -> Result<[f64; 6]> {
+        let (readback, data) = self.read_back("get_inverse_kin", |sequence, registers| {
+            motion::inverse_kin(pose, config, sequence, registers)
+        }).await?;
+        crate::controller::decode_inverse_kin(&data, readback.sequence, readback.registers)
+    }
+    
+    /// Read an interpreter variable back through the readback registers
+    /// 
+    /// Held like URScript, since the readback script runs on the interpreter.
+    async fn read_variable(&mut self, name: &str, kind: VarKind) -> Result<serde_json::Value> {
+        if let Some(message) = self.urscript_blocked_reason().await? {
+            return Err(crate::URError::InvalidRequest(message).into());
+        }
+        if kind.register_count() > 6 {
+            return Err(crate::URError::InvalidRequest(format!(
+                "{} has more than 6 entries - only lists that fit the readback registers can be read", name
+            )).into());
+        }
+        
+        let (readback, data) = self.read_back(name, |sequence, registers| {
+            kind.readback_script(name, sequence, registers)
+        }).await?;
+        // The script occupies the interpreter buffer like any statement
+        self.command_count += 1;
+        let values = crate::controller::decode_readback(&data, readback.sequence, readback.registers, kind.register_count())?;
+        Ok(kind.decode(&values))
+    }
+    
+    /// Run a register readback script and read the registers it wrote
+    /// 
+    /// The controller is locked to send the script and for each executed-ID
+    /// poll, but not across the wait or the RTDE read, so monitoring, aborts
+    /// and `@reload` carry on meanwhile.
+    async fn read_back(
+        &mut self,
+        what: &str,
+        build: impl FnOnce(i32, (u8, u8)) -> String,
+    ) -> Result<(crate::controller::RegisterReadback, std::collections::HashMap<String, Vec<f64>>)> {
+        let readback = self.with_controller_mut(|controller| controller.send_readback(what, build)).await?;
+        
+        let deadline = tokio::time::Instant::now() + READBACK_EXECUTION_TIMEOUT;
+        while !self.with_controller_mut(|controller| controller.command_executed(readback.command_id)).await? {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(crate::URError::Timeout(format!(
+                    "{} did not execute within {}s", what, READBACK_EXECUTION_TIMEOUT.as_secs()
+                )).into());
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+        
+        let data = readback.read().await?;
+        Ok((readback, data))
+    }
+    
+    /// Wait until RTDE joint speeds fall below `STOPPED_JOINT_SPEED`
+    /// 
+    /// Returns whether the arm stopped within `timeout`, or None when joint
+    /// speeds aren't available. The controller is unlocked between polls so
+    /// the monitoring task can deliver new data.
+    async fn wait_until_stopped(&mut self, timeout: Duration) -> Result<Option<bool>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            match self.with_controller_mut(|controller| Ok(controller.is_moving(STOPPED_JOINT_SPEED))).await? {
+                None => return Ok(None),
+                Some(false) => return Ok(Some(true)),
+                Some(true) if tokio::time::Instant::now() >= deadline => return Ok(Some(false)),
+                Some(true) => tokio::time::sleep(Duration::from_millis(20)).await,
+            }
         }
     }
     
+    /// Run protective stop recovery on the controller
+    /// 
+    /// The shared controller is only locked to start the recovery, not for the
+    /// dashboard polling, so monitoring and aborts carry on meanwhile.
+    async fn recover_robot(&mut self) -> Result<String> {
+        if let Some(ref shared) = self.shared_controller {
+            let recovery = shared.lock().await.recover_task();
+            recovery.await
+        } else if let Some(ref mut controller) = self.controller {
+            controller.recover().await
+        } else {
+            Err(crate::URError::NotReady("No controller available".to_string()).into())
+        }
+    }
     
     /// Periodic buffer clearing to prevent interpreter overflow
-    async fn periodic_clear(&mut self) -> Result<()> {
+    /// 
+    /// Returns false if a shutdown interrupted the wait and nothing was cleared.
+    async fn periodic_clear(&mut self) -> Result<bool> {
         info!("Clearing interpreter buffer after {} commands", self.command_count);
         
         // Output JSON for buffer clear request
@@ -834,7 +3305,7 @@ impl CommandStream {
         if !completed {
             // Shutdown was signaled during wait
             info!("Buffer clear interrupted by shutdown signal");
-            return Ok(());
+            return Ok(false);
         }
         
         // Clear the buffer
@@ -843,11 +3314,29 @@ impl CommandStream {
                 .clear()
                 .context("Failed to clear interpreter buffer")
         }).await?;
+        self.variables.clear();
         
         // Output JSON for buffer clear completion
         json_output::output::buffer_clear_completed(self.command_count, clear_id);
         
-        Ok(())
+        Ok(true)
+    }
+    
+    /// Query the interpreter for its buffer backlog
+    /// 
+    /// A growing `pending_depth` precedes "runtime too much behind" errors.
+    pub async fn get_buffer_stats(&mut self) -> Result<BufferStats> {
+        let (last_interpreted_id, last_executed_id) = self.with_controller_mut(|controller| {
+            let interpreter = controller.interpreter_mut()?;
+            Ok((interpreter.get_last_interpreted_id()?, interpreter.get_last_executed_id()?))
+        }).await?;
+        
+        Ok(BufferStats {
+            last_interpreted_id,
+            last_executed_id,
+            pending_depth: last_interpreted_id.saturating_sub(last_executed_id),
+            urscript_count: self.command_count,
+        })
     }
     
     /// Get statistics about command processing
@@ -855,6 +3344,8 @@ impl CommandStream {
         CommandStats {
             total_commands: self.command_count,
             pending_commands: self.pending_commands.len() as u32,
+            freedrive_active: self.freedrive_active,
+            force_mode_active: self.force_mode_active,
         }
     }
     
@@ -901,5 +3392,18 @@ impl CommandStream {
 pub struct CommandStats {
     pub total_commands: u32,
     pub pending_commands: u32,
+    pub freedrive_active: bool,
+    pub force_mode_active: bool,
+}
+
+/// Interpreter buffer backlog, as reported by @status
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct BufferStats {
+    pub last_interpreted_id: u32,
+    pub last_executed_id: u32,
+    /// Statements interpreted but not yet executed
+    pub pending_depth: u32,
+    /// URScript commands completed since startup
+    pub urscript_count: u32,
 }
 
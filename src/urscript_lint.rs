@@ -0,0 +1,228 @@
+//! URScript Lint
+//!
+//! Lexical pre-check of single URScript lines: balanced parentheses and
+//! brackets, and statement-level calls to functions that are neither URScript
+//! built-ins nor defined earlier with `def`/`thread`. This is not a parser; it
+//! catches the fat-fingered commands that would otherwise only come back as an
+//! interpreter compile error.
+
+use regex::Regex;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// URScript built-in functions recognised at the start of a statement
+pub const BUILTIN_FUNCTIONS: &[&str] = &[
+    // Motion
+    "movej", "movel", "movep", "movec", "servoj", "servoc", "speedj", "speedl", "stopj", "stopl",
+    "force_mode", "end_force_mode", "force_mode_set_damping", "force_mode_set_gain_scaling",
+    "freedrive_mode", "end_freedrive_mode", "teach_mode", "end_teach_mode", "position_deviation_warning",
+    "reset_revolution_counter", "set_pos", "zero_ftsensor",
+    // Robot setup
+    "set_tcp", "set_payload", "set_payload_mass", "set_payload_cog", "set_target_payload", "set_gravity",
+    "set_safety_mode_transition_hardness", "enable_external_ft_sensor",
+    // I/O
+    "set_digital_out", "set_standard_digital_out", "set_tool_digital_out", "set_configurable_digital_out",
+    "set_analog_out", "set_standard_analog_out", "set_tool_voltage", "set_tool_communication",
+    "set_tool_digital_output_mode", "set_standard_analog_input_domain", "set_tool_analog_input_domain",
+    "set_input_actions_to_default", "set_runstate_outputs_to_value",
+    "get_digital_in", "get_digital_out", "get_standard_digital_in", "get_standard_digital_out",
+    "get_tool_digital_in", "get_tool_digital_out", "get_configurable_digital_in", "get_configurable_digital_out",
+    "get_analog_in", "get_analog_out", "get_standard_analog_in", "get_tool_analog_in",
+    // Registers
+    "write_output_integer_register", "write_output_float_register", "write_output_boolean_register",
+    "read_input_integer_register", "read_input_float_register", "read_input_boolean_register",
+    "read_output_integer_register", "read_output_float_register", "read_output_boolean_register",
+    // State and kinematics
+    "get_actual_joint_positions", "get_actual_joint_speeds", "get_actual_tcp_pose", "get_actual_tcp_speed",
+    "get_actual_tool_flange_pose", "get_target_joint_positions", "get_target_joint_speeds", "get_target_tcp_pose",
+    "get_target_tcp_speed", "get_target_payload", "get_tcp_force", "get_tcp_offset", "get_joint_torques",
+    "get_joint_temp", "get_controller_temp", "get_steptime", "get_tool_accelerometer_reading",
+    "get_inverse_kin", "get_inverse_kin_has_solution", "get_forward_kin", "is_steady", "is_within_safety_limits",
+    "pose_add", "pose_sub", "pose_dist", "pose_inv", "pose_trans", "point_dist", "interpolate_pose",
+    "wrench_trans", "rotvec2rpy", "rpy2rotvec",
+    // Math, lists and strings
+    "abs", "acos", "asin", "atan", "atan2", "cos", "sin", "tan", "sqrt", "pow", "log", "floor", "ceil",
+    "norm", "normalize", "d2r", "r2d", "random", "length", "get_list_length", "binary_list_to_integer",
+    "integer_to_binary_list", "to_num", "to_str", "str_at", "str_cat", "str_empty", "str_find", "str_len", "str_sub",
+    // Program flow, messages and sockets
+    "sleep", "sync", "halt", "textmsg", "popup", "varmsg", "rpc_factory",
+    "socket_open", "socket_close", "socket_send_string", "socket_send_line", "socket_send_byte",
+    "socket_send_int", "socket_read_string", "socket_read_ascii_float", "socket_read_binary_integer",
+    "socket_read_byte_list", "socket_get_var", "socket_set_var",
+    // Interpreter mode
+    "interpreter_mode", "end_interpreter", "clear_interpreter", "skipbuffer", "statelessbuffer",
+];
+
+/// Keywords that can be followed by `(` without being a call
+const KEYWORDS: &[&str] = &["if", "elif", "while", "return", "not", "and", "or", "run", "kill", "global", "local"];
+
+fn statement_call_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^([A-Za-z_][A-Za-z0-9_]*)\s*\(").unwrap())
+}
+
+fn definition_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^(?:def|thread)\s+([A-Za-z_][A-Za-z0-9_]*)\s*\(").unwrap())
+}
+
+/// A problem found on one line
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintIssue {
+    /// A `(`/`[` without its closing partner, or the other way round
+    Unbalanced(String),
+    /// A statement calls a function that isn't a built-in or defined earlier
+    UnknownFunction { name: String, suggestion: Option<&'static str> },
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintIssue::Unbalanced(message) => write!(f, "{}", message),
+            LintIssue::UnknownFunction { name, suggestion: Some(suggestion) } => {
+                write!(f, "Unknown function '{}' - did you mean '{}'?", name, suggestion)
+            }
+            LintIssue::UnknownFunction { name, suggestion: None } => {
+                write!(f, "Unknown function '{}' (not a URScript built-in or defined earlier)", name)
+            }
+        }
+    }
+}
+
+/// The line with string literals blanked and any trailing `#` comment removed
+fn code_only(line: &str) -> String {
+    let mut code = String::with_capacity(line.len());
+    let mut in_string = false;
+    for c in line.chars() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => break,
+            _ if in_string => {
+                code.push(' ');
+                continue;
+            }
+            _ => {}
+        }
+        code.push(c);
+    }
+    code
+}
+
+/// The line without its trailing `#` comment (a `#` inside a string literal is kept)
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return line[..index].trim_end(),
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Check that parentheses and square brackets pair up
+fn check_balance(code: &str) -> Option<String> {
+    let mut open = Vec::new();
+    for (column, c) in code.char_indices() {
+        match c {
+            '(' | '[' => open.push(c),
+            ')' | ']' => {
+                let expected = if c == ')' { '(' } else { '[' };
+                if open.pop() != Some(expected) {
+                    return Some(format!("Unbalanced '{}' at column {}", c, column + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    open.last().map(|c| format!("Unclosed '{}'", c))
+}
+
+/// Levenshtein distance, for suggesting the built-in a typo was meant to be
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Lints lines in order, remembering functions defined along the way
+#[derive(Debug, Default)]
+pub struct Linter {
+    defined: HashSet<String>,
+}
+
+impl Linter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Problems found on one line (empty if none)
+    pub fn check_line(&mut self, line: &str) -> Vec<LintIssue> {
+        let code = code_only(line);
+        let code = code.trim();
+        let mut issues = Vec::new();
+
+        if let Some(message) = check_balance(code) {
+            issues.push(LintIssue::Unbalanced(message));
+        }
+
+        if let Some(definition) = definition_regex().captures(code) {
+            self.defined.insert(definition[1].to_string());
+        } else if let Some(call) = statement_call_regex().captures(code) {
+            let name = &call[1];
+            let known = BUILTIN_FUNCTIONS.contains(&name) || KEYWORDS.contains(&name) || self.defined.contains(name);
+            if !known {
+                let suggestion = BUILTIN_FUNCTIONS.iter()
+                    .map(|builtin| (edit_distance(name, builtin), *builtin))
+                    .filter(|(distance, _)| *distance <= 2)
+                    .min_by_key(|(distance, _)| *distance)
+                    .map(|(_, builtin)| builtin);
+                issues.push(LintIssue::UnknownFunction { name: name.to_string(), suggestion });
+            }
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_calls_and_definitions_pass() {
+        let mut linter = Linter::new();
+        assert!(linter.check_line("movej([0, -1.57, 1.57, 0, 0, 0], a=1.4, v=1.05)").is_empty());
+        assert!(linter.check_line("textmsg(\"closing (gripper\")  # string and comment are skipped )").is_empty());
+        assert!(linter.check_line("x = my_helper(1)").is_empty());
+        assert!(linter.check_line("if (x > 1): popup(\"x\") end").is_empty());
+
+        assert_eq!(linter.check_line("approach(0.1)").len(), 1);
+        assert!(linter.check_line("def approach(offset): movel(pose_add(get_actual_tcp_pose(), p[0,0,offset,0,0,0])) end").is_empty());
+        assert!(linter.check_line("approach(0.1)").is_empty());
+    }
+
+    #[test]
+    fn test_typos_and_unbalanced_lines() {
+        let mut linter = Linter::new();
+        assert_eq!(
+            linter.check_line("movj([0,0,0,0,0,0])"),
+            vec![LintIssue::UnknownFunction { name: "movj".to_string(), suggestion: Some("movej") }]
+        );
+        assert_eq!(
+            linter.check_line("movel(p[0.1,0.2,0.3,0,3.1,0)").first().map(|issue| issue.to_string()),
+            Some("Unbalanced ')' at column 28".to_string())
+        );
+        assert_eq!(linter.check_line("set_digital_out(0, True"), vec![LintIssue::Unbalanced("Unclosed '('".to_string())]);
+    }
+}
@@ -0,0 +1,185 @@
+//! URScript Dry-Run Validation
+//!
+//! Checks a script the way the command stream would read it (line by line,
+//! skipping blanks and `#` comments, tracking `{ ... }` blocks) without
+//! touching the robot. Each line is also linted (see `urscript_lint`).
+
+use crate::urscript_lint::{LintIssue, Linter};
+use serde::Serialize;
+
+/// Longest single line sent to the interpreter (conservative limit)
+pub const MAX_LINE_LENGTH: usize = 4096;
+
+/// Diagnostic severity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found on a specific line (1-based, 0 for whole-script issues)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Result of validating a script
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationReport {
+    /// URScript lines that would be sent to the interpreter
+    pub line_count: usize,
+    /// Top-level units: single lines plus whole `{ ... }` blocks
+    pub block_count: usize,
+    /// Whether every `{` has a matching `}`
+    pub brace_balanced: bool,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// True when no error-level diagnostics were found
+    pub fn is_valid(&self) -> bool {
+        !self.diagnostics.iter().any(|d| d.severity == Severity::Error)
+    }
+}
+
+/// Validate a script without executing it
+pub fn validate_urscript(script: &str) -> ValidationReport {
+    let mut report = ValidationReport {
+        line_count: 0,
+        block_count: 0,
+        brace_balanced: true,
+        diagnostics: Vec::new(),
+    };
+    let mut depth: usize = 0;
+    let mut block_start = 0;
+    let mut linter = Linter::new();
+
+    // Tolerate a byte order mark from Windows editors (CRLF is handled by trim)
+    let script = script.strip_prefix('\u{feff}').unwrap_or(script);
+    for (index, raw_line) in script.lines().enumerate() {
+        let line_number = index + 1;
+        let line = raw_line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('@') {
+            report.diagnostics.push(Diagnostic {
+                line: line_number,
+                severity: Severity::Warning,
+                message: format!("Sentinel command '{}' is handled by urd, not the interpreter", line),
+            });
+            continue;
+        }
+
+        report.line_count += 1;
+        if line.len() > MAX_LINE_LENGTH {
+            report.diagnostics.push(Diagnostic {
+                line: line_number,
+                severity: Severity::Error,
+                message: format!("Line is {} characters, exceeding the {} character limit", line.len(), MAX_LINE_LENGTH),
+            });
+        }
+
+        // Unbalanced brackets never compile; an unknown name may be defined outside the script
+        for issue in linter.check_line(line) {
+            report.diagnostics.push(Diagnostic {
+                line: line_number,
+                severity: if matches!(issue, LintIssue::Unbalanced(_)) { Severity::Error } else { Severity::Warning },
+                message: issue.to_string(),
+            });
+        }
+
+        if depth == 0 {
+            report.block_count += 1;
+            block_start = line_number;
+        }
+        for c in line.chars() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => {
+                    report.brace_balanced = false;
+                    report.diagnostics.push(Diagnostic {
+                        line: line_number,
+                        severity: Severity::Error,
+                        message: "Closing brace without a matching opening brace".to_string(),
+                    });
+                }
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    if depth > 0 {
+        report.brace_balanced = false;
+        report.diagnostics.push(Diagnostic {
+            line: block_start,
+            severity: Severity::Error,
+            message: format!("Block opened here is never closed ({} unclosed brace(s))", depth),
+        });
+    }
+
+    if report.line_count == 0 {
+        report.diagnostics.push(Diagnostic {
+            line: 0,
+            severity: Severity::Error,
+            message: "Script contains no URScript commands".to_string(),
+        });
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_script_with_block() {
+        let script = "# approach\nmovej([0,0,0,0,0,0])\n{\nset_digital_out(0, True)\nsleep(0.5)\n}\n\ntextmsg(\"done\")\n";
+        let report = validate_urscript(script);
+
+        assert!(report.is_valid(), "{:?}", report.diagnostics);
+        assert!(report.brace_balanced);
+        assert_eq!(report.line_count, 6);
+        assert_eq!(report.block_count, 3);
+    }
+
+    #[test]
+    fn test_reports_unbalanced_braces_and_long_lines() {
+        let long_line = format!("textmsg(\"{}\")", "x".repeat(MAX_LINE_LENGTH));
+        let script = format!("{{\nmovej([0,0,0,0,0,0])\n{}\n", long_line);
+        let report = validate_urscript(&script);
+
+        assert!(!report.is_valid());
+        assert!(!report.brace_balanced);
+        assert!(report.diagnostics.iter().any(|d| d.line == 3 && d.message.contains("character limit")));
+        assert!(report.diagnostics.iter().any(|d| d.line == 1 && d.message.contains("never closed")));
+
+        let stray = validate_urscript("movej([0,0,0,0,0,0])\n}\n");
+        assert!(!stray.brace_balanced);
+        assert_eq!(stray.diagnostics[0].line, 2);
+    }
+
+    #[test]
+    fn test_lint_diagnostics() {
+        let report = validate_urscript("movj([0,0,0,0,0,0])\nset_digital_out(0, True\n");
+        assert!(!report.is_valid());
+        assert_eq!(report.diagnostics[0].severity, Severity::Warning);
+        assert!(report.diagnostics[0].message.contains("did you mean 'movej'"));
+        assert_eq!(report.diagnostics[1].line, 2);
+        assert_eq!(report.diagnostics[1].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_empty_program_is_invalid() {
+        let report = validate_urscript("# nothing here\n\n@status\n");
+        assert!(!report.is_valid());
+        assert_eq!(report.line_count, 0);
+        assert_eq!(report.diagnostics.len(), 2);
+    }
+}
@@ -0,0 +1,157 @@
+//! End-to-end tests against the mock robot (`test-support` feature)
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use urd::mock::{MockRobot, MOCK_TCP_POSE};
+use urd::{CommandStream, ControllerRobotState, RobotController};
+
+#[tokio::test]
+async fn test_initialize_and_stream_script() {
+    let robot = MockRobot::start().unwrap();
+    let mut controller = RobotController::new(robot.config());
+
+    controller.initialize(false).await.unwrap();
+    assert_eq!(controller.state(), &ControllerRobotState::Running);
+    assert_eq!(controller.robot_info().model, "UR5");
+    assert!(robot.primary_scripts().contains("interpreter_mode()"));
+
+    // A powered-off robot is powered on and its brakes released
+    let dashboard = robot.dashboard_commands();
+    let power_on = dashboard.iter().position(|c| c == "power on").unwrap();
+    let brake_release = dashboard.iter().position(|c| c == "brake release").unwrap();
+    assert!(power_on < brake_release);
+
+    let program = controller.get_program_state().await.unwrap();
+    assert_eq!((program.loaded, program.running, program.state.as_str()), (None, false, "STOPPED"));
+
+    let script = std::env::temp_dir().join(format!("urd-mock-script-{}.script", std::process::id()));
+    std::fs::write(&script, "movej([0,-1.57,1.57,-1.57,-1.57,0])\n@group begin\nx = 1\ny = x + 1\n@group end repeat=2\n@movej [0, 0, 0, 0, 0, 0] v=0.5\n").unwrap();
+
+    let controller = Arc::new(tokio::sync::Mutex::new(controller));
+    let mut stream = CommandStream::new_with_shared_controller(Arc::clone(&controller), Arc::new(AtomicBool::new(false)));
+    stream.run_script(script.to_str().unwrap(), false).await.unwrap();
+    std::fs::remove_file(&script).ok();
+
+    // Each command is followed by the termination token it waits on
+    let sent: Vec<String> = robot.interpreter_commands()
+        .into_iter()
+        .filter(|c| !c.starts_with("state"))
+        .collect();
+    let movej = sent.iter().position(|c| c == "movej([0,-1.57,1.57,-1.57,-1.57,0])").unwrap();
+    assert_eq!(sent[movej + 1], "time(0)");
+    assert_eq!(sent[movej + 2], "x = 1 y = x + 1");
+    assert_eq!(sent[movej + 3], "time(0)");
+    assert_eq!(sent[movej + 4], "x = 1 y = x + 1");
+    assert_eq!(sent[movej + 5], "time(0)");
+    assert_eq!(sent[movej + 6], "movej([0, 0, 0, 0, 0, 0], a=1.4, v=0.5)");
+
+    controller.lock().await.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_script_group_with_inline_comments() {
+    let robot = MockRobot::start().unwrap();
+    let mut controller = RobotController::new(robot.config());
+    controller.initialize(false).await.unwrap();
+
+    // `--script --group`: the whole file is one command, so comments must not swallow later lines
+    let script = std::env::temp_dir().join(format!("urd-mock-group-{}.script", std::process::id()));
+    std::fs::write(&script, "# wave twice\ndef wave(): # defined inline\n  textmsg(\"wave #1\")  # log\nend\nwave()\n").unwrap();
+
+    let controller = Arc::new(tokio::sync::Mutex::new(controller));
+    let mut stream = CommandStream::new_with_shared_controller(Arc::clone(&controller), Arc::new(AtomicBool::new(false)));
+    stream.run_script(script.to_str().unwrap(), true).await.unwrap();
+    std::fs::remove_file(&script).ok();
+
+    let sent: Vec<String> = robot.interpreter_commands()
+        .into_iter()
+        .filter(|c| !c.starts_with("state"))
+        .collect();
+    assert!(sent.iter().any(|c| c == "def wave(): textmsg(\"wave #1\") end wave()"), "{:?}", sent);
+
+    controller.lock().await.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_safe_mode_waits_for_power_on() {
+    let robot = MockRobot::start().unwrap();
+    let mut config = robot.config();
+    config.robot.auto_power_on = Some(false);
+    let mut controller = RobotController::new(config);
+
+    // Connected but not energized, so there is no interpreter yet
+    controller.initialize(false).await.unwrap();
+    assert_eq!(controller.state(), &ControllerRobotState::PowerOff);
+    assert!(!robot.dashboard_commands().iter().any(|c| c == "power on"));
+    let error = controller.interpreter_mut().err().unwrap().to_string();
+    assert!(error.contains("@power on"), "{}", error);
+
+    controller.power_on().await.unwrap();
+    assert!(controller.is_ready());
+    assert!(robot.dashboard_commands().iter().any(|c| c == "brake release"));
+
+    controller.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_rtde_serves_canned_state() {
+    let robot = MockRobot::start().unwrap();
+    let config = robot.config();
+
+    let mut client = urd::RTDEClient::new(&config.robot.host, config.robot.ports.rtde).unwrap();
+    client.connect().unwrap();
+    client.negotiate_protocol_version(2).unwrap();
+    assert!(client.setup_output_recipe(vec!["no_such_variable".to_string()], 125.0).is_err());
+    client.setup_output_recipe(vec!["actual_TCP_pose".to_string(), "robot_mode".to_string()], 125.0).unwrap();
+    client.start_data_synchronization().unwrap();
+
+    let data = client.read_data_package().unwrap();
+    assert_eq!(data["actual_TCP_pose"], MOCK_TCP_POSE.to_vec());
+    assert_eq!(data["robot_mode"], vec![3.0]);
+}
+
+#[tokio::test]
+async fn test_rtde_recipe_skips_unknown_variables() {
+    let robot = MockRobot::start().unwrap();
+    let config = robot.config();
+
+    let mut client = urd::RTDEClient::new(&config.robot.host, config.robot.ports.rtde).unwrap();
+    client.connect().unwrap();
+    client.negotiate_protocol_version(2).unwrap();
+    let variables = ["actual_TCP_pose", "no_such_variable", "robot_mode"].map(String::from).to_vec();
+    let (_, missing) = client.setup_available_output_recipe(variables, 125.0).unwrap();
+    assert_eq!(missing, vec!["no_such_variable".to_string()]);
+    client.start_data_synchronization().unwrap();
+
+    let data = client.read_data_package().unwrap();
+    assert_eq!(data["actual_TCP_pose"], MOCK_TCP_POSE.to_vec());
+    assert_eq!(data["robot_mode"], vec![3.0]);
+}
+
+#[tokio::test]
+async fn test_var_get_reads_registers_back() {
+    let robot = MockRobot::start().unwrap();
+    let mut controller = RobotController::new(robot.config());
+    controller.initialize(false).await.unwrap();
+
+    let script = std::env::temp_dir().join(format!("urd-mock-var-{}.script", std::process::id()));
+    std::fs::write(&script, "@var set target p[0.1, -0.2, 0.3, 0, 3.1, 0]\n@var get target\n@var set count 3\n@var get count\n").unwrap();
+    let mut stream = CommandStream::new(controller);
+    stream.run_script(script.to_str().unwrap(), false).await.unwrap();
+    std::fs::remove_file(&script).ok();
+
+    // The readback writes each entry, then the marker the RTDE read checks for
+    let sent = robot.interpreter_commands();
+    assert!(sent.contains(&"target = p[0.1,-0.2,0.3,0,3.1,0]".to_string()));
+    let readback = sent.iter().find(|c| c.starts_with("write_output_float_register(18, target[0])")).unwrap();
+    assert!(readback.ends_with("write_output_integer_register(23, 1)"), "{}", readback);
+    assert!(sent.contains(&"write_output_float_register(18, count) write_output_integer_register(23, 2)".to_string()));
+
+    // Both reads found their marker
+    let failures: Vec<String> = urd::json_output::recent_errors(100)
+        .into_iter()
+        .map(|event| event.error)
+        .filter(|error| error.starts_with("@var failed"))
+        .collect();
+    assert!(failures.is_empty(), "{:?}", failures);
+}